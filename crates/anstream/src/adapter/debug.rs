@@ -0,0 +1,503 @@
+use std::borrow::Cow;
+
+use anstyle::Style;
+use anstyle_parse::sgr::SgrState;
+use anstyle_parse::DefaultCharAccumulator;
+use anstyle_parse::Encoder;
+use anstyle_parse::Event;
+use anstyle_parse::Events;
+use anstyle_parse::EventsIter;
+
+/// Render escape sequences in `bytes` as readable tags
+///
+/// This is meant for comparing styled output, like in snapshot tests, where raw escape
+/// sequences make for unreadable diffs.
+///
+/// For non-contiguous data, see [`DebugBytes`].  To turn the tags back into escape sequences,
+/// see [`undebug_str`].
+///
+/// # Example
+///
+/// ```rust
+/// let debug = anstream::adapter::debug_bytes(b"\x1b[1;31merror:\x1b[0m [E0308] \x1b[2Jdone");
+/// assert_eq!(debug, r"[bold red]error:[/] \[E0308] \e\[2Jdone");
+/// ```
+pub fn debug_bytes(bytes: &[u8]) -> String {
+    let mut state = DebugBytes::new();
+    let mut debug = String::with_capacity(bytes.len());
+    for segment in state.debug_next(bytes) {
+        debug.push_str(&segment);
+    }
+    debug
+}
+
+/// Incrementally render escape sequences as readable tags for non-contiguous data
+///
+/// Text is passed through with `\` and `[` escaped as `\\` and `\[`.
+///
+/// Changes in style are rendered as tags:
+/// - `[bold red on blue]` starts a style, see [`undebug_str`] for the syntax
+/// - `[/]` ends a style
+///
+/// OSC 8 hyperlinks are rendered as `[link=URL]` and `[/link]`, dropping any parameters.
+///
+/// Other escape sequences and control characters, besides `\n` and `\t`, are written out with
+/// `\e` for ESC, `\r` for carriage return, and `\xNN` for the rest, like `\e[2J` or `\x07`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugBytes {
+    events: Events<DefaultCharAccumulator>,
+    tags: Tags,
+}
+
+impl DebugBytes {
+    /// Initial state
+    pub fn new() -> Self {
+        Self {
+            events: Events::new(),
+            tags: Tags {
+                sgr: SgrState::new(),
+                encoder: Encoder::new(Vec::new()),
+            },
+        }
+    }
+
+    /// Render the next segment of data
+    pub fn debug_next<'s>(&'s mut self, bytes: &'s [u8]) -> DebugBytesIter<'s> {
+        DebugBytesIter {
+            events: self.events.parse_next(bytes),
+            tags: &mut self.tags,
+        }
+    }
+}
+
+impl Default for DebugBytes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// See [`DebugBytes`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct DebugBytesIter<'s> {
+    events: EventsIter<'s, DefaultCharAccumulator>,
+    tags: &'s mut Tags,
+}
+
+impl<'s> Iterator for DebugBytesIter<'s> {
+    type Item = Cow<'s, str>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for event in self.events.by_ref() {
+            if let Some(debug) = self.tags.render(event) {
+                return Some(debug);
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Tags {
+    sgr: SgrState,
+    encoder: Encoder<Vec<u8>>,
+}
+
+impl Tags {
+    fn render<'s>(&mut self, event: Event<'s>) -> Option<Cow<'s, str>> {
+        let mut debug = String::new();
+        match event {
+            Event::Print(text) => {
+                if !text.contains(['\\', '[']) {
+                    return Some(text);
+                }
+                escape_text(&text, &mut debug);
+            }
+            Event::Csi {
+                params,
+                intermediates,
+                ignore: false,
+                action,
+            } if matches!((&intermediates[..], action), ([], b'm') | ([b'!'], b'p')) => {
+                let before = self.sgr.clone();
+                self.sgr
+                    .csi_dispatch(&params, &intermediates, false, action);
+                self.write_transition(&before, &mut debug);
+            }
+            Event::Osc {
+                params,
+                bell_terminated,
+            } if params.len() >= 2 && params[0] == b"8" => {
+                let before = self.sgr.clone();
+                let slices = params.iter().map(Vec::as_slice).collect::<Vec<_>>();
+                self.sgr.osc_dispatch(&slices, bell_terminated);
+                self.write_transition(&before, &mut debug);
+            }
+            // A string terminator, which is written with the string it ends
+            Event::Esc {
+                intermediates,
+                byte: b'\\',
+                ..
+            } if intermediates.is_empty() => {}
+            event => self.encode(&event),
+        }
+
+        let encoded = std::mem::take(self.encoder.as_inner_mut());
+        escape_bytes(&encoded, &mut debug);
+        (!debug.is_empty()).then_some(Cow::Owned(debug))
+    }
+
+    fn encode(&mut self, event: &Event<'_>) {
+        self.encoder.encode(event);
+    }
+
+    fn write_transition(&self, before: &SgrState, debug: &mut String) {
+        use std::fmt::Write as _;
+
+        let (before_style, after_style) = (before.style(), self.sgr.style());
+        if before_style != after_style {
+            if before_style != Style::new() {
+                debug.push_str("[/]");
+            }
+            if after_style != Style::new() {
+                debug.push('[');
+                let _ = write!(debug, "{}", after_style.describe());
+                debug.push(']');
+            }
+        }
+        if before.hyperlink() != self.sgr.hyperlink() {
+            match self.sgr.hyperlink() {
+                Some(url) => {
+                    debug.push_str("[link=");
+                    escape_tag(url, debug);
+                    debug.push(']');
+                }
+                None => debug.push_str("[/link]"),
+            }
+        }
+    }
+}
+
+fn escape_text(text: &str, debug: &mut String) {
+    for c in text.chars() {
+        if matches!(c, '\\' | '[') {
+            debug.push('\\');
+        }
+        debug.push(c);
+    }
+}
+
+fn escape_tag(text: &str, debug: &mut String) {
+    for c in text.chars() {
+        if matches!(c, '\\' | ']') {
+            debug.push('\\');
+        }
+        debug.push(c);
+    }
+}
+
+fn escape_bytes(mut bytes: &[u8], debug: &mut String) {
+    use std::fmt::Write as _;
+
+    while !bytes.is_empty() {
+        let (valid, invalid) = match std::str::from_utf8(bytes) {
+            Ok(valid) => (valid, &[][..]),
+            Err(err) => {
+                let (valid, rest) = bytes.split_at(err.valid_up_to());
+                let invalid_len = err.error_len().unwrap_or(rest.len());
+                let valid = std::str::from_utf8(valid).expect("checked by `valid_up_to`");
+                (valid, &rest[..invalid_len])
+            }
+        };
+        for c in valid.chars() {
+            match c {
+                '\x1b' => debug.push_str(r"\e"),
+                '\r' => debug.push_str(r"\r"),
+                '\n' | '\t' => debug.push(c),
+                '\\' | '[' => {
+                    debug.push('\\');
+                    debug.push(c);
+                }
+                c if c.is_ascii_control() => {
+                    let _ = write!(debug, r"\x{:02x}", c as u8);
+                }
+                c => debug.push(c),
+            }
+        }
+        for byte in invalid {
+            let _ = write!(debug, r"\x{byte:02x}");
+        }
+        bytes = &bytes[valid.len() + invalid.len()..];
+    }
+}
+
+/// Turn the tags from [`DebugBytes`] back into escape sequences
+///
+/// The syntax is:
+/// - `\\`, `\[`, `\e` (ESC), `\r`, and `\xNN` (a byte) escapes
+/// - `[STYLE]` selects a style, written as a single SGR sequence
+/// - `[/]` resets the style
+/// - `[link=URL]` and `[/link]` start and end an OSC 8 hyperlink, with `\\` and `\]` escapes
+///   within `URL`
+///
+/// `STYLE` is the text syntax from [`Style::from_str`][std::str::FromStr::from_str], like
+/// `bold red on blue underline:curly:208`.
+///
+/// # Example
+///
+/// ```rust
+/// let ansi = anstream::adapter::undebug_str(r"[bold red]error:[/] \[E0308] \e\[2Jdone").unwrap();
+/// assert_eq!(ansi, b"\x1b[1;31merror:\x1b[0m [E0308] \x1b[2Jdone");
+/// ```
+pub fn undebug_str(debug: &str) -> Result<Vec<u8>, UndebugError> {
+    let mut ansi = Vec::with_capacity(debug.len());
+    let mut chars = debug.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '\\' => {
+                let Some((_, escaped)) = chars.next() else {
+                    return Err(UndebugError::new(offset, ErrorKind::UnterminatedEscape));
+                };
+                match escaped {
+                    '\\' | '[' => ansi.push(escaped as u8),
+                    'e' => ansi.push(b'\x1b'),
+                    'r' => ansi.push(b'\r'),
+                    'x' => {
+                        let hex = (chars.next(), chars.next());
+                        let byte = match hex {
+                            (Some((_, high)), Some((_, low)))
+                                if high.is_ascii_hexdigit() && low.is_ascii_hexdigit() =>
+                            {
+                                let hex = [high, low].iter().collect::<String>();
+                                u8::from_str_radix(&hex, 16).ok()
+                            }
+                            _ => None,
+                        };
+                        let Some(byte) = byte else {
+                            return Err(UndebugError::new(offset, ErrorKind::InvalidEscape));
+                        };
+                        ansi.push(byte);
+                    }
+                    _ => return Err(UndebugError::new(offset, ErrorKind::InvalidEscape)),
+                }
+            }
+            '[' => {
+                let mut tag = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, ']')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped @ ('\\' | ']'))) => tag.push(escaped),
+                            _ => return Err(UndebugError::new(offset, ErrorKind::InvalidEscape)),
+                        },
+                        Some((_, c)) => tag.push(c),
+                        None => return Err(UndebugError::new(offset, ErrorKind::UnterminatedTag)),
+                    }
+                }
+                write_tag(&tag, &mut ansi).map_err(|kind| UndebugError::new(offset, kind))?;
+            }
+            c => {
+                let mut buffer = [0; 4];
+                ansi.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            }
+        }
+    }
+    Ok(ansi)
+}
+
+fn write_tag(tag: &str, ansi: &mut Vec<u8>) -> Result<(), ErrorKind> {
+    if tag == "/" {
+        ansi.extend_from_slice(b"\x1b[0m");
+    } else if tag == "/link" {
+        ansi.extend_from_slice(b"\x1b]8;;\x1b\\");
+    } else if let Some(url) = tag.strip_prefix("link=") {
+        ansi.extend_from_slice(b"\x1b]8;;");
+        ansi.extend_from_slice(url.as_bytes());
+        ansi.extend_from_slice(b"\x1b\\");
+    } else {
+        let style = tag.parse::<Style>().map_err(|err| {
+            let word = tag.get(err.span()).unwrap_or_default().to_owned();
+            ErrorKind::InvalidStyle(err.kind(), word)
+        })?;
+        if style == Style::new() {
+            return Err(ErrorKind::EmptyTag);
+        }
+        // Unlike `Style::render`, a transition is a single SGR sequence, matching what was parsed
+        let sgr = Style::new().render_transition(style).to_string();
+        ansi.extend_from_slice(sgr.as_bytes());
+    }
+    Ok(())
+}
+
+/// Invalid input for [`undebug_str`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UndebugError {
+    offset: usize,
+    kind: ErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ErrorKind {
+    UnterminatedEscape,
+    InvalidEscape,
+    UnterminatedTag,
+    EmptyTag,
+    InvalidStyle(anstyle::ParseErrorKind, String),
+}
+
+impl UndebugError {
+    fn new(offset: usize, kind: ErrorKind) -> Self {
+        Self { offset, kind }
+    }
+
+    /// Byte offset of the escape or tag with the error
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl std::fmt::Display for UndebugError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            ErrorKind::UnterminatedEscape => f.write_str("unterminated escape"),
+            ErrorKind::InvalidEscape => f.write_str("invalid escape"),
+            ErrorKind::UnterminatedTag => f.write_str("unterminated tag"),
+            ErrorKind::EmptyTag => f.write_str("empty tag"),
+            ErrorKind::InvalidStyle(anstyle::ParseErrorKind::MissingColor, _) => {
+                write!(f, "{}", anstyle::ParseErrorKind::MissingColor)
+            }
+            ErrorKind::InvalidStyle(kind, word) => write!(f, "{kind} `{word}`"),
+        }?;
+        write!(f, " at byte {}", self.offset)
+    }
+}
+
+impl std::error::Error for UndebugError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Model verifying incremental parsing
+    fn debug_byte(bytes: &[u8]) -> String {
+        let mut state = DebugBytes::new();
+        let mut debug = String::new();
+        for byte in bytes {
+            for segment in state.debug_next(std::slice::from_ref(byte)) {
+                debug.push_str(&segment);
+            }
+        }
+        debug
+    }
+
+    #[test]
+    fn test_styles() {
+        assert_eq!(
+            debug_bytes(b"\x1b[1mbold\x1b[31mred\x1b[22mplain red\x1b[m"),
+            "[bold]bold[/][bold red]red[/][red]plain red[/]"
+        );
+        assert_eq!(
+            debug_bytes(b"\x1b[3;4:3;38;5;208;48;2;0;128;255;58:5:1mall\x1b[0m\x1b[0m"),
+            "[italic 208 on #0080ff underline:curly:1]all[/]"
+        );
+        assert_eq!(
+            debug_bytes(b"\x1b[91;40mbright\x1b[39;49m"),
+            "[bright_red on black]bright[/]"
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        assert_eq!(
+            debug_bytes(b"\x1b]8;id=1;https://example.com/[x]\x1b\\link\x1b]8;;\x07"),
+            r"[link=https://example.com/[x\]]link[/link]"
+        );
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(
+            debug_bytes(b"a\\b[c]\r\n\t\x07\x1b[2J\x1b]0;title\x07\x1b(B"),
+            "a\\\\b\\[c]\\r\n\t\\x07\\e\\[2J\\e]0;title\\x07\\e(B"
+        );
+        assert_eq!(debug_bytes(b"\x1b]0;\xff\x1b\\"), r"\e]0;\xff\e\\");
+        assert_eq!(debug_bytes(b"\x80"), r"\x80");
+        assert_eq!(debug_byte(b"\x80"), r"\x80");
+        assert_eq!(undebug_str(r"\x80").unwrap(), b"\x80");
+    }
+
+    #[test]
+    fn test_undebug() {
+        assert_eq!(
+            undebug_str("[bold]a[/][italic 208 on #0080ff underline:curly:1]b[/]").unwrap(),
+            b"\x1b[1ma\x1b[0m\x1b[3;4:3;38;5;208;48;2;0;128;255;58;5;1mb\x1b[0m"
+        );
+        assert_eq!(
+            undebug_str(r"[link=https://example.com/[x\]]link[/link]").unwrap(),
+            b"\x1b]8;;https://example.com/[x]\x1b\\link\x1b]8;;\x1b\\"
+        );
+        assert_eq!(
+            undebug_str(r"a\\b\[c]\r\x07\e\[2J\xff").unwrap(),
+            b"a\\b[c]\r\x07\x1b[2J\xff"
+        );
+    }
+
+    #[test]
+    fn test_undebug_errors() {
+        for (input, expected) in [
+            ("ab\\", "unterminated escape at byte 2"),
+            (r"\q", "invalid escape at byte 0"),
+            (r"\xf", "invalid escape at byte 0"),
+            (r"a\x+f", "invalid escape at byte 1"),
+            ("a[bold", "unterminated tag at byte 1"),
+            ("[]", "empty tag at byte 0"),
+            ("[bold on]", "expected a color after `on` at byte 0"),
+            (
+                "x[bold purple]",
+                "unknown effect or color `purple` at byte 1",
+            ),
+            ("[#12345]", "unknown effect or color `#12345` at byte 0"),
+            ("[256]", "unknown effect or color `256` at byte 0"),
+            (
+                "[underline:wavy:red]",
+                "unknown effect `underline:wavy:red` at byte 0",
+            ),
+        ] {
+            let actual = undebug_str(input).unwrap_err().to_string();
+            assert_eq!(actual, expected, "{input:?}");
+        }
+    }
+
+    proptest! {
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn debug_no_escapes(s in "[^\\\\\\[\\p{C}]*") {
+            assert_eq!(debug_bytes(s.as_bytes()), s);
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn debug_byte_matches(s in any::<Vec<u8>>()) {
+            assert_eq!(debug_byte(&s), debug_bytes(&s));
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn undebug_round_trips(s in any::<Vec<u8>>()) {
+            let debug = debug_bytes(&s);
+            let ansi = undebug_str(&debug).unwrap();
+            assert_eq!(debug_bytes(&ansi), debug);
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn undebug_styles_round_trip(r: u8, g: u8, b: u8, index: u8, s in "[a-z ]*") {
+            let input = format!("\x1b[1;38;2;{r};{g};{b};48;5;{index}m{s}\x1b[0m");
+            let debug = debug_bytes(input.as_bytes());
+            let ansi = undebug_str(&debug).unwrap();
+            assert_eq!(debug_bytes(&ansi), debug);
+        }
+    }
+}
@@ -0,0 +1,506 @@
+use std::borrow::Cow;
+
+use anstyle_parse::state::state_change;
+use anstyle_parse::state::Action;
+use anstyle_parse::state::State;
+
+use super::strip::Utf8Parser;
+
+/// Incrementally rewrite colors in non-contiguous data for terminals with fewer colors
+///
+/// Truecolor (`38;2;r;g;b`) and, when targeting 16 colors, 256-color (`38;5;n`) SGR parameters
+/// are replaced with the closest color the terminal supports.  All other content, including other
+/// SGR parameters, is passed through unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// let mut downsample = anstream::adapter::DownsampleBytes::xterm();
+/// let downsampled = downsample
+///     .downsample_next(b"\x1b[1;38;2;255;0;0mHello\x1b[0m")
+///     .map(|b| b.into_owned())
+///     .collect::<Vec<_>>()
+///     .concat();
+/// assert_eq!(downsampled, b"\x1b[1;38;5;196mHello\x1b[0m");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownsampleBytes {
+    target: Target,
+    state: State,
+    utf8parser: Utf8Parser,
+    /// The escape sequence currently being read, which might need rewriting
+    pending: Vec<u8>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Target {
+    Xterm,
+    Ansi(anstyle_lossy::palette::Palette),
+}
+
+impl DownsampleBytes {
+    /// Downsample to the 256 colors of [`anstyle::Ansi256Color`]
+    pub fn xterm() -> Self {
+        Self::with_target(Target::Xterm)
+    }
+
+    /// Downsample to the 16 colors of [`anstyle::AnsiColor`]
+    ///
+    /// `palette` is used for finding the closest color.  See [`anstyle_lossy::palette`].
+    pub fn ansi(palette: anstyle_lossy::palette::Palette) -> Self {
+        Self::with_target(Target::Ansi(palette))
+    }
+
+    fn with_target(target: Target) -> Self {
+        Self {
+            target,
+            state: State::Ground,
+            utf8parser: Default::default(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Downsample the next segment of data
+    pub fn downsample_next<'s>(&'s mut self, bytes: &'s [u8]) -> DownsampleBytesIter<'s> {
+        DownsampleBytesIter {
+            bytes,
+            downsample: self,
+        }
+    }
+}
+
+/// See [`DownsampleBytes`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct DownsampleBytesIter<'s> {
+    bytes: &'s [u8],
+    downsample: &'s mut DownsampleBytes,
+}
+
+impl<'s> Iterator for DownsampleBytesIter<'s> {
+    type Item = Cow<'s, [u8]>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        next_bytes(&mut self.bytes, self.downsample)
+    }
+}
+
+fn next_bytes<'s>(bytes: &mut &'s [u8], downsample: &mut DownsampleBytes) -> Option<Cow<'s, [u8]>> {
+    if bytes.is_empty() {
+        return None;
+    }
+
+    if downsample.pending.is_empty() {
+        // Pass through everything up to the next escape sequence
+        let offset = bytes
+            .iter()
+            .copied()
+            .position(|b| {
+                let next_state = advance(downsample, b);
+                is_escape(next_state)
+            })
+            .unwrap_or(bytes.len());
+        let (passthrough, next) = bytes.split_at(offset);
+        if !next.is_empty() {
+            downsample.pending.push(next[0]);
+            *bytes = &next[1..];
+        } else {
+            *bytes = next;
+        }
+        if !passthrough.is_empty() {
+            return Some(Cow::Borrowed(passthrough));
+        }
+    }
+
+    while let Some((byte, remainder)) = bytes.split_first() {
+        let byte = *byte;
+        if byte == ESC {
+            // Abandon any partial sequence, starting over with this one
+            let abandoned = std::mem::take(&mut downsample.pending);
+            advance(downsample, byte);
+            *bytes = remainder;
+            downsample.pending.push(byte);
+            if !abandoned.is_empty() {
+                return Some(Cow::Owned(abandoned));
+            }
+            continue;
+        }
+
+        let previous_state = downsample.state;
+        let next_state = advance(downsample, byte);
+        if is_escape(next_state) {
+            *bytes = remainder;
+            downsample.pending.push(byte);
+            continue;
+        }
+
+        *bytes = remainder;
+        let mut sequence = std::mem::take(&mut downsample.pending);
+        sequence.push(byte);
+        let (_, action) = state_change(previous_state, byte);
+        if action == Action::CsiDispatch && byte == b'm' {
+            if let Some(rewritten) = rewrite_sgr(&sequence, downsample.target) {
+                sequence = rewritten;
+            }
+        }
+        return Some(Cow::Owned(sequence));
+    }
+
+    None
+}
+
+const ESC: u8 = 0x1b;
+
+fn advance(downsample: &mut DownsampleBytes, byte: u8) -> State {
+    if downsample.state == State::Utf8 {
+        if downsample.utf8parser.add(byte) {
+            downsample.state = State::Ground;
+        }
+    } else {
+        let (next_state, _) = state_change(downsample.state, byte);
+        if next_state != State::Anywhere {
+            downsample.state = next_state;
+        }
+        if downsample.state == State::Utf8 {
+            downsample.utf8parser.add(byte);
+        }
+    }
+    downsample.state
+}
+
+fn is_escape(state: State) -> bool {
+    matches!(
+        state,
+        State::Escape
+            | State::CsiEntry
+            | State::CsiParam
+            | State::CsiIntermediate
+            | State::CsiIgnore
+    )
+}
+
+/// Rewrite the colors within an SGR sequence, returning `None` if nothing was changed
+fn rewrite_sgr(sequence: &[u8], target: Target) -> Option<Vec<u8>> {
+    const INTRODUCER: &[u8] = b"\x1b[";
+    let params = sequence.strip_prefix(INTRODUCER)?.strip_suffix(b"m")?;
+    if !params
+        .iter()
+        .all(|b| b.is_ascii_digit() || *b == b';' || *b == b':')
+    {
+        return None;
+    }
+    let params = std::str::from_utf8(params).ok()?;
+
+    let mut changed = false;
+    let mut rewritten = Vec::new();
+    let mut remaining = params.split(';');
+    while let Some(param) = remaining.next() {
+        if param.contains(':') {
+            match rewrite_subparams(param, target) {
+                Some(replacement) => {
+                    changed = true;
+                    rewritten.push(replacement);
+                }
+                None => rewritten.push(param.to_owned()),
+            }
+            continue;
+        }
+
+        let Some(kind) = ColorKind::from_param(param) else {
+            rewritten.push(param.to_owned());
+            continue;
+        };
+        let mut lookahead = remaining.clone();
+        let color = match lookahead.next() {
+            Some("5") => lookahead.next().and_then(parse_index).map(Color::Xterm),
+            Some("2") => {
+                let r = lookahead.next().and_then(parse_index);
+                let g = lookahead.next().and_then(parse_index);
+                let b = lookahead.next().and_then(parse_index);
+                match (r, g, b) {
+                    (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let Some(color) = color else {
+            rewritten.push(param.to_owned());
+            continue;
+        };
+        remaining = lookahead;
+        match downsample_color(kind, color, target, ';') {
+            Some(replacement) => {
+                changed = true;
+                rewritten.push(replacement);
+            }
+            None => rewritten.push(color.to_param(kind, ';')),
+        }
+    }
+
+    if !changed {
+        return None;
+    }
+
+    let mut sequence = INTRODUCER.to_vec();
+    sequence.extend_from_slice(rewritten.join(";").as_bytes());
+    sequence.push(b'm');
+    Some(sequence)
+}
+
+/// Rewrite a `:`-separated color, like `38:2::r:g:b` or `38:5:n`
+fn rewrite_subparams(param: &str, target: Target) -> Option<String> {
+    let mut subparams = param.split(':');
+    let kind = ColorKind::from_param(subparams.next()?)?;
+    let values = subparams.collect::<Vec<_>>();
+    let color = match values.as_slice() {
+        ["5", index] => Color::Xterm(parse_index(index)?),
+        // With or without the colorspace ID
+        ["2", _, r, g, b, ..] | ["2", r, g, b] => {
+            Color::Rgb(parse_index(r)?, parse_index(g)?, parse_index(b)?)
+        }
+        _ => return None,
+    };
+    downsample_color(kind, color, target, ':')
+}
+
+fn parse_index(value: &str) -> Option<u8> {
+    if value.is_empty() {
+        Some(0)
+    } else {
+        value.parse().ok()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ColorKind {
+    Fg,
+    Bg,
+    Underline,
+}
+
+impl ColorKind {
+    fn from_param(param: &str) -> Option<Self> {
+        match param {
+            "38" => Some(Self::Fg),
+            "48" => Some(Self::Bg),
+            "58" => Some(Self::Underline),
+            _ => None,
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            Self::Fg => 38,
+            Self::Bg => 48,
+            Self::Underline => 58,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Color {
+    Xterm(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    fn to_param(self, kind: ColorKind, separator: char) -> String {
+        let code = kind.code();
+        match self {
+            Self::Xterm(index) => format!("{code}{separator}5{separator}{index}"),
+            Self::Rgb(r, g, b) => {
+                format!("{code}{separator}2{separator}{r}{separator}{g}{separator}{b}")
+            }
+        }
+    }
+}
+
+/// Convert `color` to fit `target`, returning `None` if it already fits
+fn downsample_color(
+    kind: ColorKind,
+    color: Color,
+    target: Target,
+    separator: char,
+) -> Option<String> {
+    let code = kind.code();
+    match (target, color) {
+        (Target::Xterm, Color::Xterm(_)) => None,
+        (Target::Xterm, Color::Rgb(r, g, b)) => {
+            let index = anstyle_lossy::rgb_to_xterm(anstyle::RgbColor(r, g, b)).index();
+            Some(format!("{code}{separator}5{separator}{index}"))
+        }
+        (Target::Ansi(palette), color) => {
+            let ansi = match color {
+                Color::Xterm(index) => {
+                    anstyle_lossy::xterm_to_ansi(anstyle::Ansi256Color(index), palette)
+                }
+                Color::Rgb(r, g, b) => {
+                    anstyle_lossy::rgb_to_ansi(anstyle::RgbColor(r, g, b), palette)
+                }
+            };
+            let index = anstyle::Ansi256Color::from_ansi(ansi).index();
+            let (bright, index) = if 8 <= index {
+                (true, index - 8)
+            } else {
+                (false, index)
+            };
+            let param = match (kind, bright) {
+                (ColorKind::Fg, false) => format!("{}", 30 + index),
+                (ColorKind::Fg, true) => format!("{}", 90 + index),
+                (ColorKind::Bg, false) => format!("{}", 40 + index),
+                (ColorKind::Bg, true) => format!("{}", 100 + index),
+                // There is no 16-color form for underline colors
+                (ColorKind::Underline, _) => {
+                    let index = anstyle::Ansi256Color::from_ansi(ansi).index();
+                    if color == Color::Xterm(index) {
+                        return None;
+                    }
+                    format!("{code}{separator}5{separator}{index}")
+                }
+            };
+            Some(param)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn downsample(mut state: DownsampleBytes, bytes: &[u8]) -> String {
+        let downsampled = state
+            .downsample_next(bytes)
+            .map(|b| b.into_owned())
+            .collect::<Vec<_>>()
+            .concat();
+        String::from_utf8(downsampled).unwrap()
+    }
+
+    /// Model verifying incremental parsing
+    fn downsample_byte(mut state: DownsampleBytes, bytes: &[u8]) -> Vec<u8> {
+        let mut downsampled = Vec::new();
+        for byte in bytes {
+            for segment in state.downsample_next(std::slice::from_ref(byte)) {
+                downsampled.extend_from_slice(&segment);
+            }
+        }
+        downsampled
+    }
+
+    fn vga() -> DownsampleBytes {
+        DownsampleBytes::ansi(anstyle_lossy::palette::VGA)
+    }
+
+    #[test]
+    fn test_xterm_rgb() {
+        let state = DownsampleBytes::xterm();
+        assert_eq!(
+            downsample(state.clone(), b"\x1b[38;2;255;0;0mred\x1b[0m"),
+            "\x1b[38;5;196mred\x1b[0m"
+        );
+        assert_eq!(
+            downsample(state.clone(), b"\x1b[1;48;2;0;0;0;4mbold\x1b[0m"),
+            "\x1b[1;48;5;16;4mbold\x1b[0m"
+        );
+        assert_eq!(
+            downsample(state.clone(), b"\x1b[58:2::255:255:255mline"),
+            "\x1b[58:5:231mline"
+        );
+        assert_eq!(
+            downsample(state, b"\x1b[38:2:255:0:0mred"),
+            "\x1b[38:5:196mred"
+        );
+    }
+
+    #[test]
+    fn test_xterm_passthrough() {
+        let state = DownsampleBytes::xterm();
+        for input in [
+            "plain",
+            "\x1b[38;5;100mxterm\x1b[0m",
+            "\x1b[31mred\x1b[0m",
+            "\x1b[38;2;255mtruncated",
+            "\x1b[?25h\x1b[2J",
+            "\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\",
+            "漢字\x1b[m",
+        ] {
+            assert_eq!(downsample(state.clone(), input.as_bytes()), input);
+        }
+    }
+
+    #[test]
+    fn test_ansi() {
+        let state = vga();
+        assert_eq!(
+            downsample(state.clone(), b"\x1b[38;2;255;80;80mred"),
+            "\x1b[91mred"
+        );
+        assert_eq!(
+            downsample(state.clone(), b"\x1b[48;2;170;0;0mred"),
+            "\x1b[41mred"
+        );
+        assert_eq!(
+            downsample(state.clone(), b"\x1b[38;5;12mblue"),
+            "\x1b[94mblue"
+        );
+        assert_eq!(
+            downsample(state.clone(), b"\x1b[48;5;0mblack"),
+            "\x1b[40mblack"
+        );
+        assert_eq!(
+            downsample(state.clone(), b"\x1b[58;2;255;0;0mred"),
+            "\x1b[58;5;1mred"
+        );
+        assert_eq!(downsample(state, b"\x1b[58;5;9mred"), "\x1b[58;5;9mred");
+    }
+
+    #[test]
+    fn test_abandoned_sequence() {
+        let state = DownsampleBytes::xterm();
+        assert_eq!(
+            downsample(state, b"\x1b[38\x1b[38;2;255;0;0mred"),
+            "\x1b[38\x1b[38;5;196mred"
+        );
+    }
+
+    #[test]
+    fn test_split_sequence() {
+        let mut state = DownsampleBytes::xterm();
+        let mut downsampled = Vec::new();
+        for chunk in [&b"a\x1b[38;2;25"[..], b"5;0;0", b"mb"] {
+            for segment in state.downsample_next(chunk) {
+                downsampled.extend_from_slice(&segment);
+            }
+        }
+        assert_eq!(downsampled, b"a\x1b[38;5;196mb");
+    }
+
+    proptest! {
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn downsample_no_escapes(s in "\\PC*") {
+            assert_eq!(downsample(DownsampleBytes::xterm(), s.as_bytes()), s);
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn downsample_byte_matches(s in any::<Vec<u8>>()) {
+            let mut expected = Vec::new();
+            let mut state = vga();
+            for segment in state.downsample_next(&s) {
+                expected.extend_from_slice(&segment);
+            }
+            assert_eq!(downsample_byte(vga(), &s), expected);
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn downsample_preserves_text(r: u8, g: u8, b: u8, s in "\\PC*") {
+            let styled = format!("\x1b[38;2;{r};{g};{b}m{s}\x1b[0m");
+            let downsampled = downsample(vga(), styled.as_bytes());
+            assert_eq!(super::super::strip_str(&downsampled).to_string(), s);
+        }
+    }
+}
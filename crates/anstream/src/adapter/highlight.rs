@@ -0,0 +1,183 @@
+use std::ops::Range;
+
+use anstyle::Style;
+
+use super::strip_str;
+use super::wrap::StateCapture;
+
+/// Apply styles to ranges of the printable content of a `&str`
+///
+/// The ranges are byte offsets into the stripped text, like from running a regex over
+/// [`strip_str`], and they are mapped back to `data` with [`StrippedStr::offsets`][super::StrippedStr::offsets].
+/// The ranges must be sorted and not overlap; any part of a range overlapping an earlier one is
+/// skipped.
+///
+/// The existing escapes in `data` are preserved.  A highlight's style is re-applied after any
+/// escape within it and, at its end, the style from `data` is restored.
+///
+/// # Panics
+///
+/// If a range does not fall on a `char` boundary of the printable content
+///
+/// # Example
+///
+/// ```rust
+/// let styled_text = "\x1b[32mfoo\x1b[m bar";
+/// let highlight = anstyle::AnsiColor::Yellow.on_default().invert();
+/// let highlighted = anstream::adapter::highlight_str(styled_text, &[(2..5, highlight)]);
+/// assert_eq!(
+///     highlighted,
+///     "\x1b[32mfo\x1b[7m\x1b[33mo\x1b[m\x1b[7m\x1b[33m b\x1b[0mar"
+/// );
+/// ```
+pub fn highlight_str(data: &str, highlights: &[(Range<usize>, Style)]) -> String {
+    let mut highlighter = Highlighter {
+        highlighted: String::with_capacity(data.len()),
+        state: StateCapture::default(),
+        highlights: highlights.iter(),
+        active: None,
+    };
+
+    let mut offset = 0;
+    for (original, stripped) in strip_str(data).offsets() {
+        highlighter.escape(&data[offset..original.start]);
+        offset = original.end;
+
+        let printable = &data[original];
+        let mut start = 0;
+        while start < printable.len() {
+            let end = highlighter.next_boundary(stripped.start + start, stripped.end);
+            highlighter.printable(&printable[start..end - stripped.start], end);
+            start = end - stripped.start;
+        }
+    }
+    highlighter.close();
+    highlighter.highlighted.push_str(&data[offset..]);
+    highlighter.highlighted
+}
+
+struct Highlighter<'h> {
+    highlighted: String,
+    /// The state from the original text
+    state: StateCapture,
+    highlights: std::slice::Iter<'h, (Range<usize>, Style)>,
+    active: Option<&'h (Range<usize>, Style)>,
+}
+
+impl Highlighter<'_> {
+    fn escape(&mut self, escape: &str) {
+        if escape.is_empty() {
+            return;
+        }
+        self.highlighted.push_str(escape);
+        self.state.advance(escape);
+        if let Some((_, style)) = self.active {
+            self.open(*style);
+        }
+    }
+
+    /// Find where the next highlight starts or ends, opening any highlight starting at `start`
+    fn next_boundary(&mut self, start: usize, end: usize) -> usize {
+        if let Some((range, _)) = self.active {
+            return range.end.min(end);
+        }
+
+        while let Some((range, _)) = self.highlights.as_slice().first() {
+            if range.end <= start || range.is_empty() {
+                self.highlights.next();
+            } else {
+                break;
+            }
+        }
+        match self.highlights.as_slice().first() {
+            Some((range, style)) if range.start <= start => {
+                self.active = self.highlights.next();
+                self.open(*style);
+                range.end.min(end)
+            }
+            Some((range, _)) => range.start.min(end),
+            None => end,
+        }
+    }
+
+    fn printable(&mut self, printable: &str, end: usize) {
+        self.highlighted.push_str(printable);
+        if let Some((range, _)) = self.active {
+            if range.end <= end {
+                self.close();
+            }
+        }
+    }
+
+    fn open(&mut self, style: Style) {
+        use std::fmt::Write as _;
+
+        let _ = write!(self.highlighted, "{}", style.render());
+    }
+
+    fn close(&mut self) {
+        use std::fmt::Write as _;
+
+        if self.active.take().is_some() {
+            let _ = write!(
+                self.highlighted,
+                "{}{}",
+                anstyle::Reset.render(),
+                self.state.style().render()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    const RED: Style = anstyle::AnsiColor::Red.on_default();
+
+    #[test]
+    fn test_highlight_str_plain() {
+        let highlighted = highlight_str("foo bar baz", &[(0..3, RED), (4..7, RED)]);
+        assert_eq!(highlighted, "\x1b[31mfoo\x1b[0m \x1b[31mbar\x1b[0m baz");
+    }
+
+    #[test]
+    fn test_highlight_str_restores_style() {
+        let highlighted = highlight_str("\x1b[1mfoo bar\x1b[0m", &[(2..5, RED)]);
+        assert_eq!(highlighted, "\x1b[1mfo\x1b[31mo b\x1b[0m\x1b[1mar\x1b[0m");
+    }
+
+    #[test]
+    fn test_highlight_str_across_escapes() {
+        let highlighted = highlight_str("a\x1b]8;;url\x1b\\b\x1b]8;;\x1b\\c", &[(0..3, RED)]);
+        assert_eq!(
+            highlighted,
+            "\x1b[31ma\x1b]8;;url\x1b\\\x1b[31mb\x1b]8;;\x1b\\\x1b[31mc\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_highlight_str_skips_invalid() {
+        let highlighted =
+            highlight_str("foo", &[(1..2, RED), (0..2, RED), (2..2, RED), (2..9, RED)]);
+        assert_eq!(highlighted, "f\x1b[31mo\x1b[0m\x1b[31mo\x1b[0m");
+    }
+
+    proptest! {
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn highlight_str_preserves_text(s in "\\PC*", start in 0..20_usize, len in 0..20_usize) {
+            let stripped = strip_str(&s).to_string();
+            let start = start.min(stripped.len());
+            let end = (start + len).min(stripped.len());
+            let highlights = if stripped.is_char_boundary(start) && stripped.is_char_boundary(end) {
+                vec![(start..end, RED)]
+            } else {
+                vec![]
+            };
+            let highlighted = highlight_str(&s, &highlights);
+            assert_eq!(strip_str(&highlighted).to_string(), stripped);
+        }
+    }
+}
@@ -1,15 +1,39 @@
 //! Gracefully degrade styled output
 
+mod debug;
+mod downsample;
+mod highlight;
+mod sanitize;
 mod strip;
+mod width;
 mod wincon;
+mod wrap;
 
+pub use debug::debug_bytes;
+pub use debug::undebug_str;
+pub use debug::DebugBytes;
+pub use debug::DebugBytesIter;
+pub use debug::UndebugError;
+pub use downsample::DownsampleBytes;
+pub use downsample::DownsampleBytesIter;
+pub use highlight::highlight_str;
+pub use sanitize::SanitizeBytes;
+pub use sanitize::SanitizeBytesIter;
+pub use sanitize::SanitizePolicy;
+pub use sanitize::SequenceClass;
 pub use strip::strip_bytes;
 pub use strip::strip_str;
 pub use strip::StripBytes;
 pub use strip::StripBytesIter;
 pub use strip::StripStr;
 pub use strip::StripStrIter;
+pub use strip::StripStrOffsets;
 pub use strip::StrippedBytes;
 pub use strip::StrippedStr;
+pub use strip::StrippedStrOffsets;
+pub use width::str_width;
+pub use width::StrWidth;
 pub use wincon::WinconBytes;
 pub use wincon::WinconBytesIter;
+pub use wrap::truncate_str;
+pub use wrap::wrap_str;
@@ -0,0 +1,505 @@
+use std::borrow::Cow;
+
+use anstyle_parse::DefaultCharAccumulator;
+use anstyle_parse::Encoder;
+use anstyle_parse::Event;
+use anstyle_parse::Events;
+use anstyle_parse::EventsIter;
+use anstyle_parse::Params;
+
+/// Incrementally neutralize escape sequences in untrusted, non-contiguous data
+///
+/// Printable text is passed through while escape sequences and control characters are only kept
+/// when their [`SequenceClass`] is allowed by the [`SanitizePolicy`].  Kept sequences are
+/// re-encoded, so malformed sequences can't leak through.
+///
+/// Whitespace control characters (`\t`, `\n`, `\x0c`, `\r`) are always kept.
+///
+/// # Example
+///
+/// ```rust
+/// use anstream::adapter::SanitizeBytes;
+/// use anstream::adapter::SanitizePolicy;
+/// use anstream::adapter::SequenceClass;
+///
+/// let policy = SanitizePolicy::new().allow(SequenceClass::Hyperlink);
+/// let mut sanitize = SanitizeBytes::new(policy);
+/// let sanitized = sanitize
+///     .sanitize_next(b"\x1b]52;c;cm0gLXJmIH4K\x07\x1b[2J\x1b[1mfile\x1b[0m")
+///     .map(|b| b.into_owned())
+///     .collect::<Vec<_>>()
+///     .concat();
+/// assert_eq!(sanitized, b"\x1b[1mfile\x1b[m");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SanitizeBytes {
+    events: Events<DefaultCharAccumulator>,
+    filter: Filter,
+}
+
+impl SanitizeBytes {
+    /// Keep the sequences allowed by `policy`
+    pub fn new(policy: SanitizePolicy) -> Self {
+        Self {
+            events: Events::new(),
+            filter: Filter {
+                policy,
+                encoder: Encoder::new(Vec::new()),
+                keep_string: false,
+            },
+        }
+    }
+
+    /// The sequences being kept
+    #[inline]
+    pub fn policy(&self) -> SanitizePolicy {
+        self.filter.policy
+    }
+
+    /// Sanitize the next segment of data
+    pub fn sanitize_next<'s>(&'s mut self, bytes: &'s [u8]) -> SanitizeBytesIter<'s> {
+        SanitizeBytesIter {
+            events: self.events.parse_next(bytes),
+            filter: &mut self.filter,
+        }
+    }
+}
+
+impl Default for SanitizeBytes {
+    fn default() -> Self {
+        Self::new(SanitizePolicy::default())
+    }
+}
+
+/// See [`SanitizeBytes`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct SanitizeBytesIter<'s> {
+    events: EventsIter<'s, DefaultCharAccumulator>,
+    filter: &'s mut Filter,
+}
+
+impl<'s> Iterator for SanitizeBytesIter<'s> {
+    type Item = Cow<'s, [u8]>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for event in self.events.by_ref() {
+            if let Some(sanitized) = self.filter.sanitize(event) {
+                return Some(sanitized);
+            }
+        }
+        None
+    }
+}
+
+/// Which [`SequenceClass`]es [`SanitizeBytes`] keeps
+///
+/// # Example
+///
+/// ```rust
+/// use anstream::adapter::SanitizePolicy;
+/// use anstream::adapter::SequenceClass;
+///
+/// let policy = SanitizePolicy::new().allow(SequenceClass::Hyperlink);
+/// assert!(policy.is_allowed(SequenceClass::Sgr));
+/// assert!(policy.is_allowed(SequenceClass::Hyperlink));
+/// assert!(!policy.is_allowed(SequenceClass::Clipboard));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SanitizePolicy {
+    allowed: u16,
+}
+
+impl SanitizePolicy {
+    /// Keep styling ([`SequenceClass::Sgr`]) only
+    #[inline]
+    pub const fn new() -> Self {
+        Self::none().allow(SequenceClass::Sgr)
+    }
+
+    /// Drop all escape sequences and non-whitespace control characters
+    #[inline]
+    pub const fn none() -> Self {
+        Self { allowed: 0 }
+    }
+
+    /// Keep sequences of `class`
+    #[must_use]
+    #[inline]
+    pub const fn allow(self, class: SequenceClass) -> Self {
+        Self {
+            allowed: self.allowed | class.bit(),
+        }
+    }
+
+    /// Drop sequences of `class`
+    #[must_use]
+    #[inline]
+    pub const fn deny(self, class: SequenceClass) -> Self {
+        Self {
+            allowed: self.allowed & !class.bit(),
+        }
+    }
+
+    /// Whether sequences of `class` are kept
+    #[inline]
+    pub const fn is_allowed(self, class: SequenceClass) -> bool {
+        self.allowed & class.bit() != 0
+    }
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Kinds of escape sequences and control characters for [`SanitizePolicy`]
+///
+/// Each sequence belongs to exactly one class.  When a sequence fits several, like reading the
+/// clipboard with `OSC 52 ; c ; ?`, the riskiest one is used.
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SequenceClass {
+    /// Text styling with SGR, like `CSI 1 m`
+    Sgr,
+    /// Hyperlinks with OSC 8
+    Hyperlink,
+    /// Window and icon titles with OSC 0, 1, and 2
+    Title,
+    /// Writing to the clipboard with OSC 52
+    Clipboard,
+    /// Sequences the terminal replies to, writing to the input of the program
+    ///
+    /// This includes device attributes (`CSI c`), status reports (`CSI n`), mode reports
+    /// (`CSI $ p`), `?` OSC queries like `OSC 10 ; ?`, and `DCS $ q`.
+    Query,
+    /// Moving, saving, and restoring the cursor, like `CSI H`, `CSI A`, and `ESC 7`
+    Cursor,
+    /// Erasing, inserting, deleting, and scrolling text, like `CSI J` and `CSI K`
+    Edit,
+    /// Any other CSI sequence, like setting modes with `CSI ? 1049 h`
+    OtherCsi,
+    /// Any other OSC
+    OtherOsc,
+    /// Any other escape sequence, like selecting a character set with `ESC ( B`
+    OtherEsc,
+    /// Device control strings
+    Dcs,
+    /// SOS, PM, and APC strings, like kitty's graphics protocol
+    SosPmApc,
+    /// Control characters besides whitespace, like BEL, backspace, and DEL
+    Control,
+}
+
+impl SequenceClass {
+    const fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Filter {
+    policy: SanitizePolicy,
+    encoder: Encoder<Vec<u8>>,
+    /// Whether the content of the current DCS, SOS, PM, or APC string is kept
+    keep_string: bool,
+}
+
+impl Filter {
+    fn sanitize<'s>(&mut self, event: Event<'s>) -> Option<Cow<'s, [u8]>> {
+        match event {
+            Event::Print(text) => {
+                if self.policy.is_allowed(SequenceClass::Control) || !text.contains(is_control) {
+                    let text = match text {
+                        Cow::Borrowed(text) => Cow::Borrowed(text.as_bytes()),
+                        Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+                    };
+                    Some(text)
+                } else {
+                    let text = text.chars().filter(|c| !is_control(*c)).collect::<String>();
+                    (!text.is_empty()).then(|| Cow::Owned(text.into_bytes()))
+                }
+            }
+            event => {
+                if !self.keep(&event) {
+                    return None;
+                }
+                self.encoder.encode(&event);
+                let encoded = std::mem::take(self.encoder.as_inner_mut());
+                (!encoded.is_empty()).then_some(Cow::Owned(encoded))
+            }
+        }
+    }
+
+    fn keep(&mut self, event: &Event<'_>) -> bool {
+        let class = match event {
+            Event::Execute(byte) if byte.is_ascii_whitespace() => return true,
+            Event::Execute(_) => SequenceClass::Control,
+            Event::Csi {
+                params,
+                intermediates,
+                action,
+                ..
+            } => csi_class(params, intermediates, *action),
+            // A string terminator, which is written with the string it ends
+            Event::Esc {
+                intermediates,
+                byte: b'\\',
+                ..
+            } if intermediates.is_empty() => return false,
+            Event::Esc {
+                intermediates,
+                byte,
+                ..
+            } => esc_class(intermediates, *byte),
+            Event::Osc { params, .. } => osc_class(params),
+            Event::DcsHook {
+                intermediates,
+                action,
+                ..
+            } => {
+                self.keep_string = self.policy.is_allowed(dcs_class(intermediates, *action));
+                return self.keep_string;
+            }
+            Event::SosPmApcStart(_) => {
+                self.keep_string = self.policy.is_allowed(SequenceClass::SosPmApc);
+                return self.keep_string;
+            }
+            Event::DcsPut(_) | Event::DcsUnhook | Event::SosPmApcPut(_) | Event::SosPmApcEnd => {
+                return self.keep_string;
+            }
+            _ => return false,
+        };
+        self.policy.is_allowed(class)
+    }
+}
+
+fn csi_class(params: &Params, intermediates: &[u8], action: u8) -> SequenceClass {
+    let marker = intermediates
+        .first()
+        .copied()
+        .filter(|b| (0x3c..=0x3f).contains(b));
+    let first = params
+        .iter()
+        .next()
+        .and_then(|param| param.first())
+        .copied()
+        .unwrap_or(0);
+    match (marker, intermediates, action) {
+        // Device attributes and status reports
+        (_, _, b'c' | b'n') => SequenceClass::Query,
+        // DECRQM
+        (_, [.., b'$'], b'p') => SequenceClass::Query,
+        // XTVERSION
+        (Some(b'>'), _, b'q') => SequenceClass::Query,
+        // Kitty keyboard flags and XTQMODKEYS
+        (Some(b'?'), _, b'u' | b'm') => SequenceClass::Query,
+        // Window reports from XTWINOPS
+        (None, [], b't') if matches!(first, 11 | 13 | 14 | 15 | 16 | 18 | 19 | 20 | 21) => {
+            SequenceClass::Query
+        }
+        (None, [], b'm') => SequenceClass::Sgr,
+        (None, [], b'A'..=b'I' | b'Z' | b'`' | b'a' | b'd' | b'e' | b'f' | b's' | b'u') => {
+            SequenceClass::Cursor
+        }
+        (None, [], b'@' | b'J' | b'K' | b'L' | b'M' | b'P' | b'S' | b'T' | b'X' | b'b') => {
+            SequenceClass::Edit
+        }
+        // DECSED and DECSEL
+        (Some(b'?'), [_], b'J' | b'K') => SequenceClass::Edit,
+        _ => SequenceClass::OtherCsi,
+    }
+}
+
+fn esc_class(intermediates: &[u8], byte: u8) -> SequenceClass {
+    match (intermediates, byte) {
+        // DECID
+        ([], b'Z') => SequenceClass::Query,
+        // DECSC, DECRC, IND, NEL, RI
+        ([], b'7' | b'8' | b'D' | b'E' | b'M') => SequenceClass::Cursor,
+        _ => SequenceClass::OtherEsc,
+    }
+}
+
+fn osc_class(params: &[Vec<u8>]) -> SequenceClass {
+    if params.iter().skip(1).any(|param| param == b"?") {
+        return SequenceClass::Query;
+    }
+    match params.first().map(Vec::as_slice) {
+        Some(b"0" | b"1" | b"2") => SequenceClass::Title,
+        Some(b"8") => SequenceClass::Hyperlink,
+        Some(b"52") => SequenceClass::Clipboard,
+        _ => SequenceClass::OtherOsc,
+    }
+}
+
+fn dcs_class(intermediates: &[u8], action: u8) -> SequenceClass {
+    match (intermediates, action) {
+        // DECRQSS and XTGETTCAP
+        (b"$" | b"+", b'q') => SequenceClass::Query,
+        _ => SequenceClass::Dcs,
+    }
+}
+
+/// Characters a terminal may act on rather than print
+fn is_control(c: char) -> bool {
+    c == '\x7f' || ('\u{80}'..='\u{9f}').contains(&c)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn sanitize(policy: SanitizePolicy, bytes: &[u8]) -> Vec<u8> {
+        let mut state = SanitizeBytes::new(policy);
+        let sanitized = state
+            .sanitize_next(bytes)
+            .map(|b| b.into_owned())
+            .collect::<Vec<_>>();
+        sanitized.concat()
+    }
+
+    /// Model verifying incremental parsing
+    fn sanitize_byte(policy: SanitizePolicy, bytes: &[u8]) -> Vec<u8> {
+        let mut state = SanitizeBytes::new(policy);
+        let mut sanitized = Vec::new();
+        for byte in bytes {
+            for segment in state.sanitize_next(std::slice::from_ref(byte)) {
+                sanitized.extend_from_slice(&segment);
+            }
+        }
+        sanitized
+    }
+
+    fn all() -> SanitizePolicy {
+        [
+            SequenceClass::Sgr,
+            SequenceClass::Hyperlink,
+            SequenceClass::Title,
+            SequenceClass::Clipboard,
+            SequenceClass::Query,
+            SequenceClass::Cursor,
+            SequenceClass::Edit,
+            SequenceClass::OtherCsi,
+            SequenceClass::OtherOsc,
+            SequenceClass::OtherEsc,
+            SequenceClass::Dcs,
+            SequenceClass::SosPmApc,
+            SequenceClass::Control,
+        ]
+        .into_iter()
+        .fold(SanitizePolicy::none(), SanitizePolicy::allow)
+    }
+
+    #[test]
+    fn test_keeps_sgr() {
+        assert_eq!(
+            sanitize(SanitizePolicy::new(), b"\x1b[1;38:2::255:0:0mred\x1b[m\r\n"),
+            b"\x1b[1;38:2:0:255:0:0mred\x1b[m\r\n"
+        );
+        assert_eq!(
+            sanitize(SanitizePolicy::none(), b"\x1b[1mbold\x1b[m\t"),
+            b"bold\t"
+        );
+    }
+
+    #[test]
+    fn test_drops_dangerous() {
+        let policy = SanitizePolicy::new().allow(SequenceClass::Hyperlink);
+        for (input, expected) in [
+            (&b"\x1b]52;c;cm0gLXJmIH4K\x07ok"[..], &b"ok"[..]),
+            (b"\x1b]0;pwned\x1b\\ok", b"ok"),
+            (b"\x1b[2J\x1b[Hok", b"ok"),
+            (b"\x1b[6n\x1b[c\x1b[>qok", b"ok"),
+            (b"\x1bP$qm\x1b\\ok", b"ok"),
+            (b"\x1bPq#0;2;0;0;0#0~~\x1b\\ok", b"ok"),
+            (b"\x1b_Ga=q;AAAA\x1b\\ok", b"ok"),
+            (b"\x1b[?1049h\x1bcok", b"ok"),
+            (b"o\x08\x07\x7fk", b"ok"),
+            ("o\u{9b}2Jk".as_bytes(), b"o2Jk"),
+        ] {
+            assert_eq!(
+                sanitize(policy, input),
+                expected,
+                "{:?}",
+                String::from_utf8_lossy(input)
+            );
+        }
+    }
+
+    #[test]
+    fn test_hyperlinks() {
+        let input = b"\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x07";
+        assert_eq!(sanitize(SanitizePolicy::new(), input), b"link");
+        let policy = SanitizePolicy::new().allow(SequenceClass::Hyperlink);
+        assert_eq!(sanitize(policy, input), input);
+    }
+
+    #[test]
+    fn test_query_overrides() {
+        let policy = SanitizePolicy::none()
+            .allow(SequenceClass::Clipboard)
+            .allow(SequenceClass::OtherOsc);
+        assert_eq!(
+            sanitize(policy, b"\x1b]52;c;?\x07\x1b]10;?\x07\x1b]52;c;aGk=\x07"),
+            b"\x1b]52;c;aGk=\x07"
+        );
+    }
+
+    #[test]
+    fn test_keeps_allowed_strings() {
+        let policy = SanitizePolicy::none().allow(SequenceClass::SosPmApc);
+        let input = b"\x1b_Gf=100;AAAA\x1b\\ok\x1bP1$r0m\x1b\\";
+        assert_eq!(sanitize(policy, input), b"\x1b_Gf=100;AAAA\x1b\\ok");
+    }
+
+    #[test]
+    fn test_split_sequence() {
+        let mut state = SanitizeBytes::default();
+        let mut sanitized = Vec::new();
+        for chunk in [&b"a\x1b]52;c;"[..], b"aGk=\x07\x1b[3", b"1mb"] {
+            for segment in state.sanitize_next(chunk) {
+                sanitized.extend_from_slice(&segment);
+            }
+        }
+        assert_eq!(sanitized, b"a\x1b[31mb");
+    }
+
+    proptest! {
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn sanitize_no_escapes(s in "\\PC*") {
+            assert_eq!(sanitize(SanitizePolicy::none(), s.as_bytes()), s.as_bytes());
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn sanitize_byte_matches(s in any::<Vec<u8>>()) {
+            let policy = SanitizePolicy::new().allow(SequenceClass::Hyperlink);
+            assert_eq!(sanitize_byte(policy, &s), sanitize(policy, &s));
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn sanitize_none_matches_strip(s in any::<String>()) {
+            let expected = super::super::strip_str(&s)
+                .to_string()
+                .chars()
+                .filter(|c| !is_control(*c))
+                .collect::<String>();
+            let actual = sanitize(SanitizePolicy::none(), s.as_bytes());
+            assert_eq!(String::from_utf8(actual).unwrap(), expected);
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn sanitize_is_idempotent(s in any::<Vec<u8>>()) {
+            for policy in [SanitizePolicy::none(), SanitizePolicy::new(), all()] {
+                let sanitized = sanitize(policy, &s);
+                assert_eq!(sanitize(policy, &sanitized), sanitized);
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use anstyle_parse::state::state_change;
 use anstyle_parse::state::Action;
 use anstyle_parse::state::State;
@@ -7,7 +9,8 @@ use anstyle_parse::state::State;
 /// This can be used to take output from a program that includes escape sequences and write it
 /// somewhere that does not easily support them, such as a log file.
 ///
-/// For non-contiguous data, see [`StripStr`].
+/// For non-contiguous data, see [`StripStr`].  To map the printable content back to `data`,
+/// see [`StrippedStr::offsets`].
 ///
 /// # Example
 ///
@@ -28,6 +31,7 @@ pub fn strip_str(data: &str) -> StrippedStr<'_> {
 pub struct StrippedStr<'s> {
     bytes: &'s [u8],
     state: State,
+    offsets: Offsets,
 }
 
 impl<'s> StrippedStr<'s> {
@@ -36,9 +40,28 @@ impl<'s> StrippedStr<'s> {
         Self {
             bytes: data.as_bytes(),
             state: State::Ground,
+            offsets: Offsets::default(),
         }
     }
 
+    /// Map each printable segment to where it came from
+    ///
+    /// This yields `(original, stripped)` pairs of byte ranges for each printable segment, where
+    /// `original` is the range within the data passed to [`strip_str`] and `stripped` is the
+    /// range within the printable content.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let styled_text = "\x1b[32mfoo\x1b[m bar";
+    /// let offsets = anstream::adapter::strip_str(styled_text).offsets().collect::<Vec<_>>();
+    /// assert_eq!(offsets, [(5..8, 0..3), (11..15, 3..7)]);
+    /// ```
+    #[inline]
+    pub fn offsets(self) -> StrippedStrOffsets<'s> {
+        StrippedStrOffsets { inner: self }
+    }
+
     /// Create a [`String`] of the printable content
     #[inline]
     #[allow(clippy::inherent_to_string_shadow_display)] // Single-allocation implementation
@@ -57,6 +80,7 @@ impl std::fmt::Display for StrippedStr<'_> {
         let iter = Self {
             bytes: self.bytes,
             state: self.state,
+            offsets: self.offsets,
         };
         for printable in iter {
             printable.fmt(f)?;
@@ -70,7 +94,25 @@ impl<'s> Iterator for StrippedStr<'s> {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        next_str(&mut self.bytes, &mut self.state)
+        next_str_offsets(&mut self.bytes, &mut self.state, &mut self.offsets)
+            .map(|(printable, _)| printable)
+    }
+}
+
+/// See [`StrippedStr::offsets`]
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct StrippedStrOffsets<'s> {
+    inner: StrippedStr<'s>,
+}
+
+impl Iterator for StrippedStrOffsets<'_> {
+    type Item = OffsetPair;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let inner = &mut self.inner;
+        next_str_offsets(&mut inner.bytes, &mut inner.state, &mut inner.offsets)
+            .map(|(_, offsets)| offsets)
     }
 }
 
@@ -78,6 +120,7 @@ impl<'s> Iterator for StrippedStr<'s> {
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct StripStr {
     state: State,
+    offsets: Offsets,
 }
 
 impl StripStr {
@@ -91,6 +134,7 @@ impl StripStr {
         StripStrIter {
             bytes: data.as_bytes(),
             state: &mut self.state,
+            offsets: &mut self.offsets,
         }
     }
 }
@@ -100,6 +144,18 @@ impl StripStr {
 pub struct StripStrIter<'s> {
     bytes: &'s [u8],
     state: &'s mut State,
+    offsets: &'s mut Offsets,
+}
+
+impl<'s> StripStrIter<'s> {
+    /// Map each printable segment to where it came from
+    ///
+    /// Like [`StrippedStr::offsets`] except the ranges are relative to the start of all data
+    /// passed to [`StripStr::strip_next`].
+    #[inline]
+    pub fn offsets(self) -> StripStrOffsets<'s> {
+        StripStrOffsets { inner: self }
+    }
 }
 
 impl<'s> Iterator for StripStrIter<'s> {
@@ -107,10 +163,53 @@ impl<'s> Iterator for StripStrIter<'s> {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        next_str(&mut self.bytes, self.state)
+        next_str_offsets(&mut self.bytes, self.state, self.offsets).map(|(printable, _)| printable)
+    }
+}
+
+/// See [`StripStrIter::offsets`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct StripStrOffsets<'s> {
+    inner: StripStrIter<'s>,
+}
+
+impl Iterator for StripStrOffsets<'_> {
+    type Item = OffsetPair;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let inner = &mut self.inner;
+        next_str_offsets(&mut inner.bytes, inner.state, inner.offsets).map(|(_, offsets)| offsets)
     }
 }
 
+/// `(original, stripped)` byte ranges of a printable segment
+type OffsetPair = (Range<usize>, Range<usize>);
+
+/// Bytes consumed so far from the original and stripped data
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+struct Offsets {
+    original: usize,
+    stripped: usize,
+}
+
+#[inline]
+fn next_str_offsets<'s>(
+    bytes: &mut &'s [u8],
+    state: &mut State,
+    offsets: &mut Offsets,
+) -> Option<(&'s str, OffsetPair)> {
+    let len = bytes.len();
+    let printable = next_str(bytes, state);
+    offsets.original += len - bytes.len();
+    let printable = printable?;
+
+    let original = offsets.original - printable.len()..offsets.original;
+    let stripped = offsets.stripped..offsets.stripped + printable.len();
+    offsets.stripped = stripped.end;
+    Some((printable, (original, stripped)))
+}
+
 #[inline]
 fn next_str<'s>(bytes: &mut &'s [u8], state: &mut State) -> Option<&'s str> {
     let offset = bytes.iter().copied().position(|b| {
@@ -120,7 +219,12 @@ fn next_str<'s>(bytes: &mut &'s [u8], state: &mut State) -> Option<&'s str> {
         }
         is_printable_bytes(action, b)
     });
-    let (_, next) = bytes.split_at(offset.unwrap_or(bytes.len()));
+    let Some(offset) = offset else {
+        // Preserve `state` in case an escape sequence continues in the next segment
+        *bytes = &[];
+        return None;
+    };
+    let (_, next) = bytes.split_at(offset);
     *bytes = next;
     *state = State::Ground;
 
@@ -334,7 +438,7 @@ pub(crate) struct Utf8Parser {
 }
 
 impl Utf8Parser {
-    fn add(&mut self, byte: u8) -> bool {
+    pub(crate) fn add(&mut self, byte: u8) -> bool {
         let mut b = false;
         let mut receiver = VtUtf8Receiver(&mut b);
         self.utf8_parser.advance(&mut receiver, byte);
@@ -462,6 +566,43 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_strip_str_split_sequence() {
+        let mut state = StripStr::new();
+        assert_eq!(state.strip_next("a\x1b[3").collect::<Vec<_>>(), ["a"]);
+        assert_eq!(
+            state.strip_next("1").collect::<Vec<_>>(),
+            Vec::<&str>::new()
+        );
+        assert_eq!(state.strip_next("mb\x1b]8;;").collect::<Vec<_>>(), ["b"]);
+        assert_eq!(state.strip_next("url\x1b\\c").collect::<Vec<_>>(), ["c"]);
+    }
+
+    #[test]
+    fn test_strip_str_offsets() {
+        let s = "\x1b[1mfoo\x1b[0m bar\x1b]8;;url\x1b\\baz";
+        let offsets = strip_str(s).offsets().collect::<Vec<_>>();
+        assert_eq!(offsets, [(4..7, 0..3), (11..15, 3..7), (25..28, 7..10)]);
+    }
+
+    #[test]
+    fn test_strip_str_split_offsets() {
+        let mut state = StripStr::new();
+        assert_eq!(
+            state.strip_next("a\x1b[3").offsets().collect::<Vec<_>>(),
+            [(0..1, 0..1)]
+        );
+        assert_eq!(
+            state.strip_next("1mbc").offsets().collect::<Vec<_>>(),
+            [(6..8, 1..3)]
+        );
+        assert_eq!(state.strip_next("d").collect::<Vec<_>>(), ["d"]);
+        assert_eq!(
+            state.strip_next("\x1b[me").offsets().collect::<Vec<_>>(),
+            [(12..13, 4..5)]
+        );
+    }
+
     #[test]
     fn test_strip_str_handles_broken_sequence() {
         // valid utf8: \xc3\xb6 then \x1b then \xf0\x9f\x98\x80
@@ -480,6 +621,40 @@ mod test {
             assert_eq!(expected, actual);
         }
 
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn strip_str_offsets_map(s in "\\PC*") {
+            let stripped = strip_str(&s).to_string();
+            let mut end = 0;
+            for (original, range) in strip_str(&s).offsets() {
+                assert_eq!(&s[original], &stripped[range.clone()]);
+                assert_eq!(range.start, end);
+                end = range.end;
+            }
+            assert_eq!(end, stripped.len());
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn strip_char_offsets_match(s in "\\PC*") {
+            let mut state = StripStr::new();
+            let mut actual = Vec::new();
+            for (i, c) in s.char_indices() {
+                let c = &s[i..i + c.len_utf8()];
+                actual.extend(state.strip_next(c).offsets());
+            }
+            let expected = strip_str(&s).offsets().collect::<Vec<_>>();
+            let actual = actual
+                .into_iter()
+                .flat_map(|(original, stripped)| original.zip(stripped))
+                .collect::<Vec<_>>();
+            let expected = expected
+                .into_iter()
+                .flat_map(|(original, stripped)| original.zip(stripped))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, actual);
+        }
+
         #[test]
         #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
         fn strip_char_no_escapes(s in "\\PC*") {
@@ -0,0 +1,162 @@
+use unicode_width::UnicodeWidthStr as _;
+
+use super::StripStr;
+
+/// Measure the number of terminal cells a `&str` occupies, ignoring ANSI escapes
+///
+/// East Asian wide characters take up two cells while combining marks take up none.
+/// Control characters, like `\n` and `\t`, are treated as zero-width, so split lines before
+/// measuring them.
+///
+/// For non-contiguous data, see [`StrWidth`].
+///
+/// # Example
+///
+/// ```rust
+/// let styled_text = "\x1b[32m漢字\x1b[m \x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\";
+/// assert_eq!(anstream::adapter::str_width(styled_text), 9);
+/// ```
+#[inline]
+pub fn str_width(data: &str) -> usize {
+    let mut width = StrWidth::new();
+    width.measure_next(data);
+    width.width()
+}
+
+/// Incrementally measure the width of non-contiguous data
+///
+/// Escape sequences may be split across calls.  Character sequences that combine into a single
+/// glyph, like emoji with modifiers, are only measured as such when in the same call.
+///
+/// # Example
+///
+/// ```rust
+/// let mut width = anstream::adapter::StrWidth::new();
+/// width.measure_next("\x1b[3");
+/// width.measure_next("2mHello\x1b[m");
+/// assert_eq!(width.width(), 5);
+/// ```
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct StrWidth {
+    strip: StripStr,
+    width: usize,
+}
+
+impl StrWidth {
+    /// Initial state
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Measure the next segment of data, returning its width
+    pub fn measure_next(&mut self, data: &str) -> usize {
+        let mut width = 0;
+        for printable in self.strip.strip_next(data) {
+            width += printable_width(printable);
+        }
+        self.width += width;
+        width
+    }
+
+    /// Total width measured so far
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Restart measuring from zero, without losing track of any in-progress escape sequence
+    #[inline]
+    pub fn reset_width(&mut self) {
+        self.width = 0;
+    }
+}
+
+#[inline]
+fn printable_width(printable: &str) -> usize {
+    printable
+        .split(|c: char| c.is_ascii_control())
+        .map(|s| s.width())
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Model verifying incremental parsing
+    fn width_char(mut s: &str) -> usize {
+        let mut state = StrWidth::new();
+        while !s.is_empty() {
+            let mut indices = s.char_indices();
+            indices.next(); // current
+            let offset = indices.next().map(|(i, _)| i).unwrap_or_else(|| s.len());
+            let (current, remainder) = s.split_at(offset);
+            state.measure_next(current);
+            s = remainder;
+        }
+        state.width()
+    }
+
+    #[test]
+    fn test_str_width_plain() {
+        assert_eq!(str_width(""), 0);
+        assert_eq!(str_width("Hello"), 5);
+        assert_eq!(str_width("Hello\nworld\t!\r\n"), 11);
+    }
+
+    #[test]
+    fn test_str_width_wide() {
+        assert_eq!(str_width("漢字"), 4);
+        assert_eq!(str_width("ｗｏｒｌｄ"), 10);
+    }
+
+    #[test]
+    fn test_str_width_combining() {
+        assert_eq!(str_width("e\u{301}"), 1);
+        assert_eq!(str_width("m̃n̄o̅"), 3);
+        assert_eq!(str_width("\u{200b}"), 0);
+    }
+
+    #[test]
+    fn test_str_width_escapes() {
+        assert_eq!(str_width("\x1b[1;31mred\x1b[0m"), 3);
+        assert_eq!(str_width("\x1b[38;2;255;0;0m漢\x1b[m"), 2);
+        assert_eq!(
+            str_width("\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\"),
+            4
+        );
+        assert_eq!(
+            str_width("\x1b]8;;https://example.com\x07link\x1b]8;;\x07"),
+            4
+        );
+        assert_eq!(str_width("\x1b]9;4;1;50\x1b\\50%\x1b]9;4;0\x07"), 3);
+    }
+
+    #[test]
+    fn test_str_width_incremental() {
+        let mut width = StrWidth::new();
+        assert_eq!(width.measure_next("\x1b]8;;https://exa"), 0);
+        assert_eq!(width.measure_next("mple.com\x1b\\漢"), 2);
+        assert_eq!(width.measure_next("字\x1b]8;;\x1b\\"), 2);
+        assert_eq!(width.width(), 4);
+
+        width.reset_width();
+        assert_eq!(width.width(), 0);
+    }
+
+    proptest! {
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn str_width_ignores_style(s in "\\PC*") {
+            let styled = format!("\x1b[1;31m{s}\x1b[0m");
+            assert_eq!(str_width(&styled), str_width(&s));
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn width_char_matches_str_width(s in "[ -~\\p{Han}\\p{Hangul}]*") {
+            assert_eq!(width_char(&s), str_width(&s));
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use anstyle_parse::sgr::SgrState;
+use unicode_width::UnicodeWidthChar as _;
+
+use super::str_width;
+use super::strip_str;
+
+/// Word-wrap a `&str` to fit within `width` terminal cells
+///
+/// ANSI escapes are preserved.  At each inserted line break, the active style and OSC 8
+/// hyperlink are closed and then re-opened on the continuation line so each line stands on its
+/// own.
+///
+/// Lines are broken at whitespace, with words wider than `width` being split.  Existing
+/// newlines are kept as-is.  Tabs are treated as a single cell; expand them first if that
+/// matters.
+///
+/// # Example
+///
+/// ```rust
+/// let styled_text = "\x1b[1mHello world\x1b[0m";
+/// let wrapped = anstream::adapter::wrap_str(styled_text, 5);
+/// assert_eq!(wrapped, "\x1b[1mHello\x1b[0m\n\x1b[1mworld\x1b[0m");
+/// ```
+pub fn wrap_str(data: &str, width: usize) -> String {
+    let mut wrap = Wrap::new(width.max(1), data.len());
+    for segment in segments(data) {
+        match segment {
+            Segment::Escape(escape) => wrap.escape(escape),
+            Segment::Printable(printable) => {
+                for c in printable.chars() {
+                    wrap.char(c);
+                }
+            }
+        }
+    }
+    wrap.finish()
+}
+
+/// Truncate a `&str` to fit within `width` terminal cells, ending with `ellipsis`
+///
+/// ANSI escapes before the cut are preserved.  When truncated, the `ellipsis` is written with
+/// the active style after which the style and any OSC 8 hyperlink are closed.
+/// If `ellipsis` is wider than `width`, only as much of it as fits is written.
+///
+/// This is intended for single lines of text.
+///
+/// # Example
+///
+/// ```rust
+/// let styled_text = "\x1b[32mHello world\x1b[0m";
+/// let truncated = anstream::adapter::truncate_str(styled_text, 8, "…");
+/// assert_eq!(truncated, "\x1b[32mHello w…\x1b[0m");
+///
+/// let fits = anstream::adapter::truncate_str(styled_text, 11, "…");
+/// assert_eq!(fits, styled_text);
+/// ```
+pub fn truncate_str(data: &str, width: usize, ellipsis: &str) -> String {
+    if str_width(data) <= width {
+        return data.to_owned();
+    }
+
+    let ellipsis = &ellipsis[..fit_width(ellipsis, width)];
+    let max_width = width - str_width(ellipsis);
+    let mut truncated = String::with_capacity(data.len());
+    let mut state = StateCapture::default();
+    let mut current_width = 0;
+    'segments: for segment in segments(data) {
+        match segment {
+            Segment::Escape(escape) => {
+                truncated.push_str(escape);
+                state.advance(escape);
+            }
+            Segment::Printable(printable) => {
+                for c in printable.chars() {
+                    let c_width = c.width().unwrap_or(0);
+                    if max_width < current_width + c_width {
+                        break 'segments;
+                    }
+                    truncated.push(c);
+                    current_width += c_width;
+                }
+            }
+        }
+    }
+    truncated.push_str(ellipsis);
+    close(&mut truncated, &state.state);
+    truncated
+}
+
+/// The byte length of the longest prefix of `text` that is at most `width` cells wide
+fn fit_width(text: &str, width: usize) -> usize {
+    let mut current_width = 0;
+    for (i, c) in text.char_indices() {
+        current_width += c.width().unwrap_or(0);
+        if width < current_width {
+            return i;
+        }
+    }
+    text.len()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Segment<'s> {
+    Escape(&'s str),
+    Printable(&'s str),
+}
+
+/// Split `data` into printable text and the escapes (or non-printable content) between them
+fn segments(data: &str) -> impl Iterator<Item = Segment<'_>> {
+    let mut offset = 0;
+    let mut printables = strip_str(data);
+    let mut pending = None;
+    std::iter::from_fn(move || {
+        if let Some(printable) = pending.take() {
+            return Some(Segment::Printable(printable));
+        }
+
+        let Some(printable) = printables.next() else {
+            let remainder = &data[offset..];
+            offset = data.len();
+            return (!remainder.is_empty()).then_some(Segment::Escape(remainder));
+        };
+        let start = printable.as_ptr() as usize - data.as_ptr() as usize;
+        let escape = &data[offset..start];
+        offset = start + printable.len();
+        if escape.is_empty() {
+            Some(Segment::Printable(printable))
+        } else {
+            pending = Some(printable);
+            Some(Segment::Escape(escape))
+        }
+    })
+}
+
+/// Greedy word-wrapping
+struct Wrap {
+    width: usize,
+    wrapped: String,
+    state: StateCapture,
+    /// Width of content already written to the current line in `wrapped`
+    line_width: usize,
+    /// Whitespace before `word`, dropped if a line break is inserted instead
+    space: String,
+    /// The current word, including any escapes within it
+    word: String,
+    word_width: usize,
+    /// The state at the start of `word`
+    word_state: SgrState,
+}
+
+impl Wrap {
+    fn new(width: usize, capacity: usize) -> Self {
+        Self {
+            width,
+            wrapped: String::with_capacity(capacity),
+            state: StateCapture::default(),
+            line_width: 0,
+            space: String::new(),
+            word: String::new(),
+            word_width: 0,
+            word_state: SgrState::new(),
+        }
+    }
+
+    fn escape(&mut self, escape: &str) {
+        self.start_word();
+        self.word.push_str(escape);
+        self.state.advance(escape);
+    }
+
+    fn char(&mut self, c: char) {
+        if c == '\n' {
+            self.place_word();
+            self.place_space();
+            self.wrapped.push(c);
+            self.line_width = 0;
+        } else if c == ' ' || c == '\t' {
+            self.place_word();
+            self.space.push(c);
+        } else {
+            let c_width = c.width().unwrap_or(0);
+            if self.width < self.word_width + c_width && self.word_width != 0 {
+                // Word is wider than a line; split it
+                if self.line_width != 0 {
+                    self.break_line(&self.word_state.clone());
+                }
+                self.space.clear();
+                self.wrapped.push_str(&self.word);
+                self.word.clear();
+                self.word_width = 0;
+                self.break_line(&self.state.state.clone());
+            }
+            self.start_word();
+            self.word.push(c);
+            self.word_width += c_width;
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.place_word();
+        self.place_space();
+        self.wrapped
+    }
+
+    fn start_word(&mut self) {
+        if self.word.is_empty() {
+            self.word_state.clone_from(&self.state.state);
+        }
+    }
+
+    fn place_word(&mut self) {
+        if self.word.is_empty() {
+            return;
+        }
+
+        let space_width = self.space.chars().count();
+        if self.width < self.line_width + space_width + self.word_width {
+            self.space.clear();
+            if self.line_width != 0 {
+                self.break_line(&self.word_state.clone());
+            }
+        }
+        self.place_space();
+        self.wrapped.push_str(&self.word);
+        self.line_width += self.word_width;
+        self.word.clear();
+        self.word_width = 0;
+    }
+
+    fn place_space(&mut self) {
+        let available = self.width.saturating_sub(self.line_width);
+        for c in self.space.drain(..).take(available) {
+            self.wrapped.push(c);
+            self.line_width += 1;
+        }
+    }
+
+    fn break_line(&mut self, state: &SgrState) {
+        close(&mut self.wrapped, state);
+        self.wrapped.push('\n');
+        open(&mut self.wrapped, state);
+        self.line_width = 0;
+    }
+}
+
+fn close(buffer: &mut String, state: &SgrState) {
+    use std::fmt::Write as _;
+
+    let _ = write!(buffer, "{}", state.style().render_reset());
+    if state.hyperlink().is_some() {
+        buffer.push_str("\x1b]8;;\x1b\\");
+    }
+}
+
+fn open(buffer: &mut String, state: &SgrState) {
+    use std::fmt::Write as _;
+
+    if let Some(url) = state.hyperlink() {
+        let _ = write!(buffer, "\x1b]8;;{url}\x1b\\");
+    }
+    let _ = write!(buffer, "{}", state.style().render());
+}
+
+#[derive(Default)]
+pub(super) struct StateCapture {
+    parser: anstyle_parse::Parser,
+    state: SgrState,
+}
+
+impl StateCapture {
+    pub(super) fn advance(&mut self, escape: &str) {
+        let mut performer = StatePerformer(&mut self.state);
+        for byte in escape.as_bytes() {
+            self.parser.advance(&mut performer, *byte);
+        }
+    }
+
+    pub(super) fn style(&self) -> anstyle::Style {
+        self.state.style()
+    }
+}
+
+struct StatePerformer<'s>(&'s mut SgrState);
+
+impl anstyle_parse::Perform for StatePerformer<'_> {
+    fn csi_dispatch(
+        &mut self,
+        params: &anstyle_parse::Params,
+        intermediates: &[u8],
+        ignore: bool,
+        action: u8,
+    ) {
+        self.0.csi_dispatch(params, intermediates, ignore, action);
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        self.0.osc_dispatch(params, bell_terminated);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    const LINK: &str = "\x1b]8;;https://example.com\x1b\\";
+    const UNLINK: &str = "\x1b]8;;\x1b\\";
+
+    #[test]
+    fn test_wrap_str_plain() {
+        assert_eq!(wrap_str("", 5), "");
+        assert_eq!(wrap_str("Hello world", 20), "Hello world");
+        assert_eq!(wrap_str("Hello world", 5), "Hello\nworld");
+        assert_eq!(wrap_str("a b c d", 3), "a b\nc d");
+        assert_eq!(wrap_str("Hello  ", 5), "Hello");
+        assert_eq!(wrap_str(" Hello", 5), "Hello");
+        assert_eq!(wrap_str("one\ntwo three", 5), "one\ntwo\nthree");
+    }
+
+    #[test]
+    fn test_wrap_str_long_word() {
+        assert_eq!(wrap_str("abcdefgh", 3), "abc\ndef\ngh");
+        assert_eq!(wrap_str("a bcdefgh", 3), "a\nbcd\nefg\nh");
+        assert_eq!(wrap_str("漢字漢字", 3), "漢\n字\n漢\n字");
+    }
+
+    #[test]
+    fn test_wrap_str_wide_chars() {
+        let input = " \u{30000}\u{16fe2}\u{3038}\u{2a700}";
+        let wrapped = wrap_str(input, 8);
+        for line in wrapped.lines() {
+            assert!(str_width(line) <= 8, "{line:?} is wider than 8");
+        }
+        assert_eq!(
+            wrapped.split_whitespace().collect::<String>(),
+            input.split_whitespace().collect::<String>()
+        );
+    }
+
+    #[test]
+    fn test_wrap_str_reopens_style() {
+        let bold = anstyle::Style::new().bold();
+        let input = format!("{bold}aaa bbb ccc{bold:#}");
+        let expected = format!("{bold}aaa{bold:#}\n{bold}bbb{bold:#}\n{bold}ccc{bold:#}");
+        assert_eq!(wrap_str(&input, 4), expected);
+    }
+
+    #[test]
+    fn test_wrap_str_style_starts_word() {
+        let red = anstyle::AnsiColor::Red.on_default();
+        let input = format!("aaa {red}bbb{red:#}");
+        let expected = format!("aaa\n{red}bbb{red:#}");
+        assert_eq!(wrap_str(&input, 4), expected);
+    }
+
+    #[test]
+    fn test_wrap_str_reopens_hyperlink() {
+        let input = format!("{LINK}aaa bbb{UNLINK}");
+        let expected = format!("{LINK}aaa{UNLINK}\n{LINK}bbb{UNLINK}");
+        assert_eq!(wrap_str(&input, 4), expected);
+    }
+
+    #[test]
+    fn test_truncate_str_plain() {
+        assert_eq!(truncate_str("Hello", 5, "…"), "Hello");
+        assert_eq!(truncate_str("Hello world", 5, "…"), "Hell…");
+        assert_eq!(truncate_str("Hello world", 5, "..."), "He...");
+        assert_eq!(truncate_str("漢字漢字", 4, "…"), "漢…");
+        assert_eq!(truncate_str("Hello", 0, "…"), "");
+        assert_eq!(truncate_str("Hello world", 2, "..."), "..");
+        assert_eq!(truncate_str("Hello world", 1, "漢"), "H");
+    }
+
+    #[test]
+    fn test_truncate_str_closes() {
+        let bold = anstyle::Style::new().bold();
+        let input = format!("{LINK}{bold}Hello{bold:#} world{UNLINK}");
+        let expected = format!("{LINK}{bold}Hel…\x1b[0m{UNLINK}");
+        assert_eq!(truncate_str(&input, 4, "…"), expected);
+
+        let expected = format!("{LINK}{bold}Hello{bold:#} w…{UNLINK}");
+        assert_eq!(truncate_str(&input, 8, "…"), expected);
+    }
+
+    proptest! {
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn wrap_str_fits(s in "[a-z \\p{Han}]*", width in 2..20_usize) {
+            let styled = format!("\x1b[1m{s}\x1b[0m");
+            let wrapped = wrap_str(&styled, width);
+            for line in wrapped.lines() {
+                assert!(str_width(line) <= width, "{line:?} is wider than {width}");
+            }
+            let expected = s.split_whitespace().collect::<String>();
+            let actual = strip_str(&wrapped).to_string().split_whitespace().collect::<String>();
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn truncate_str_fits(s in "\\PC*", width in 0..20_usize) {
+            let styled = format!("\x1b[1m{s}\x1b[0m");
+            let truncated = truncate_str(&styled, width, "…");
+            assert!(str_width(&truncated) <= width);
+        }
+    }
+}
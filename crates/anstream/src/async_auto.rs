@@ -0,0 +1,382 @@
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+
+#[cfg(feature = "auto")]
+use crate::adapter::DownsampleBytes;
+#[cfg(feature = "auto")]
+use crate::async_strip::Adapted;
+#[cfg(all(windows, feature = "wincon"))]
+use crate::async_wincon::AsyncWincon;
+#[cfg(all(windows, feature = "wincon"))]
+use crate::async_wincon::StdioConsole;
+use crate::stream::AsyncRawStream;
+use crate::AsyncStripStream;
+use crate::ColorChoice;
+
+/// `AsyncWrite` that adapts ANSI escape codes to the underlying `AsyncWrite`s capabilities
+///
+/// The async counterpart to [`AutoStream`][crate::AutoStream], writing to a
+/// [`tokio::io::AsyncWrite`] (with the `tokio` feature) or a [`futures_io::AsyncWrite`] (with the
+/// `futures` feature).  The same [`ColorChoice`] is made as for [`AutoStream`][crate::AutoStream]
+/// except:
+/// - *(windows)* Where the wincon API would be needed, it is only used for [`tokio::io::Stdout`]
+///   and [`tokio::io::Stderr`], flushing them before each color change.  Other `AsyncWrite`s
+///   are passed ANSI escape codes as-is.
+/// - There is no `lock`.  Holding a [`std::io::Stdout`] lock across an `.await` could block other
+///   tasks on the same thread, so locking is left to the async writer, like
+///   [`tokio::io::Stdout`] writing each `write` call as a unit.
+///
+/// Adapted output is buffered when the inner `AsyncWrite` can't accept it all at once.  Call
+/// `flush` or `shutdown` / `close` to make sure it is written.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "tokio")]
+/// # futures::executor::block_on(async {
+/// use tokio::io::AsyncWriteExt as _;
+///
+/// let mut stream = anstream::AsyncAutoStream::new(Vec::new(), anstream::ColorChoice::Never);
+/// stream.write_all(b"\x1b[32mfoo\x1b[m bar").await.unwrap();
+/// stream.flush().await.unwrap();
+/// assert_eq!(stream.into_inner(), b"foo bar");
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct AsyncAutoStream<S: AsyncRawStream> {
+    inner: StreamInner<S>,
+}
+
+#[derive(Debug)]
+enum StreamInner<S: AsyncRawStream> {
+    PassThrough(S),
+    Strip(AsyncStripStream<S>),
+    #[cfg(feature = "auto")]
+    Downsample(Adapted<S, DownsampleBytes>),
+    #[cfg(all(windows, feature = "wincon"))]
+    Wincon(AsyncWincon<S, StdioConsole>),
+}
+
+impl<S> AsyncAutoStream<S>
+where
+    S: AsyncRawStream,
+{
+    /// Runtime control over styling behavior
+    #[inline]
+    pub fn new(raw: S, choice: ColorChoice) -> Self {
+        match choice {
+            #[cfg(feature = "auto")]
+            ColorChoice::Auto => Self::auto(raw),
+            #[cfg(not(feature = "auto"))]
+            ColorChoice::Auto => Self::never(raw),
+            ColorChoice::AlwaysAnsi => Self::always_ansi(raw),
+            ColorChoice::Always => Self::always(raw),
+            ColorChoice::Never => Self::never(raw),
+        }
+    }
+
+    /// Auto-adapt for the stream's capabilities
+    #[cfg(feature = "auto")]
+    #[inline]
+    pub fn auto(raw: S) -> Self {
+        let choice = Self::choice(&raw);
+        debug_assert_ne!(choice, ColorChoice::Auto);
+        Self::new(raw, choice)
+    }
+
+    /// Report the desired choice for the given stream
+    #[cfg(feature = "auto")]
+    pub fn choice(raw: &S) -> ColorChoice {
+        crate::auto::choice(raw)
+    }
+
+    /// Force ANSI escape codes to be passed through as-is, no matter what the inner `AsyncWrite`
+    /// supports.
+    #[inline]
+    pub fn always_ansi(raw: S) -> Self {
+        #[cfg(feature = "auto")]
+        {
+            if raw.is_terminal() {
+                let _ = anstyle_query::windows::enable_ansi_colors();
+            }
+        }
+        Self::always_ansi_(raw)
+    }
+
+    #[inline]
+    fn always_ansi_(raw: S) -> Self {
+        let inner = StreamInner::PassThrough(raw);
+        Self { inner }
+    }
+
+    /// Force color, no matter what the inner `AsyncWrite` supports.
+    ///
+    /// Colors will be downsampled for terminals that don't report truecolor support.
+    #[inline]
+    pub fn always(raw: S) -> Self {
+        if cfg!(windows) {
+            #[cfg(feature = "auto")]
+            let use_wincon = raw.is_terminal()
+                && !anstyle_query::windows::enable_ansi_colors().unwrap_or(true)
+                && !anstyle_query::term_supports_ansi_color();
+            #[cfg(not(feature = "auto"))]
+            let use_wincon = true;
+            if use_wincon {
+                Self::wincon(raw).unwrap_or_else(Self::always_ansi_)
+            } else {
+                Self::always_downsample(raw)
+            }
+        } else {
+            Self::always_downsample(raw)
+        }
+    }
+
+    #[inline]
+    fn always_downsample(raw: S) -> Self {
+        #[cfg(feature = "auto")]
+        if raw.is_terminal() {
+            match anstyle_query::color_level() {
+                anstyle_query::ColorLevel::TrueColor => {}
+                anstyle_query::ColorLevel::Ansi256 => {
+                    return Self::downsample(raw, DownsampleBytes::xterm());
+                }
+                anstyle_query::ColorLevel::Ansi16 | anstyle_query::ColorLevel::None => {
+                    let palette = anstyle_lossy::palette::Palette::default();
+                    return Self::downsample(raw, DownsampleBytes::ansi(palette));
+                }
+            }
+        }
+        Self::always_ansi_(raw)
+    }
+
+    #[cfg(feature = "auto")]
+    #[inline]
+    fn downsample(raw: S, state: DownsampleBytes) -> Self {
+        let inner = StreamInner::Downsample(Adapted::new(raw, state));
+        Self { inner }
+    }
+
+    #[cfg(all(windows, feature = "wincon"))]
+    #[inline]
+    fn wincon(raw: S) -> Result<Self, S> {
+        let Some(console) = crate::stream::async_console(&raw) else {
+            return Err(raw);
+        };
+        let inner = StreamInner::Wincon(AsyncWincon::new(raw, console));
+        Ok(Self { inner })
+    }
+
+    #[cfg(not(all(windows, feature = "wincon")))]
+    fn wincon(raw: S) -> Result<Self, S> {
+        Err(raw)
+    }
+
+    /// Only pass printable data to the inner `AsyncWrite`.
+    #[inline]
+    pub fn never(raw: S) -> Self {
+        let inner = StreamInner::Strip(AsyncStripStream::new(raw));
+        Self { inner }
+    }
+
+    /// Get the wrapped [`AsyncRawStream`]
+    ///
+    /// **Note:** output that has not been flushed is lost
+    #[inline]
+    pub fn into_inner(self) -> S {
+        match self.inner {
+            StreamInner::PassThrough(w) => w,
+            StreamInner::Strip(w) => w.into_inner(),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.raw,
+            #[cfg(all(windows, feature = "wincon"))]
+            StreamInner::Wincon(w) => w.raw,
+        }
+    }
+
+    /// Get the wrapped [`AsyncRawStream`]
+    #[inline]
+    pub fn as_inner(&self) -> &S {
+        match &self.inner {
+            StreamInner::PassThrough(w) => w,
+            StreamInner::Strip(w) => w.as_inner(),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => &w.raw,
+            #[cfg(all(windows, feature = "wincon"))]
+            StreamInner::Wincon(w) => &w.raw,
+        }
+    }
+
+    /// Returns `true` if the descriptor/handle refers to a terminal/tty.
+    #[inline]
+    pub fn is_terminal(&self) -> bool {
+        self.as_inner().is_terminal()
+    }
+
+    /// Prefer [`AsyncAutoStream::choice`]
+    ///
+    /// This doesn't report what is requested but what is currently active.
+    #[inline]
+    #[cfg(feature = "auto")]
+    pub fn current_choice(&self) -> ColorChoice {
+        match &self.inner {
+            StreamInner::PassThrough(_) => ColorChoice::AlwaysAnsi,
+            StreamInner::Strip(_) => ColorChoice::Never,
+            StreamInner::Downsample(_) => ColorChoice::Always,
+            #[cfg(all(windows, feature = "wincon"))]
+            StreamInner::Wincon(_) => ColorChoice::Always,
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<S> tokio::io::AsyncWrite for AsyncAutoStream<S>
+where
+    S: AsyncRawStream + tokio::io::AsyncWrite + Unpin,
+{
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match &mut self.get_mut().inner {
+            StreamInner::PassThrough(w) => Pin::new(w).poll_write(cx, buf),
+            StreamInner::Strip(w) => Pin::new(w).poll_write(cx, buf),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.tokio_poll_write(cx, buf),
+            #[cfg(all(windows, feature = "wincon"))]
+            StreamInner::Wincon(w) => w.tokio_poll_write(cx, buf),
+        }
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match &mut self.get_mut().inner {
+            StreamInner::PassThrough(w) => Pin::new(w).poll_flush(cx),
+            StreamInner::Strip(w) => Pin::new(w).poll_flush(cx),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.tokio_poll_flush(cx),
+            #[cfg(all(windows, feature = "wincon"))]
+            StreamInner::Wincon(w) => w.tokio_poll_flush(cx),
+        }
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match &mut self.get_mut().inner {
+            StreamInner::PassThrough(w) => Pin::new(w).poll_shutdown(cx),
+            StreamInner::Strip(w) => Pin::new(w).poll_shutdown(cx),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.tokio_poll_shutdown(cx),
+            #[cfg(all(windows, feature = "wincon"))]
+            StreamInner::Wincon(w) => w.tokio_poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S> futures_io::AsyncWrite for AsyncAutoStream<S>
+where
+    S: AsyncRawStream + futures_io::AsyncWrite + Unpin,
+{
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match &mut self.get_mut().inner {
+            StreamInner::PassThrough(w) => Pin::new(w).poll_write(cx, buf),
+            StreamInner::Strip(w) => Pin::new(w).poll_write(cx, buf),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.futures_poll_write(cx, buf),
+            #[cfg(all(windows, feature = "wincon"))]
+            StreamInner::Wincon(w) => w.futures_poll_write(cx, buf),
+        }
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match &mut self.get_mut().inner {
+            StreamInner::PassThrough(w) => Pin::new(w).poll_flush(cx),
+            StreamInner::Strip(w) => Pin::new(w).poll_flush(cx),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.futures_poll_flush(cx),
+            #[cfg(all(windows, feature = "wincon"))]
+            StreamInner::Wincon(w) => w.futures_poll_flush(cx),
+        }
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match &mut self.get_mut().inner {
+            StreamInner::PassThrough(w) => Pin::new(w).poll_close(cx),
+            StreamInner::Strip(w) => Pin::new(w).poll_close(cx),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.futures_poll_close(cx),
+            #[cfg(all(windows, feature = "wincon"))]
+            StreamInner::Wincon(w) => w.futures_poll_close(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "tokio")]
+    fn assert_tokio<T>()
+    where
+        T: AsyncRawStream,
+        AsyncAutoStream<T>: tokio::io::AsyncWrite,
+    {
+    }
+
+    #[cfg(feature = "futures")]
+    fn assert_futures<T>()
+    where
+        T: AsyncRawStream,
+        AsyncAutoStream<T>: futures_io::AsyncWrite,
+    {
+    }
+
+    #[test]
+    #[cfg(feature = "tokio")]
+    fn tokio_streams() {
+        assert_tokio::<tokio::io::Stdout>();
+        assert_tokio::<tokio::io::Stderr>();
+        assert_tokio::<Vec<u8>>();
+        assert_tokio::<&mut Vec<u8>>();
+        assert_tokio::<Box<dyn tokio::io::AsyncWrite + Unpin>>();
+        assert_tokio::<Box<dyn tokio::io::AsyncWrite + Unpin + Send>>();
+        assert_tokio::<Box<dyn tokio::io::AsyncWrite + Unpin + Send + Sync>>();
+    }
+
+    #[test]
+    #[cfg(feature = "futures")]
+    fn futures_streams() {
+        assert_futures::<Vec<u8>>();
+        assert_futures::<&mut Vec<u8>>();
+        assert_futures::<Box<dyn futures_io::AsyncWrite + Unpin>>();
+        assert_futures::<Box<dyn futures_io::AsyncWrite + Unpin + Send>>();
+        assert_futures::<Box<dyn futures_io::AsyncWrite + Unpin + Send + Sync>>();
+    }
+
+    #[test]
+    #[cfg(all(feature = "tokio", feature = "auto"))]
+    fn tokio_downsample() {
+        use tokio::io::AsyncWriteExt as _;
+
+        let mut stream = AsyncAutoStream::downsample(Vec::new(), DownsampleBytes::xterm());
+        futures::executor::block_on(async {
+            stream
+                .write_all(b"\x1b[38;2;255;0;0mred\x1b[0m")
+                .await
+                .unwrap();
+            stream.flush().await.unwrap();
+        });
+        assert_eq!(stream.current_choice(), ColorChoice::Always);
+        assert_eq!(stream.into_inner(), b"\x1b[38;5;196mred\x1b[0m");
+    }
+}
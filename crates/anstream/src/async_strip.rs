@@ -0,0 +1,496 @@
+use std::pin::Pin;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use crate::adapter::StripBytes;
+use crate::stream::IsTerminal;
+
+/// Only pass printable data to the inner `AsyncWrite`
+///
+/// The async counterpart to [`StripStream`][crate::StripStream], writing to a
+/// [`tokio::io::AsyncWrite`] (with the `tokio` feature) or a [`futures_io::AsyncWrite`] (with the
+/// `futures` feature).
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "tokio")]
+/// # futures::executor::block_on(async {
+/// use tokio::io::AsyncWriteExt as _;
+///
+/// let mut stream = anstream::AsyncStripStream::new(Vec::new());
+/// stream.write_all(b"\x1b[32mfoo\x1b[m bar").await.unwrap();
+/// stream.flush().await.unwrap();
+/// assert_eq!(stream.into_inner(), b"foo bar");
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct AsyncStripStream<S> {
+    inner: Adapted<S, StripBytes>,
+}
+
+impl<S> AsyncStripStream<S> {
+    /// Only pass printable data to the inner `AsyncWrite`
+    #[inline]
+    pub fn new(raw: S) -> Self {
+        Self {
+            inner: Adapted::new(raw, StripBytes::new()),
+        }
+    }
+
+    /// Get the wrapped `AsyncWrite`
+    ///
+    /// **Note:** output that has not been flushed is lost
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner.raw
+    }
+
+    /// Get the wrapped `AsyncWrite`
+    #[inline]
+    pub fn as_inner(&self) -> &S {
+        &self.inner.raw
+    }
+}
+
+impl<S> AsyncStripStream<S>
+where
+    S: IsTerminal,
+{
+    /// Returns `true` if the descriptor/handle refers to a terminal/tty.
+    #[inline]
+    pub fn is_terminal(&self) -> bool {
+        self.inner.raw.is_terminal()
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<S> tokio::io::AsyncWrite for AsyncStripStream<S>
+where
+    S: tokio::io::AsyncWrite + Unpin,
+{
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.get_mut().inner.tokio_poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.get_mut().inner.tokio_poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.get_mut().inner.tokio_poll_shutdown(cx)
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S> futures_io::AsyncWrite for AsyncStripStream<S>
+where
+    S: futures_io::AsyncWrite + Unpin,
+{
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.get_mut().inner.futures_poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.get_mut().inner.futures_poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.get_mut().inner.futures_poll_close(cx)
+    }
+}
+
+/// Adapt the data for an `AsyncWrite`
+pub(crate) trait Adapter {
+    fn adapt(&mut self, buf: &[u8], adapted: &mut Vec<u8>);
+}
+
+impl Adapter for StripBytes {
+    #[inline]
+    fn adapt(&mut self, buf: &[u8], adapted: &mut Vec<u8>) {
+        for printable in self.strip_next(buf) {
+            adapted.extend_from_slice(printable);
+        }
+    }
+}
+
+#[cfg(feature = "auto")]
+impl Adapter for crate::adapter::DownsampleBytes {
+    #[inline]
+    fn adapt(&mut self, buf: &[u8], adapted: &mut Vec<u8>) {
+        for segment in self.downsample_next(buf) {
+            adapted.extend_from_slice(&segment);
+        }
+    }
+}
+
+/// Buffer adapted data that the inner `AsyncWrite` has not accepted yet
+///
+/// An adapter's state can't be rewound once it has seen data, like for a partial write in the
+/// sync streams, if the inner `AsyncWrite` returns [`Poll::Pending`] part way through.  Instead,
+/// all of `buf` is accepted and any remaining adapted data is written out before the next
+/// `buf`.  Likewise, an error from writing out an accepted `buf` is reported on the next call.
+#[derive(Debug)]
+pub(crate) struct Adapted<S, A> {
+    pub(crate) raw: S,
+    state: A,
+    pending: Vec<u8>,
+    written: usize,
+    error: Option<std::io::Error>,
+}
+
+impl<S, A> Adapted<S, A>
+where
+    A: Adapter,
+{
+    #[inline]
+    pub(crate) fn new(raw: S, state: A) -> Self {
+        Self {
+            raw,
+            state,
+            pending: Vec::new(),
+            written: 0,
+            error: None,
+        }
+    }
+
+    fn poll_pending(
+        &mut self,
+        cx: &mut Context<'_>,
+        poll_write: &mut impl FnMut(&mut S, &mut Context<'_>, &[u8]) -> Poll<std::io::Result<usize>>,
+    ) -> Poll<std::io::Result<()>> {
+        if let Some(err) = self.error.take() {
+            return Poll::Ready(Err(err));
+        }
+        while self.written < self.pending.len() {
+            let written = ready!(poll_write(&mut self.raw, cx, &self.pending[self.written..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+            }
+            self.written += written;
+        }
+        self.pending.clear();
+        self.written = 0;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_write(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        mut poll_write: impl FnMut(&mut S, &mut Context<'_>, &[u8]) -> Poll<std::io::Result<usize>>,
+    ) -> Poll<std::io::Result<usize>> {
+        ready!(self.poll_pending(cx, &mut poll_write))?;
+        self.state.adapt(buf, &mut self.pending);
+        // `buf` is accepted, so any error or `Pending` will be reported on the next call
+        if let Poll::Ready(Err(err)) = self.poll_pending(cx, &mut poll_write) {
+            self.error = Some(err);
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut poll_write: impl FnMut(&mut S, &mut Context<'_>, &[u8]) -> Poll<std::io::Result<usize>>,
+        poll_flush: impl FnOnce(&mut S, &mut Context<'_>) -> Poll<std::io::Result<()>>,
+    ) -> Poll<std::io::Result<()>> {
+        ready!(self.poll_pending(cx, &mut poll_write))?;
+        poll_flush(&mut self.raw, cx)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<S, A> Adapted<S, A>
+where
+    S: tokio::io::AsyncWrite + Unpin,
+    A: Adapter,
+{
+    #[inline]
+    pub(crate) fn tokio_poll_write(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.poll_write(cx, buf, |raw, cx, buf| Pin::new(raw).poll_write(cx, buf))
+    }
+
+    #[inline]
+    pub(crate) fn tokio_poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(
+            cx,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+        )
+    }
+
+    #[inline]
+    pub(crate) fn tokio_poll_shutdown(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        self.poll_flush(
+            cx,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_shutdown(cx),
+        )
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S, A> Adapted<S, A>
+where
+    S: futures_io::AsyncWrite + Unpin,
+    A: Adapter,
+{
+    #[inline]
+    pub(crate) fn futures_poll_write(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.poll_write(cx, buf, |raw, cx, buf| Pin::new(raw).poll_write(cx, buf))
+    }
+
+    #[inline]
+    pub(crate) fn futures_poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(
+            cx,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+        )
+    }
+
+    #[inline]
+    pub(crate) fn futures_poll_close(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(
+            cx,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_close(cx),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Accept a byte at a time, alternating with [`Poll::Pending`]
+    #[derive(Default)]
+    struct Trickle {
+        written: Vec<u8>,
+        ready: bool,
+    }
+
+    impl Trickle {
+        fn poll_write(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+            self.ready = !self.ready;
+            if self.ready {
+                let written = buf.len().min(1);
+                self.written.extend_from_slice(&buf[..written]);
+                Poll::Ready(Ok(written))
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl tokio::io::AsyncWrite for Trickle {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.get_mut().poll_write(cx, buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    impl futures_io::AsyncWrite for Trickle {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.get_mut().poll_write(cx, buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Fail the first write, accepting everything after
+    #[derive(Default)]
+    struct FailOnce {
+        written: Vec<u8>,
+        failed: bool,
+    }
+
+    impl FailOnce {
+        fn poll_write(&mut self, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+            if !self.failed {
+                self.failed = true;
+                return Poll::Ready(Err(std::io::ErrorKind::BrokenPipe.into()));
+            }
+            self.written.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl tokio::io::AsyncWrite for FailOnce {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.get_mut().poll_write(buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[cfg(feature = "futures")]
+    impl futures_io::AsyncWrite for FailOnce {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.get_mut().poll_write(buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "tokio")]
+    fn tokio_error_after_accepting() {
+        use tokio::io::AsyncWriteExt as _;
+
+        let mut stream = AsyncStripStream::new(FailOnce::default());
+        futures::executor::block_on(async {
+            assert_eq!(stream.write(b"\x1b[1mfoo").await.unwrap(), 7);
+            let err = stream.flush().await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+            stream.write_all(b"bar").await.unwrap();
+            stream.flush().await.unwrap();
+        });
+        assert_eq!(stream.into_inner().written, b"foobar");
+    }
+
+    #[test]
+    #[cfg(feature = "futures")]
+    fn futures_error_after_accepting() {
+        use futures::AsyncWriteExt as _;
+
+        let mut stream = AsyncStripStream::new(FailOnce::default());
+        futures::executor::block_on(async {
+            assert_eq!(stream.write(b"\x1b[1mfoo").await.unwrap(), 7);
+            let err = stream.flush().await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+            stream.write_all(b"bar").await.unwrap();
+            stream.flush().await.unwrap();
+        });
+        assert_eq!(stream.into_inner().written, b"foobar");
+    }
+
+    proptest! {
+        #[test]
+        #[cfg(feature = "tokio")]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn tokio_write_all_random(s in any::<Vec<u8>>()) {
+            use tokio::io::AsyncWriteExt as _;
+
+            let expected = crate::adapter::strip_bytes(&s).into_vec();
+            let mut stream = AsyncStripStream::new(Trickle::default());
+            futures::executor::block_on(async {
+                stream.write_all(&s).await.unwrap();
+                stream.shutdown().await.unwrap();
+            });
+            let actual = stream.into_inner().written;
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        #[cfg(feature = "tokio")]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn tokio_write_byte_random(s in any::<Vec<u8>>()) {
+            use tokio::io::AsyncWriteExt as _;
+
+            let mut state = StripBytes::new();
+            let mut expected = Vec::new();
+            for byte in &s {
+                state.adapt(std::slice::from_ref(byte), &mut expected);
+            }
+            let mut stream = AsyncStripStream::new(Trickle::default());
+            futures::executor::block_on(async {
+                for byte in &s {
+                    stream.write_all(std::slice::from_ref(byte)).await.unwrap();
+                }
+                stream.flush().await.unwrap();
+            });
+            let actual = stream.into_inner().written;
+            assert_eq!(expected, actual);
+        }
+
+        #[test]
+        #[cfg(feature = "futures")]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn futures_write_all_random(s in any::<Vec<u8>>()) {
+            use futures::io::AsyncWriteExt as _;
+
+            let expected = crate::adapter::strip_bytes(&s).into_vec();
+            let mut stream = AsyncStripStream::new(Trickle::default());
+            futures::executor::block_on(async {
+                stream.write_all(&s).await.unwrap();
+                stream.close().await.unwrap();
+            });
+            let actual = stream.into_inner().written;
+            assert_eq!(expected, actual);
+        }
+    }
+}
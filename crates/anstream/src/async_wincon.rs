@@ -0,0 +1,397 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use crate::adapter::WinconBytes;
+
+/// Console whose colors apply to what an async writer writes next
+pub(crate) trait Console {
+    /// Apply the colors, using the console's initial colors for `None`
+    fn set_colors(
+        &mut self,
+        fg: Option<anstyle::AnsiColor>,
+        bg: Option<anstyle::AnsiColor>,
+    ) -> std::io::Result<()>;
+}
+
+/// The console behind [`std::io::stdout`] or [`std::io::stderr`]
+#[cfg(windows)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum StdioConsole {
+    Stdout,
+    Stderr,
+}
+
+#[cfg(windows)]
+impl Console for StdioConsole {
+    fn set_colors(
+        &mut self,
+        fg: Option<anstyle::AnsiColor>,
+        bg: Option<anstyle::AnsiColor>,
+    ) -> std::io::Result<()> {
+        match self {
+            Self::Stdout => {
+                let (initial_fg, initial_bg) = anstyle_wincon::windows::stdout_initial_colors()?;
+                anstyle_wincon::windows::set_colors(
+                    &mut std::io::stdout(),
+                    fg.unwrap_or(initial_fg),
+                    bg.unwrap_or(initial_bg),
+                )
+            }
+            Self::Stderr => {
+                let (initial_fg, initial_bg) = anstyle_wincon::windows::stderr_initial_colors()?;
+                anstyle_wincon::windows::set_colors(
+                    &mut std::io::stderr(),
+                    fg.unwrap_or(initial_fg),
+                    bg.unwrap_or(initial_bg),
+                )
+            }
+        }
+    }
+}
+
+/// Style an async writer's console with the wincon API
+///
+/// Like `WinconStream`, the inner `AsyncWrite` is flushed before each color change so colors only
+/// apply to the text written with them.  As with `Adapted`, all of `buf` is accepted and any
+/// remaining text is written out before the next `buf`, with errors reported on the next call.
+#[derive(Debug)]
+pub(crate) struct AsyncWincon<S, C> {
+    pub(crate) raw: S,
+    console: C,
+    state: WinconBytes,
+    segments: VecDeque<Segment>,
+    step: Step,
+    written: usize,
+    error: Option<std::io::Error>,
+}
+
+#[derive(Debug)]
+struct Segment {
+    fg: Option<anstyle::AnsiColor>,
+    bg: Option<anstyle::AnsiColor>,
+    text: String,
+}
+
+impl Segment {
+    fn is_styled(&self) -> bool {
+        self.fg.is_some() || self.bg.is_some()
+    }
+}
+
+/// Progress through the front [`Segment`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Step {
+    SetColors,
+    Write,
+    ResetColors,
+}
+
+impl<S, C> AsyncWincon<S, C>
+where
+    C: Console,
+{
+    #[inline]
+    pub(crate) fn new(raw: S, console: C) -> Self {
+        Self {
+            raw,
+            console,
+            state: WinconBytes::new(),
+            segments: VecDeque::new(),
+            step: Step::SetColors,
+            written: 0,
+            error: None,
+        }
+    }
+
+    fn poll_segments(
+        &mut self,
+        cx: &mut Context<'_>,
+        poll_write: &mut impl FnMut(&mut S, &mut Context<'_>, &[u8]) -> Poll<std::io::Result<usize>>,
+        poll_flush: &mut impl FnMut(&mut S, &mut Context<'_>) -> Poll<std::io::Result<()>>,
+    ) -> Poll<std::io::Result<()>> {
+        if let Some(err) = self.error.take() {
+            return Poll::Ready(Err(err));
+        }
+        while let Some(segment) = self.segments.front() {
+            match self.step {
+                Step::SetColors => {
+                    if segment.is_styled() {
+                        // Ensure everything is written with the last set of colors before
+                        // applying the next set
+                        ready!(poll_flush(&mut self.raw, cx))?;
+                        self.console.set_colors(segment.fg, segment.bg)?;
+                    }
+                    self.step = Step::Write;
+                }
+                Step::Write => {
+                    let text = segment.text.as_bytes();
+                    while self.written < text.len() {
+                        let written = ready!(poll_write(&mut self.raw, cx, &text[self.written..]))?;
+                        if written == 0 {
+                            return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+                        }
+                        self.written += written;
+                    }
+                    self.step = Step::ResetColors;
+                }
+                Step::ResetColors => {
+                    if segment.is_styled() {
+                        ready!(poll_flush(&mut self.raw, cx))?;
+                        self.console.set_colors(None, None)?;
+                    }
+                    self.segments.pop_front();
+                    self.step = Step::SetColors;
+                    self.written = 0;
+                }
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_write(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        mut poll_write: impl FnMut(&mut S, &mut Context<'_>, &[u8]) -> Poll<std::io::Result<usize>>,
+        mut poll_flush: impl FnMut(&mut S, &mut Context<'_>) -> Poll<std::io::Result<()>>,
+    ) -> Poll<std::io::Result<usize>> {
+        ready!(self.poll_segments(cx, &mut poll_write, &mut poll_flush))?;
+        for (style, text) in self.state.extract_next(buf) {
+            self.segments.push_back(Segment {
+                fg: style.get_fg_color().and_then(cap_wincon_color),
+                bg: style.get_bg_color().and_then(cap_wincon_color),
+                text,
+            });
+        }
+        // `buf` is accepted, so any error or `Pending` will be reported on the next call
+        if let Poll::Ready(Err(err)) = self.poll_segments(cx, &mut poll_write, &mut poll_flush) {
+            self.error = Some(err);
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut poll_write: impl FnMut(&mut S, &mut Context<'_>, &[u8]) -> Poll<std::io::Result<usize>>,
+        mut poll_flush: impl FnMut(&mut S, &mut Context<'_>) -> Poll<std::io::Result<()>>,
+        poll_finish: impl FnOnce(&mut S, &mut Context<'_>) -> Poll<std::io::Result<()>>,
+    ) -> Poll<std::io::Result<()>> {
+        ready!(self.poll_segments(cx, &mut poll_write, &mut poll_flush))?;
+        poll_finish(&mut self.raw, cx)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<S, C> AsyncWincon<S, C>
+where
+    S: tokio::io::AsyncWrite + Unpin,
+    C: Console,
+{
+    #[inline]
+    pub(crate) fn tokio_poll_write(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.poll_write(
+            cx,
+            buf,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+        )
+    }
+
+    #[inline]
+    pub(crate) fn tokio_poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(
+            cx,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+        )
+    }
+
+    #[inline]
+    pub(crate) fn tokio_poll_shutdown(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        self.poll_flush(
+            cx,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+            |raw, cx| Pin::new(raw).poll_shutdown(cx),
+        )
+    }
+}
+
+#[cfg(feature = "futures")]
+impl<S, C> AsyncWincon<S, C>
+where
+    S: futures_io::AsyncWrite + Unpin,
+    C: Console,
+{
+    #[inline]
+    pub(crate) fn futures_poll_write(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.poll_write(
+            cx,
+            buf,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+        )
+    }
+
+    #[inline]
+    pub(crate) fn futures_poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(
+            cx,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+        )
+    }
+
+    #[inline]
+    pub(crate) fn futures_poll_close(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.poll_flush(
+            cx,
+            |raw, cx, buf| Pin::new(raw).poll_write(cx, buf),
+            |raw, cx| Pin::new(raw).poll_flush(cx),
+            |raw, cx| Pin::new(raw).poll_close(cx),
+        )
+    }
+}
+
+fn cap_wincon_color(color: anstyle::Color) -> Option<anstyle::AnsiColor> {
+    match color {
+        anstyle::Color::Ansi(c) => Some(c),
+        anstyle::Color::Ansi256(c) => c.into_ansi(),
+        anstyle::Color::Rgb(_) => None,
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "tokio")]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    /// Record colors alongside what was flushed, accepting a byte at a time
+    struct Recorder {
+        log: Log,
+        buffered: Vec<u8>,
+        ready: bool,
+    }
+
+    impl tokio::io::AsyncWrite for Recorder {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            let this = self.get_mut();
+            this.ready = !this.ready;
+            if this.ready {
+                let written = buf.len().min(1);
+                this.buffered.extend_from_slice(&buf[..written]);
+                Poll::Ready(Ok(written))
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            let this = self.get_mut();
+            if !this.buffered.is_empty() {
+                let text = String::from_utf8(std::mem::take(&mut this.buffered)).unwrap();
+                this.log.borrow_mut().push(text);
+            }
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            self.poll_flush(cx)
+        }
+    }
+
+    struct FakeConsole(Log);
+
+    impl Console for FakeConsole {
+        fn set_colors(
+            &mut self,
+            fg: Option<anstyle::AnsiColor>,
+            bg: Option<anstyle::AnsiColor>,
+        ) -> std::io::Result<()> {
+            self.0.borrow_mut().push(format!("<{fg:?} on {bg:?}>"));
+            Ok(())
+        }
+    }
+
+    struct Stream(AsyncWincon<Recorder, FakeConsole>);
+
+    impl tokio::io::AsyncWrite for Stream {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<std::io::Result<usize>> {
+            self.get_mut().0.tokio_poll_write(cx, buf)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            self.get_mut().0.tokio_poll_flush(cx)
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+            self.get_mut().0.tokio_poll_shutdown(cx)
+        }
+    }
+
+    #[test]
+    fn colors_in_step_with_writes() {
+        use tokio::io::AsyncWriteExt as _;
+
+        let log = Log::default();
+        let recorder = Recorder {
+            log: log.clone(),
+            buffered: Vec::new(),
+            ready: false,
+        };
+        let mut stream = Stream(AsyncWincon::new(recorder, FakeConsole(log.clone())));
+        futures::executor::block_on(async {
+            stream
+                .write_all(b"plain \x1b[31mred\x1b[0m \x1b[44mon blue\x1b[0m")
+                .await
+                .unwrap();
+            stream.write_all(b" plain").await.unwrap();
+            stream.shutdown().await.unwrap();
+        });
+        assert_eq!(
+            *log.borrow(),
+            [
+                "plain ",
+                "<Some(Red) on None>",
+                "red",
+                "<None on None>",
+                " ",
+                "<None on Some(Blue)>",
+                "on blue",
+                "<None on None>",
+                " plain",
+            ]
+        );
+    }
+}
@@ -1,6 +1,10 @@
 use crate::stream::AsLockedWrite;
+#[cfg(feature = "auto")]
+use crate::stream::IsTerminal;
 use crate::stream::RawStream;
 use crate::ColorChoice;
+#[cfg(feature = "auto")]
+use crate::DownsampleStream;
 use crate::StripStream;
 #[cfg(all(windows, feature = "wincon"))]
 use crate::WinconStream;
@@ -9,6 +13,7 @@ use crate::WinconStream;
 ///
 /// This includes
 /// - Stripping colors for non-terminals
+/// - Downsampling truecolor and 256-color escape codes for terminals that don't support them
 /// - Respecting env variables like [NO_COLOR](https://no-color.org/) or [CLICOLOR](https://bixense.com/clicolors/)
 /// - *(windows)* Falling back to the wincon API where [ENABLE_VIRTUAL_TERMINAL_PROCESSING](https://learn.microsoft.com/en-us/windows/console/console-virtual-terminal-sequences#output-sequences) is unsupported
 ///
@@ -24,6 +29,8 @@ pub struct AutoStream<S: RawStream> {
 enum StreamInner<S: RawStream> {
     PassThrough(S),
     Strip(StripStream<S>),
+    #[cfg(feature = "auto")]
+    Downsample(DownsampleStream<S>),
     #[cfg(all(windows, feature = "wincon"))]
     Wincon(WinconStream<S>),
 }
@@ -106,6 +113,8 @@ where
     }
 
     /// Force color, no matter what the inner `Write` supports.
+    ///
+    /// Colors will be downsampled for terminals that don't report truecolor support.
     #[inline]
     pub fn always(raw: S) -> Self {
         if cfg!(windows) {
@@ -118,11 +127,41 @@ where
             if use_wincon {
                 Self::wincon(raw).unwrap_or_else(|raw| Self::always_ansi_(raw))
             } else {
-                Self::always_ansi_(raw)
+                Self::always_downsample(raw)
             }
         } else {
-            Self::always_ansi(raw)
+            Self::always_downsample(raw)
+        }
+    }
+
+    #[inline]
+    fn always_downsample(raw: S) -> Self {
+        #[cfg(feature = "auto")]
+        if raw.is_terminal() {
+            match anstyle_query::color_level() {
+                anstyle_query::ColorLevel::TrueColor => {}
+                anstyle_query::ColorLevel::Ansi256 => return Self::downsample_xterm(raw),
+                anstyle_query::ColorLevel::Ansi16 | anstyle_query::ColorLevel::None => {
+                    return Self::downsample_ansi(raw);
+                }
+            }
         }
+        Self::always_ansi_(raw)
+    }
+
+    #[cfg(feature = "auto")]
+    #[inline]
+    fn downsample_xterm(raw: S) -> Self {
+        let inner = StreamInner::Downsample(DownsampleStream::xterm(raw));
+        Self { inner }
+    }
+
+    #[cfg(feature = "auto")]
+    #[inline]
+    fn downsample_ansi(raw: S) -> Self {
+        let palette = anstyle_lossy::palette::Palette::default();
+        let inner = StreamInner::Downsample(DownsampleStream::ansi(raw, palette));
+        Self { inner }
     }
 
     /// Only pass printable data to the inner `Write`.
@@ -152,6 +191,8 @@ where
         match self.inner {
             StreamInner::PassThrough(w) => w,
             StreamInner::Strip(w) => w.into_inner(),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.into_inner(),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(w) => w.into_inner(),
         }
@@ -163,6 +204,8 @@ where
         match &self.inner {
             StreamInner::PassThrough(w) => w,
             StreamInner::Strip(w) => w.as_inner(),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.as_inner(),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(w) => w.as_inner(),
         }
@@ -174,6 +217,8 @@ where
         match &self.inner {
             StreamInner::PassThrough(w) => w.is_terminal(),
             StreamInner::Strip(w) => w.is_terminal(),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.is_terminal(),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(_) => true, // its only ever a terminal
         }
@@ -188,6 +233,7 @@ where
         match &self.inner {
             StreamInner::PassThrough(_) => ColorChoice::AlwaysAnsi,
             StreamInner::Strip(_) => ColorChoice::Never,
+            StreamInner::Downsample(_) => ColorChoice::Always,
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(_) => ColorChoice::Always,
         }
@@ -195,7 +241,7 @@ where
 }
 
 #[cfg(feature = "auto")]
-fn choice(raw: &dyn RawStream) -> ColorChoice {
+pub(crate) fn choice(raw: &dyn IsTerminal) -> ColorChoice {
     let choice = ColorChoice::global();
     match choice {
         ColorChoice::Auto => {
@@ -233,6 +279,8 @@ impl AutoStream<std::io::Stdout> {
         let inner = match self.inner {
             StreamInner::PassThrough(w) => StreamInner::PassThrough(w.lock()),
             StreamInner::Strip(w) => StreamInner::Strip(w.lock()),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => StreamInner::Downsample(w.lock()),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(w) => StreamInner::Wincon(w.lock()),
         };
@@ -251,6 +299,8 @@ impl AutoStream<std::io::Stderr> {
         let inner = match self.inner {
             StreamInner::PassThrough(w) => StreamInner::PassThrough(w.lock()),
             StreamInner::Strip(w) => StreamInner::Strip(w.lock()),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => StreamInner::Downsample(w.lock()),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(w) => StreamInner::Wincon(w.lock()),
         };
@@ -268,6 +318,8 @@ where
         match &mut self.inner {
             StreamInner::PassThrough(w) => w.as_locked_write().write(buf),
             StreamInner::Strip(w) => w.write(buf),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.write(buf),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(w) => w.write(buf),
         }
@@ -277,6 +329,8 @@ where
         match &mut self.inner {
             StreamInner::PassThrough(w) => w.as_locked_write().write_vectored(bufs),
             StreamInner::Strip(w) => w.write_vectored(bufs),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.write_vectored(bufs),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(w) => w.write_vectored(bufs),
         }
@@ -287,6 +341,8 @@ where
         match &mut self.inner {
             StreamInner::PassThrough(w) => w.as_locked_write().flush(),
             StreamInner::Strip(w) => w.flush(),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.flush(),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(w) => w.flush(),
         }
@@ -296,6 +352,8 @@ where
         match &mut self.inner {
             StreamInner::PassThrough(w) => w.as_locked_write().write_all(buf),
             StreamInner::Strip(w) => w.write_all(buf),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.write_all(buf),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(w) => w.write_all(buf),
         }
@@ -306,6 +364,8 @@ where
         match &mut self.inner {
             StreamInner::PassThrough(w) => w.as_locked_write().write_fmt(args),
             StreamInner::Strip(w) => w.write_fmt(args),
+            #[cfg(feature = "auto")]
+            StreamInner::Downsample(w) => w.write_fmt(args),
             #[cfg(all(windows, feature = "wincon"))]
             StreamInner::Wincon(w) => w.write_fmt(args),
         }
@@ -0,0 +1,225 @@
+use std::borrow::Cow;
+
+use crate::adapter::DownsampleBytes;
+use crate::stream::AsLockedWrite;
+use crate::stream::IsTerminal;
+
+/// Rewrite colors for the inner `Write`s capabilities
+///
+/// See [`DownsampleBytes`]
+#[derive(Debug)]
+pub struct DownsampleStream<S>
+where
+    S: std::io::Write,
+{
+    raw: S,
+    state: DownsampleBytes,
+}
+
+impl<S> DownsampleStream<S>
+where
+    S: std::io::Write,
+{
+    /// Downsample colors to the 256 colors of [`anstyle::Ansi256Color`]
+    #[inline]
+    pub fn xterm(raw: S) -> Self {
+        Self {
+            raw,
+            state: DownsampleBytes::xterm(),
+        }
+    }
+
+    /// Downsample colors to the 16 colors of [`anstyle::AnsiColor`]
+    ///
+    /// `palette` is used for finding the closest color.  See [`anstyle_lossy::palette`].
+    #[inline]
+    pub fn ansi(raw: S, palette: anstyle_lossy::palette::Palette) -> Self {
+        Self {
+            raw,
+            state: DownsampleBytes::ansi(palette),
+        }
+    }
+
+    /// Get the wrapped [`std::io::Write`]
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.raw
+    }
+
+    /// Get the wrapped [`std::io::Write`]
+    #[inline]
+    pub fn as_inner(&self) -> &S {
+        &self.raw
+    }
+}
+
+impl<S> DownsampleStream<S>
+where
+    S: std::io::Write,
+    S: IsTerminal,
+{
+    /// Returns `true` if the descriptor/handle refers to a terminal/tty.
+    #[inline]
+    pub fn is_terminal(&self) -> bool {
+        self.raw.is_terminal()
+    }
+}
+
+impl DownsampleStream<std::io::Stdout> {
+    /// Get exclusive access to the `DownsampleStream`
+    ///
+    /// Why?
+    /// - Faster performance when writing in a loop
+    /// - Avoid other threads interleaving output with the current thread
+    #[inline]
+    pub fn lock(self) -> DownsampleStream<std::io::StdoutLock<'static>> {
+        DownsampleStream {
+            raw: self.raw.lock(),
+            state: self.state,
+        }
+    }
+}
+
+impl DownsampleStream<std::io::Stderr> {
+    /// Get exclusive access to the `DownsampleStream`
+    ///
+    /// Why?
+    /// - Faster performance when writing in a loop
+    /// - Avoid other threads interleaving output with the current thread
+    #[inline]
+    pub fn lock(self) -> DownsampleStream<std::io::StderrLock<'static>> {
+        DownsampleStream {
+            raw: self.raw.lock(),
+            state: self.state,
+        }
+    }
+}
+
+impl<S> std::io::Write for DownsampleStream<S>
+where
+    S: std::io::Write,
+    S: AsLockedWrite,
+{
+    // Must forward all calls to ensure locking happens appropriately
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        write(&mut self.raw.as_locked_write(), &mut self.state, buf)
+    }
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        let buf = bufs
+            .iter()
+            .find(|b| !b.is_empty())
+            .map(|b| &**b)
+            .unwrap_or(&[][..]);
+        self.write(buf)
+    }
+    // is_write_vectored: nightly only
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.raw.as_locked_write().flush()
+    }
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        write_all(&mut self.raw.as_locked_write(), &mut self.state, buf)
+    }
+    // write_all_vectored: nightly only
+    #[inline]
+    fn write_fmt(&mut self, args: std::fmt::Arguments<'_>) -> std::io::Result<()> {
+        write_fmt(&mut self.raw.as_locked_write(), &mut self.state, args)
+    }
+}
+
+fn write(
+    raw: &mut dyn std::io::Write,
+    state: &mut DownsampleBytes,
+    buf: &[u8],
+) -> std::io::Result<usize> {
+    let initial_state = state.clone();
+
+    for segment in state.downsample_next(buf) {
+        match segment {
+            Cow::Borrowed(passthrough) => {
+                let written = raw.write(passthrough)?;
+                if passthrough.len() != written {
+                    let divergence = &passthrough[written..];
+                    let offset = offset_to(buf, divergence);
+                    *state = initial_state;
+                    state.downsample_next(&buf[..offset]).last();
+                    return Ok(offset);
+                }
+            }
+            // Rewritten escapes can't be partially written
+            Cow::Owned(escape) => raw.write_all(&escape)?,
+        }
+    }
+    Ok(buf.len())
+}
+
+fn write_all(
+    raw: &mut dyn std::io::Write,
+    state: &mut DownsampleBytes,
+    buf: &[u8],
+) -> std::io::Result<()> {
+    for segment in state.downsample_next(buf) {
+        raw.write_all(&segment)?;
+    }
+    Ok(())
+}
+
+fn write_fmt(
+    raw: &mut dyn std::io::Write,
+    state: &mut DownsampleBytes,
+    args: std::fmt::Arguments<'_>,
+) -> std::io::Result<()> {
+    let write_all = |buf: &[u8]| write_all(raw, state, buf);
+    crate::fmt::Adapter::new(write_all).write_fmt(args)
+}
+
+#[inline]
+fn offset_to(total: &[u8], subslice: &[u8]) -> usize {
+    let total = total.as_ptr();
+    let subslice = subslice.as_ptr();
+
+    debug_assert!(
+        total <= subslice,
+        "`Offset::offset_to` only accepts slices of `self`"
+    );
+    subslice as usize - total as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::io::Write as _;
+
+    proptest! {
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn write_all_no_escapes(s in "\\PC*") {
+            let buffer = Vec::new();
+            let mut stream = DownsampleStream::xterm(buffer);
+            stream.write_all(s.as_bytes()).unwrap();
+            let buffer = stream.into_inner();
+            let actual = std::str::from_utf8(buffer.as_ref()).unwrap();
+            assert_eq!(s, actual);
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn write_byte_truecolor(r: u8, g: u8, b: u8, s in "\\PC*") {
+            let input = format!("\x1b[38;2;{r};{g};{b}m{s}\x1b[0m");
+            let buffer = Vec::new();
+            let mut stream = DownsampleStream::xterm(buffer);
+            for byte in input.as_bytes() {
+                stream.write_all(&[*byte]).unwrap();
+            }
+            let buffer = stream.into_inner();
+            let actual = std::str::from_utf8(buffer.as_ref()).unwrap();
+            let index = anstyle_lossy::rgb_to_xterm(anstyle::RgbColor(r, g, b)).index();
+            let expected = format!("\x1b[38;5;{index}m{s}\x1b[0m");
+            assert_eq!(expected, actual);
+        }
+    }
+}
@@ -42,14 +42,29 @@ pub mod stream;
 #[macro_use]
 pub mod _macros;
 
+#[cfg(any(feature = "tokio", feature = "futures"))]
+mod async_auto;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+mod async_strip;
+#[cfg(all(any(feature = "tokio", feature = "futures"), feature = "wincon"))]
+#[cfg_attr(not(windows), allow(dead_code))]
+mod async_wincon;
 mod auto;
 mod buffer;
+mod downsample;
 mod fmt;
+mod sanitize;
 mod strip;
 #[cfg(all(windows, feature = "wincon"))]
 mod wincon;
 
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub use async_auto::AsyncAutoStream;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub use async_strip::AsyncStripStream;
 pub use auto::AutoStream;
+pub use downsample::DownsampleStream;
+pub use sanitize::SanitizeStream;
 pub use strip::StripStream;
 #[cfg(all(windows, feature = "wincon"))]
 pub use wincon::WinconStream;
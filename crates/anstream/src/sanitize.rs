@@ -0,0 +1,227 @@
+use std::borrow::Cow;
+
+use crate::adapter::SanitizeBytes;
+use crate::adapter::SanitizePolicy;
+use crate::stream::AsLockedWrite;
+use crate::stream::IsTerminal;
+
+/// Neutralize escape sequences before passing data to the inner `Write`
+///
+/// See [`SanitizeBytes`]
+#[derive(Debug)]
+pub struct SanitizeStream<S>
+where
+    S: std::io::Write,
+{
+    raw: S,
+    state: SanitizeBytes,
+}
+
+impl<S> SanitizeStream<S>
+where
+    S: std::io::Write,
+{
+    /// Only pass along the sequences allowed by `policy`
+    #[inline]
+    pub fn new(raw: S, policy: SanitizePolicy) -> Self {
+        Self {
+            raw,
+            state: SanitizeBytes::new(policy),
+        }
+    }
+
+    /// Get the wrapped [`std::io::Write`]
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.raw
+    }
+
+    /// Get the wrapped [`std::io::Write`]
+    #[inline]
+    pub fn as_inner(&self) -> &S {
+        &self.raw
+    }
+}
+
+impl<S> SanitizeStream<S>
+where
+    S: std::io::Write,
+    S: IsTerminal,
+{
+    /// Returns `true` if the descriptor/handle refers to a terminal/tty.
+    #[inline]
+    pub fn is_terminal(&self) -> bool {
+        self.raw.is_terminal()
+    }
+}
+
+impl SanitizeStream<std::io::Stdout> {
+    /// Get exclusive access to the `SanitizeStream`
+    ///
+    /// Why?
+    /// - Faster performance when writing in a loop
+    /// - Avoid other threads interleaving output with the current thread
+    #[inline]
+    pub fn lock(self) -> SanitizeStream<std::io::StdoutLock<'static>> {
+        SanitizeStream {
+            raw: self.raw.lock(),
+            state: self.state,
+        }
+    }
+}
+
+impl SanitizeStream<std::io::Stderr> {
+    /// Get exclusive access to the `SanitizeStream`
+    ///
+    /// Why?
+    /// - Faster performance when writing in a loop
+    /// - Avoid other threads interleaving output with the current thread
+    #[inline]
+    pub fn lock(self) -> SanitizeStream<std::io::StderrLock<'static>> {
+        SanitizeStream {
+            raw: self.raw.lock(),
+            state: self.state,
+        }
+    }
+}
+
+impl<S> std::io::Write for SanitizeStream<S>
+where
+    S: std::io::Write,
+    S: AsLockedWrite,
+{
+    // Must forward all calls to ensure locking happens appropriately
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        write(&mut self.raw.as_locked_write(), &mut self.state, buf)
+    }
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        let buf = bufs
+            .iter()
+            .find(|b| !b.is_empty())
+            .map(|b| &**b)
+            .unwrap_or(&[][..]);
+        self.write(buf)
+    }
+    // is_write_vectored: nightly only
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.raw.as_locked_write().flush()
+    }
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        write_all(&mut self.raw.as_locked_write(), &mut self.state, buf)
+    }
+    // write_all_vectored: nightly only
+    #[inline]
+    fn write_fmt(&mut self, args: std::fmt::Arguments<'_>) -> std::io::Result<()> {
+        write_fmt(&mut self.raw.as_locked_write(), &mut self.state, args)
+    }
+}
+
+fn write(
+    raw: &mut dyn std::io::Write,
+    state: &mut SanitizeBytes,
+    buf: &[u8],
+) -> std::io::Result<usize> {
+    let initial_state = state.clone();
+
+    for segment in state.sanitize_next(buf) {
+        match segment {
+            Cow::Borrowed(passthrough) => {
+                let written = raw.write(passthrough)?;
+                if passthrough.len() != written {
+                    let divergence = &passthrough[written..];
+                    let offset = offset_to(buf, divergence);
+                    *state = initial_state;
+                    state.sanitize_next(&buf[..offset]).last();
+                    return Ok(offset);
+                }
+            }
+            // Re-encoded escapes can't be partially written
+            Cow::Owned(escape) => raw.write_all(&escape)?,
+        }
+    }
+    Ok(buf.len())
+}
+
+fn write_all(
+    raw: &mut dyn std::io::Write,
+    state: &mut SanitizeBytes,
+    buf: &[u8],
+) -> std::io::Result<()> {
+    for segment in state.sanitize_next(buf) {
+        raw.write_all(&segment)?;
+    }
+    Ok(())
+}
+
+fn write_fmt(
+    raw: &mut dyn std::io::Write,
+    state: &mut SanitizeBytes,
+    args: std::fmt::Arguments<'_>,
+) -> std::io::Result<()> {
+    let write_all = |buf: &[u8]| write_all(raw, state, buf);
+    crate::fmt::Adapter::new(write_all).write_fmt(args)
+}
+
+#[inline]
+fn offset_to(total: &[u8], subslice: &[u8]) -> usize {
+    let total = total.as_ptr();
+    let subslice = subslice.as_ptr();
+
+    debug_assert!(
+        total <= subslice,
+        "`Offset::offset_to` only accepts slices of `self`"
+    );
+    subslice as usize - total as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+    use std::io::Write as _;
+
+    proptest! {
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn write_all_no_escapes(s in "\\PC*") {
+            let buffer = Vec::new();
+            let mut stream = SanitizeStream::new(buffer, SanitizePolicy::new());
+            stream.write_all(s.as_bytes()).unwrap();
+            let buffer = stream.into_inner();
+            let actual = std::str::from_utf8(buffer.as_ref()).unwrap();
+            assert_eq!(s, actual);
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn write_byte_no_escapes(s in "\\PC*") {
+            let buffer = Vec::new();
+            let mut stream = SanitizeStream::new(buffer, SanitizePolicy::new());
+            for byte in s.as_bytes() {
+                stream.write_all(&[*byte]).unwrap();
+            }
+            let buffer = stream.into_inner();
+            let actual = std::str::from_utf8(buffer.as_ref()).unwrap();
+            assert_eq!(s, actual);
+        }
+
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn write_byte_clipboard(s in "\\PC*") {
+            let input = format!("\x1b]52;c;{s}\x07\x1b[1m{s}\x1b[m");
+            let buffer = Vec::new();
+            let mut stream = SanitizeStream::new(buffer, SanitizePolicy::new());
+            for byte in input.as_bytes() {
+                stream.write_all(&[*byte]).unwrap();
+            }
+            let buffer = stream.into_inner();
+            let actual = std::str::from_utf8(buffer.as_ref()).unwrap();
+            let expected = format!("\x1b[1m{s}\x1b[m");
+            assert_eq!(expected, actual);
+        }
+    }
+}
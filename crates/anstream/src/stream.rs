@@ -33,6 +33,41 @@ impl RawStream for std::fs::File {}
 #[allow(deprecated)]
 impl RawStream for crate::Buffer {}
 
+/// Required functionality for underlying async writers for adaptation
+///
+/// To be written to, the stream must also implement [`tokio::io::AsyncWrite`] (with the `tokio`
+/// feature) or [`futures_io::AsyncWrite`] (with the `futures` feature).
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub trait AsyncRawStream: IsTerminal + private::Sealed {}
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+impl<T: AsyncRawStream + ?Sized> AsyncRawStream for &mut T {}
+#[cfg(any(feature = "tokio", feature = "futures"))]
+impl<T: AsyncRawStream + ?Sized> AsyncRawStream for Box<T> {}
+
+#[cfg(feature = "tokio")]
+impl AsyncRawStream for tokio::io::Stdout {}
+
+#[cfg(feature = "tokio")]
+impl AsyncRawStream for tokio::io::Stderr {}
+
+#[cfg(feature = "tokio")]
+impl AsyncRawStream for dyn tokio::io::AsyncWrite + Unpin {}
+#[cfg(feature = "tokio")]
+impl AsyncRawStream for dyn tokio::io::AsyncWrite + Unpin + Send {}
+#[cfg(feature = "tokio")]
+impl AsyncRawStream for dyn tokio::io::AsyncWrite + Unpin + Send + Sync {}
+
+#[cfg(feature = "futures")]
+impl AsyncRawStream for dyn futures_io::AsyncWrite + Unpin {}
+#[cfg(feature = "futures")]
+impl AsyncRawStream for dyn futures_io::AsyncWrite + Unpin + Send {}
+#[cfg(feature = "futures")]
+impl AsyncRawStream for dyn futures_io::AsyncWrite + Unpin + Send + Sync {}
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+impl AsyncRawStream for Vec<u8> {}
+
 /// Trait to determine if a descriptor/handle refers to a terminal/tty.
 pub trait IsTerminal: private::Sealed {
     /// Returns `true` if the descriptor/handle refers to a terminal/tty.
@@ -131,6 +166,72 @@ impl IsTerminal for crate::Buffer {
     }
 }
 
+#[cfg(feature = "tokio")]
+impl IsTerminal for tokio::io::Stdout {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        // Writes to the same descriptor/handle
+        is_terminal_polyfill::IsTerminal::is_terminal(&std::io::stdout())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl IsTerminal for tokio::io::Stderr {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        // Writes to the same descriptor/handle
+        is_terminal_polyfill::IsTerminal::is_terminal(&std::io::stderr())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl IsTerminal for dyn tokio::io::AsyncWrite + Unpin {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl IsTerminal for dyn tokio::io::AsyncWrite + Unpin + Send {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl IsTerminal for dyn tokio::io::AsyncWrite + Unpin + Send + Sync {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "futures")]
+impl IsTerminal for dyn futures_io::AsyncWrite + Unpin {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "futures")]
+impl IsTerminal for dyn futures_io::AsyncWrite + Unpin + Send {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "futures")]
+impl IsTerminal for dyn futures_io::AsyncWrite + Unpin + Send + Sync {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
 /// Lock a stream
 pub trait AsLockedWrite: private::Sealed {
     /// Locked writer type
@@ -257,13 +358,62 @@ impl AsLockedWrite for crate::Buffer {
     }
 }
 
+/// The console `raw` writes to, for styling it with the wincon API
+#[cfg(all(
+    windows,
+    feature = "wincon",
+    any(feature = "tokio", feature = "futures")
+))]
+pub(crate) fn async_console<S: AsyncRawStream + ?Sized>(
+    raw: &S,
+) -> Option<crate::async_wincon::StdioConsole> {
+    private::Sealed::async_console(raw)
+}
+
 mod private {
     #[allow(unnameable_types)]
-    pub trait Sealed {}
+    pub trait Sealed {
+        /// The console an async writer writes to, for styling it with the wincon API
+        #[cfg(all(
+            windows,
+            feature = "wincon",
+            any(feature = "tokio", feature = "futures")
+        ))]
+        fn async_console(&self) -> Option<crate::async_wincon::StdioConsole> {
+            None
+        }
+    }
 
-    impl<T: Sealed + ?Sized> Sealed for &T {}
-    impl<T: Sealed + ?Sized> Sealed for &mut T {}
-    impl<T: Sealed + ?Sized> Sealed for Box<T> {}
+    impl<T: Sealed + ?Sized> Sealed for &T {
+        #[cfg(all(
+            windows,
+            feature = "wincon",
+            any(feature = "tokio", feature = "futures")
+        ))]
+        fn async_console(&self) -> Option<crate::async_wincon::StdioConsole> {
+            (**self).async_console()
+        }
+    }
+    impl<T: Sealed + ?Sized> Sealed for &mut T {
+        #[cfg(all(
+            windows,
+            feature = "wincon",
+            any(feature = "tokio", feature = "futures")
+        ))]
+        fn async_console(&self) -> Option<crate::async_wincon::StdioConsole> {
+            (**self).async_console()
+        }
+    }
+    impl<T: Sealed + ?Sized> Sealed for Box<T> {
+        #[cfg(all(
+            windows,
+            feature = "wincon",
+            any(feature = "tokio", feature = "futures")
+        ))]
+        fn async_console(&self) -> Option<crate::async_wincon::StdioConsole> {
+            (**self).async_console()
+        }
+    }
 
     impl Sealed for std::io::Stdout {}
 
@@ -283,6 +433,36 @@ mod private {
 
     #[allow(deprecated)]
     impl Sealed for crate::Buffer {}
+
+    #[cfg(feature = "tokio")]
+    impl Sealed for tokio::io::Stdout {
+        #[cfg(all(windows, feature = "wincon"))]
+        fn async_console(&self) -> Option<crate::async_wincon::StdioConsole> {
+            Some(crate::async_wincon::StdioConsole::Stdout)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl Sealed for tokio::io::Stderr {
+        #[cfg(all(windows, feature = "wincon"))]
+        fn async_console(&self) -> Option<crate::async_wincon::StdioConsole> {
+            Some(crate::async_wincon::StdioConsole::Stderr)
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl Sealed for dyn tokio::io::AsyncWrite + Unpin {}
+    #[cfg(feature = "tokio")]
+    impl Sealed for dyn tokio::io::AsyncWrite + Unpin + Send {}
+    #[cfg(feature = "tokio")]
+    impl Sealed for dyn tokio::io::AsyncWrite + Unpin + Send + Sync {}
+
+    #[cfg(feature = "futures")]
+    impl Sealed for dyn futures_io::AsyncWrite + Unpin {}
+    #[cfg(feature = "futures")]
+    impl Sealed for dyn futures_io::AsyncWrite + Unpin + Send {}
+    #[cfg(feature = "futures")]
+    impl Sealed for dyn futures_io::AsyncWrite + Unpin + Send + Sync {}
 }
 
 #[cfg(test)]
@@ -32,7 +32,8 @@ pub fn to_ansi_term(astyle: anstyle::Style) -> ansi_term::Style {
     if effects.contains(anstyle::Effects::UNDERLINE) {
         style = style.underline();
     }
-    if effects.contains(anstyle::Effects::BLINK) {
+    if effects.contains(anstyle::Effects::BLINK) || effects.contains(anstyle::Effects::RAPID_BLINK)
+    {
         style = style.blink();
     }
     if effects.contains(anstyle::Effects::INVERT) {
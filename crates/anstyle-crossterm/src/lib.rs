@@ -36,8 +36,20 @@ pub fn to_crossterm(astyle: anstyle::Style) -> crossterm::style::ContentStyle {
         attributes.set(crossterm::style::Attribute::Hidden);
     }
     if effects.contains(anstyle::Effects::STRIKETHROUGH) {
+        attributes.set(crossterm::style::Attribute::CrossedOut);
+    }
+    if effects.contains(anstyle::Effects::OVERLINE) {
         attributes.set(crossterm::style::Attribute::OverLined);
     }
+    if effects.contains(anstyle::Effects::RAPID_BLINK) {
+        attributes.set(crossterm::style::Attribute::RapidBlink);
+    }
+    if effects.contains(anstyle::Effects::FRAMED) {
+        attributes.set(crossterm::style::Attribute::Framed);
+    }
+    if effects.contains(anstyle::Effects::ENCIRCLED) {
+        attributes.set(crossterm::style::Attribute::Encircled);
+    }
 
     crossterm::style::ContentStyle {
         foreground_color,
@@ -88,6 +100,25 @@ fn rgb_to_ansi_color(color: anstyle::RgbColor) -> crossterm::style::Color {
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn strikethrough_is_crossed_out() {
+        let attributes = to_crossterm(anstyle::Style::new().strikethrough()).attributes;
+        assert!(attributes.has(crossterm::style::Attribute::CrossedOut));
+        assert!(!attributes.has(crossterm::style::Attribute::OverLined));
+    }
+
+    #[test]
+    fn overline_is_overlined() {
+        let attributes = to_crossterm(anstyle::Style::new().overline()).attributes;
+        assert!(attributes.has(crossterm::style::Attribute::OverLined));
+        assert!(!attributes.has(crossterm::style::Attribute::CrossedOut));
+    }
+}
+
 #[doc = include_str!("../README.md")]
 #[cfg(doctest)]
 pub struct ReadmeDoctests;
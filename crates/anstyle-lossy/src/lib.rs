@@ -7,6 +7,11 @@
 
 pub mod palette;
 
+mod matcher;
+
+pub use matcher::Matcher;
+pub use matcher::Metric;
+
 use anstyle::RgbColor as Rgb;
 
 /// Lossily convert from any color to RGB
@@ -106,6 +111,8 @@ pub const fn xterm_to_ansi(
 ///
 /// As the palette for 4-bit colors is terminal/user defined, a [`palette::Palette`] must be
 /// provided to match against.
+///
+/// For more accurate matching, see [`Matcher`].
 pub const fn rgb_to_ansi(
     color: anstyle::RgbColor,
     palette: palette::Palette,
@@ -114,6 +121,8 @@ pub const fn rgb_to_ansi(
 }
 
 /// Lossily convert an RGB value to the 256-color palette
+///
+/// For more accurate matching, see [`Matcher`].
 pub const fn rgb_to_xterm(color: anstyle::RgbColor) -> anstyle::Ansi256Color {
     // Skip placeholders
     let index = find_xterm_match(color);
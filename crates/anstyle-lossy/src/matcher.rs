@@ -0,0 +1,431 @@
+use anstyle::RgbColor as Rgb;
+
+use crate::palette::Palette;
+use crate::XTERM_COLORS;
+
+/// How to measure the difference between two colors
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Metric {
+    /// Low-cost approximation from <https://www.compuphase.com/cmetric.htm>
+    ///
+    /// This is what the `const` conversions, like [`rgb_to_xterm`][crate::rgb_to_xterm], use.
+    #[default]
+    Redmean,
+    /// [CIEDE2000](https://en.wikipedia.org/wiki/Color_difference#CIEDE2000) within CIELAB
+    ///
+    /// The most accurate but also the most expensive
+    Ciede2000,
+    /// Euclidean distance within [OKLab](https://bottosson.github.io/posts/oklab/)
+    Oklab,
+}
+
+impl Metric {
+    fn distance(self, c1: Rgb, c2: Rgb) -> f64 {
+        match self {
+            Self::Redmean => crate::distance(c1, c2) as f64,
+            Self::Ciede2000 => ciede2000(Lab::from_rgb(c1), Lab::from_rgb(c2)),
+            Self::Oklab => {
+                let c1 = Oklab::from_rgb(c1);
+                let c2 = Oklab::from_rgb(c2);
+                (c1.l - c2.l).powi(2) + (c1.a - c2.a).powi(2) + (c1.b - c2.b).powi(2)
+            }
+        }
+    }
+}
+
+/// Lossy color conversions with a configurable [`Metric`]
+///
+/// These are slower than the `const` conversions, like [`rgb_to_xterm`][crate::rgb_to_xterm],
+/// which are equivalent to `Matcher::new()`.
+///
+/// # Example
+///
+/// ```rust
+/// use anstyle_lossy::Matcher;
+/// use anstyle_lossy::Metric;
+///
+/// let matcher = Matcher::new().metric(Metric::Ciede2000).prefer_greyscale(true);
+/// let grey = matcher.rgb_to_xterm(anstyle::RgbColor(100, 101, 100));
+/// assert_eq!(grey, anstyle::Ansi256Color(241));
+/// ```
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct Matcher {
+    metric: Metric,
+    prefer_greyscale: bool,
+}
+
+impl Matcher {
+    /// Match using [`Metric::Redmean`] without preferring greys
+    pub const fn new() -> Self {
+        Self {
+            metric: Metric::Redmean,
+            prefer_greyscale: false,
+        }
+    }
+
+    /// Select how to measure the difference between colors
+    pub const fn metric(mut self, metric: Metric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Match near-grey colors only against greys, like the 24-step greyscale ramp
+    ///
+    /// Otherwise, a slightly tinted color in the 256-color cube can be closer, making greys look
+    /// off.  Colors are considered near-grey when their RGB components are within 8 of each other.
+    pub const fn prefer_greyscale(mut self, yes: bool) -> Self {
+        self.prefer_greyscale = yes;
+        self
+    }
+
+    /// Lossily convert from any color to 256-color
+    pub fn color_to_xterm(&self, color: anstyle::Color) -> anstyle::Ansi256Color {
+        match color {
+            anstyle::Color::Ansi(color) => anstyle::Ansi256Color::from_ansi(color),
+            anstyle::Color::Ansi256(color) => color,
+            anstyle::Color::Rgb(color) => self.rgb_to_xterm(color),
+        }
+    }
+
+    /// Lossily convert from any color to 4-bit color
+    ///
+    /// As the palette for 4-bit colors is terminal/user defined, a [`Palette`] must be provided
+    /// to match against.
+    pub fn color_to_ansi(&self, color: anstyle::Color, palette: Palette) -> anstyle::AnsiColor {
+        match color {
+            anstyle::Color::Ansi(color) => color,
+            anstyle::Color::Ansi256(color) => self.xterm_to_ansi(color, palette),
+            anstyle::Color::Rgb(color) => self.rgb_to_ansi(color, palette),
+        }
+    }
+
+    /// Lossily convert from the 256-color palette to 4-bit color
+    ///
+    /// As the palette for 4-bit colors is terminal/user defined, a [`Palette`] must be provided
+    /// to match against.
+    pub fn xterm_to_ansi(
+        &self,
+        color: anstyle::Ansi256Color,
+        palette: Palette,
+    ) -> anstyle::AnsiColor {
+        match color.into_ansi() {
+            Some(ansi) => ansi,
+            None => self.rgb_to_ansi(XTERM_COLORS[color.index() as usize], palette),
+        }
+    }
+
+    /// Lossily convert an RGB value to a 4-bit color
+    ///
+    /// As the palette for 4-bit colors is terminal/user defined, a [`Palette`] must be provided
+    /// to match against.
+    pub fn rgb_to_ansi(&self, color: anstyle::RgbColor, palette: Palette) -> anstyle::AnsiColor {
+        let index = if self.prefer_greyscale && is_greyish(color) {
+            self.find_match(
+                color,
+                (0..palette.0.len()).filter(|i| is_greyish(palette.0[*i])),
+                |i| palette.0[i],
+            )
+        } else {
+            None
+        };
+        let index = index
+            .or_else(|| self.find_match(color, 0..palette.0.len(), |i| palette.0[i]))
+            .unwrap_or(0);
+        anstyle::Ansi256Color(index as u8)
+            .into_ansi()
+            .expect("palettes are limited to 16 colors")
+    }
+
+    /// Lossily convert an RGB value to the 256-color palette
+    pub fn rgb_to_xterm(&self, color: anstyle::RgbColor) -> anstyle::Ansi256Color {
+        // Skip placeholders
+        let candidates = 16..XTERM_COLORS.len();
+        let index = if self.prefer_greyscale && is_greyish(color) {
+            self.find_match(
+                color,
+                candidates.filter(|i| is_grey(XTERM_COLORS[*i])),
+                |i| XTERM_COLORS[i],
+            )
+        } else {
+            self.find_match(color, candidates, |i| XTERM_COLORS[i])
+        };
+        anstyle::Ansi256Color(index.unwrap_or(16) as u8)
+    }
+
+    /// Find the closest candidate, preferring the first on ties
+    fn find_match(
+        &self,
+        color: Rgb,
+        candidates: impl Iterator<Item = usize>,
+        lookup: impl Fn(usize) -> Rgb,
+    ) -> Option<usize> {
+        let mut best = None;
+        for index in candidates {
+            let distance = self.metric.distance(color, lookup(index));
+            match best {
+                Some((_, best_distance)) if best_distance <= distance => {}
+                _ => best = Some((index, distance)),
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+}
+
+fn is_grey(color: Rgb) -> bool {
+    color.r() == color.g() && color.g() == color.b()
+}
+
+fn is_greyish(color: Rgb) -> bool {
+    const TOLERANCE: u8 = 8;
+    let max = color.r().max(color.g()).max(color.b());
+    let min = color.r().min(color.g()).min(color.b());
+    max - min <= TOLERANCE
+}
+
+fn srgb_to_linear(component: u8) -> f64 {
+    let c = component as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// CIELAB, using the D65 white point
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Lab {
+    l: f64,
+    a: f64,
+    b: f64,
+}
+
+impl Lab {
+    fn from_rgb(color: Rgb) -> Self {
+        let r = srgb_to_linear(color.r());
+        let g = srgb_to_linear(color.g());
+        let b = srgb_to_linear(color.b());
+
+        let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / 0.95047;
+        let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+        let z = (0.0193339 * r + 0.1191920 * g + 0.9503041 * b) / 1.08883;
+
+        fn f(t: f64) -> f64 {
+            const DELTA: f64 = 6.0 / 29.0;
+            if DELTA.powi(3) < t {
+                t.cbrt()
+            } else {
+                t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+            }
+        }
+        let (fx, fy, fz) = (f(x), f(y), f(z));
+        Self {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+}
+
+/// See <https://bottosson.github.io/posts/oklab/>
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Oklab {
+    l: f64,
+    a: f64,
+    b: f64,
+}
+
+impl Oklab {
+    fn from_rgb(color: Rgb) -> Self {
+        let r = srgb_to_linear(color.r());
+        let g = srgb_to_linear(color.g());
+        let b = srgb_to_linear(color.b());
+
+        let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+        let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+        let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+        Self {
+            l: 0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            a: 1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            b: 0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        }
+    }
+}
+
+/// See <https://hajim.rochester.edu/ece/sites/gsharma/ciede2000/ciede2000noteCRNA.pdf>
+fn ciede2000(lab1: Lab, lab2: Lab) -> f64 {
+    let pow7 = |v: f64| v.powi(7);
+    let hue = |b: f64, a: f64| {
+        if a == 0.0 && b == 0.0 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        }
+    };
+
+    let c1 = lab1.a.hypot(lab1.b);
+    let c2 = lab2.a.hypot(lab2.b);
+    let c_bar = (c1 + c2) / 2.0;
+    let g = 0.5 * (1.0 - (pow7(c_bar) / (pow7(c_bar) + pow7(25.0))).sqrt());
+
+    let a1 = (1.0 + g) * lab1.a;
+    let a2 = (1.0 + g) * lab2.a;
+    let c1 = a1.hypot(lab1.b);
+    let c2 = a2.hypot(lab2.b);
+    let h1 = hue(lab1.b, a1);
+    let h2 = hue(lab2.b, a2);
+
+    let delta_l = lab2.l - lab1.l;
+    let delta_c = c2 - c1;
+    let delta_h = if c1 * c2 == 0.0 {
+        0.0
+    } else if (h2 - h1).abs() <= 180.0 {
+        h2 - h1
+    } else if 180.0 < h2 - h1 {
+        h2 - h1 - 360.0
+    } else {
+        h2 - h1 + 360.0
+    };
+    let delta_h = 2.0 * (c1 * c2).sqrt() * (delta_h.to_radians() / 2.0).sin();
+
+    let l_bar = (lab1.l + lab2.l) / 2.0;
+    let c_bar = (c1 + c2) / 2.0;
+    let h_bar = if c1 * c2 == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        (h1 + h2) / 2.0
+    } else if h1 + h2 < 360.0 {
+        (h1 + h2 + 360.0) / 2.0
+    } else {
+        (h1 + h2 - 360.0) / 2.0
+    };
+
+    let cos = |degrees: f64| degrees.to_radians().cos();
+    let t =
+        1.0 - 0.17 * cos(h_bar - 30.0) + 0.24 * cos(2.0 * h_bar) + 0.32 * cos(3.0 * h_bar + 6.0)
+            - 0.20 * cos(4.0 * h_bar - 63.0);
+    let delta_theta = 30.0 * (-((h_bar - 275.0) / 25.0).powi(2)).exp();
+    let r_c = 2.0 * (pow7(c_bar) / (pow7(c_bar) + pow7(25.0))).sqrt();
+    let s_l = 1.0 + 0.015 * (l_bar - 50.0).powi(2) / (20.0 + (l_bar - 50.0).powi(2)).sqrt();
+    let s_c = 1.0 + 0.045 * c_bar;
+    let s_h = 1.0 + 0.015 * c_bar * t;
+    let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+    let l = delta_l / s_l;
+    let c = delta_c / s_c;
+    let h = delta_h / s_h;
+    (l * l + c * c + h * h + r_t * c * h).sqrt()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ciede2000_reference() {
+        // From Sharma's test data
+        let cases = [
+            ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+            ((50.0, 0.0, 0.0), (50.0, -1.0, 2.0), 2.3669),
+            ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+            (
+                (60.2574, -34.0099, 36.2677),
+                (60.4626, -34.1751, 39.4387),
+                1.2644,
+            ),
+            (
+                (2.0776, 0.0795, -1.1350),
+                (0.9033, -0.0636, -0.5514),
+                0.9082,
+            ),
+        ];
+        for ((l1, a1, b1), (l2, a2, b2), expected) in cases {
+            let lab1 = Lab {
+                l: l1,
+                a: a1,
+                b: b1,
+            };
+            let lab2 = Lab {
+                l: l2,
+                a: a2,
+                b: b2,
+            };
+            let actual = ciede2000(lab1, lab2);
+            assert!((actual - expected).abs() < 0.0001, "{actual} != {expected}");
+            let actual = ciede2000(lab2, lab1);
+            assert!((actual - expected).abs() < 0.0001, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn lab_white() {
+        let white = Lab::from_rgb(Rgb(255, 255, 255));
+        assert!((white.l - 100.0).abs() < 0.01, "{white:?}");
+        assert!(white.a.abs() < 0.01, "{white:?}");
+        assert!(white.b.abs() < 0.01, "{white:?}");
+    }
+
+    #[test]
+    fn oklab_white() {
+        let white = Oklab::from_rgb(Rgb(255, 255, 255));
+        assert!((white.l - 1.0).abs() < 0.001, "{white:?}");
+        assert!(white.a.abs() < 0.001, "{white:?}");
+        assert!(white.b.abs() < 0.001, "{white:?}");
+    }
+
+    #[test]
+    fn default_matches_const() {
+        let matcher = Matcher::new();
+        for r in (0..=255).step_by(15) {
+            for g in (0..=255).step_by(15) {
+                for b in (0..=255).step_by(15) {
+                    let color = Rgb(r, g, b);
+                    assert_eq!(matcher.rgb_to_xterm(color), crate::rgb_to_xterm(color));
+                    assert_eq!(
+                        matcher.rgb_to_ansi(color, crate::palette::VGA),
+                        crate::rgb_to_ansi(color, crate::palette::VGA)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn exact_matches() {
+        for metric in [Metric::Redmean, Metric::Ciede2000, Metric::Oklab] {
+            let matcher = Matcher::new().metric(metric);
+            for color in XTERM_COLORS.iter().copied().skip(16) {
+                let actual = matcher.rgb_to_xterm(color);
+                assert_eq!(XTERM_COLORS[actual.index() as usize], color, "{metric:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn prefer_greyscale() {
+        for metric in [Metric::Redmean, Metric::Ciede2000, Metric::Oklab] {
+            let matcher = Matcher::new().metric(metric).prefer_greyscale(true);
+            for value in 0..=255 {
+                let actual = matcher.rgb_to_xterm(Rgb(value, value.saturating_add(4), value));
+                assert!(
+                    is_grey(XTERM_COLORS[actual.index() as usize]),
+                    "{metric:?} {value}: {actual:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn prefer_greyscale_ansi() {
+        let matcher = Matcher::new().prefer_greyscale(true);
+        assert_eq!(
+            matcher.rgb_to_ansi(Rgb(90, 90, 95), crate::palette::VGA),
+            anstyle::AnsiColor::BrightBlack
+        );
+        assert_eq!(
+            matcher.xterm_to_ansi(anstyle::Ansi256Color(250), crate::palette::VGA),
+            anstyle::AnsiColor::White
+        );
+    }
+}
@@ -0,0 +1,317 @@
+//! Popular color palettes for [`anstyle::AnsiColor`]
+//!
+//! Based on [wikipedia](https://en.wikipedia.org/wiki/ANSI_escape_code#3-bit_and_4-bit)
+//!
+//! To match what the user sees, a [`Palette`] can also be loaded from their terminal's theme,
+//! like with [`Palette::from_kitty`], or looked up with [`Palette::from_name`].  For the live
+//! terminal, enable the `query` feature for `Palette::from_terminal`.
+use anstyle::RgbColor as Rgb;
+
+mod parse;
+#[cfg(feature = "query")]
+mod query;
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+pub use parse::ParseError;
+
+/// A color palette for rendering 4-bit [`anstyle::AnsiColor`]
+#[allow(clippy::exhaustive_structs)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Palette(pub RawPalette);
+type RawPalette = [Rgb; 16];
+
+impl Palette {
+    /// Look up the [`anstyle::RgbColor`] in the palette
+    pub const fn get(&self, color: anstyle::AnsiColor) -> Rgb {
+        let color = anstyle::Ansi256Color::from_ansi(color);
+        *self.get_ansi256_ref(color)
+    }
+    const fn get_ansi256_ref(&self, color: anstyle::Ansi256Color) -> &Rgb {
+        let index = color.index() as usize;
+        &self.0[index]
+    }
+
+    pub(crate) const fn rgb_from_ansi(&self, color: anstyle::AnsiColor) -> anstyle::RgbColor {
+        self.get(color)
+    }
+
+    pub(crate) const fn rgb_from_index(&self, index: u8) -> Option<anstyle::RgbColor> {
+        let index = index as usize;
+        if index < self.0.len() {
+            Some(self.0[index])
+        } else {
+            None
+        }
+    }
+
+    pub(crate) const fn find_match(&self, color: anstyle::RgbColor) -> anstyle::AnsiColor {
+        let mut best_index = 0;
+        let mut best_distance = crate::distance(color, self.0[best_index]);
+
+        let mut index = best_index + 1;
+        while index < self.0.len() {
+            let distance = crate::distance(color, self.0[index]);
+            if distance < best_distance {
+                best_index = index;
+                best_distance = distance;
+            }
+
+            index += 1;
+        }
+
+        if let Some(color) = anstyle::Ansi256Color(best_index as u8).into_ansi() {
+            color
+        } else {
+            // Panic
+            #[allow(clippy::no_effect)]
+            ["best_index is out of bounds"][best_index];
+            // Make compiler happy
+            anstyle::AnsiColor::Black
+        }
+    }
+}
+
+/// # Bundled palettes
+impl Palette {
+    /// Look up a bundled palette by its name in [`NAMED`]
+    ///
+    /// Names are matched case-insensitively.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use anstyle_lossy::palette::Palette;
+    /// assert_eq!(Palette::from_name("Dracula"), Some(anstyle_lossy::palette::DRACULA));
+    /// assert_eq!(Palette::from_name("unknown"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        NAMED
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, palette)| *palette)
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        DEFAULT
+    }
+}
+
+impl std::ops::Index<anstyle::AnsiColor> for Palette {
+    type Output = Rgb;
+
+    #[inline]
+    fn index(&self, color: anstyle::AnsiColor) -> &Rgb {
+        let color = anstyle::Ansi256Color::from_ansi(color);
+        self.get_ansi256_ref(color)
+    }
+}
+
+impl From<RawPalette> for Palette {
+    fn from(raw: RawPalette) -> Self {
+        Self(raw)
+    }
+}
+
+/// Platform-specific default
+#[cfg(not(windows))]
+pub use VGA as DEFAULT;
+
+/// Platform-specific default
+#[cfg(windows)]
+pub use WIN10_CONSOLE as DEFAULT;
+
+/// Typical colors that are used when booting PCs and leaving them in text mode
+pub const VGA: Palette = Palette([
+    Rgb(0, 0, 0),
+    Rgb(170, 0, 0),
+    Rgb(0, 170, 0),
+    Rgb(170, 85, 0),
+    Rgb(0, 0, 170),
+    Rgb(170, 0, 170),
+    Rgb(0, 170, 170),
+    Rgb(170, 170, 170),
+    Rgb(85, 85, 85),
+    Rgb(255, 85, 85),
+    Rgb(85, 255, 85),
+    Rgb(255, 255, 85),
+    Rgb(85, 85, 255),
+    Rgb(255, 85, 255),
+    Rgb(85, 255, 255),
+    Rgb(255, 255, 255),
+]);
+
+/// Campbell theme, used as of Windows 10 version 1709.
+pub const WIN10_CONSOLE: Palette = Palette([
+    Rgb(12, 12, 12),
+    Rgb(197, 15, 31),
+    Rgb(19, 161, 14),
+    Rgb(193, 156, 0),
+    Rgb(0, 55, 218),
+    Rgb(136, 23, 152),
+    Rgb(58, 150, 221),
+    Rgb(204, 204, 204),
+    Rgb(118, 118, 118),
+    Rgb(231, 72, 86),
+    Rgb(22, 198, 12),
+    Rgb(249, 241, 165),
+    Rgb(59, 120, 255),
+    Rgb(180, 0, 158),
+    Rgb(97, 214, 214),
+    Rgb(242, 242, 242),
+]);
+
+/// Default colors of xterm
+pub const XTERM: Palette = Palette([
+    Rgb(0, 0, 0),
+    Rgb(205, 0, 0),
+    Rgb(0, 205, 0),
+    Rgb(205, 205, 0),
+    Rgb(0, 0, 238),
+    Rgb(205, 0, 205),
+    Rgb(0, 205, 205),
+    Rgb(229, 229, 229),
+    Rgb(127, 127, 127),
+    Rgb(255, 0, 0),
+    Rgb(0, 255, 0),
+    Rgb(255, 255, 0),
+    Rgb(92, 92, 255),
+    Rgb(255, 0, 255),
+    Rgb(0, 255, 255),
+    Rgb(255, 255, 255),
+]);
+
+/// Default colors of macOS's Terminal.app
+pub const TERMINAL_APP: Palette = Palette([
+    Rgb(0, 0, 0),
+    Rgb(153, 0, 0),
+    Rgb(0, 166, 0),
+    Rgb(153, 153, 0),
+    Rgb(0, 0, 178),
+    Rgb(178, 0, 178),
+    Rgb(0, 166, 178),
+    Rgb(191, 191, 191),
+    Rgb(102, 102, 102),
+    Rgb(229, 0, 0),
+    Rgb(0, 217, 0),
+    Rgb(229, 229, 0),
+    Rgb(0, 0, 255),
+    Rgb(229, 0, 229),
+    Rgb(0, 229, 229),
+    Rgb(229, 229, 229),
+]);
+
+/// Default colors of `PuTTY`
+pub const PUTTY: Palette = Palette([
+    Rgb(0, 0, 0),
+    Rgb(187, 0, 0),
+    Rgb(0, 187, 0),
+    Rgb(187, 187, 0),
+    Rgb(0, 0, 187),
+    Rgb(187, 0, 187),
+    Rgb(0, 187, 187),
+    Rgb(187, 187, 187),
+    Rgb(85, 85, 85),
+    Rgb(255, 85, 85),
+    Rgb(85, 255, 85),
+    Rgb(255, 255, 85),
+    Rgb(85, 85, 255),
+    Rgb(255, 85, 255),
+    Rgb(85, 255, 255),
+    Rgb(255, 255, 255),
+]);
+
+/// Tango, the default of GNOME Terminal
+pub const TANGO: Palette = Palette([
+    Rgb(46, 52, 54),
+    Rgb(204, 0, 0),
+    Rgb(78, 154, 6),
+    Rgb(196, 160, 0),
+    Rgb(52, 101, 164),
+    Rgb(117, 80, 123),
+    Rgb(6, 152, 154),
+    Rgb(211, 215, 207),
+    Rgb(85, 87, 83),
+    Rgb(239, 41, 41),
+    Rgb(138, 226, 52),
+    Rgb(252, 233, 79),
+    Rgb(114, 159, 207),
+    Rgb(173, 127, 168),
+    Rgb(52, 226, 226),
+    Rgb(238, 238, 236),
+]);
+
+/// [Solarized](https://ethanschoonover.com/solarized/), shared by its light and dark themes
+pub const SOLARIZED: Palette = Palette([
+    Rgb(7, 54, 66),
+    Rgb(220, 50, 47),
+    Rgb(133, 153, 0),
+    Rgb(181, 137, 0),
+    Rgb(38, 139, 210),
+    Rgb(211, 54, 130),
+    Rgb(42, 161, 152),
+    Rgb(238, 232, 213),
+    Rgb(0, 43, 54),
+    Rgb(203, 75, 22),
+    Rgb(88, 110, 117),
+    Rgb(101, 123, 131),
+    Rgb(131, 148, 150),
+    Rgb(108, 113, 196),
+    Rgb(147, 161, 161),
+    Rgb(253, 246, 227),
+]);
+
+/// [Dracula](https://draculatheme.com/)
+pub const DRACULA: Palette = Palette([
+    Rgb(33, 34, 44),
+    Rgb(255, 85, 85),
+    Rgb(80, 250, 123),
+    Rgb(241, 250, 140),
+    Rgb(189, 147, 249),
+    Rgb(255, 121, 198),
+    Rgb(139, 233, 253),
+    Rgb(248, 248, 242),
+    Rgb(98, 114, 164),
+    Rgb(255, 110, 110),
+    Rgb(105, 255, 148),
+    Rgb(255, 255, 165),
+    Rgb(214, 172, 255),
+    Rgb(255, 146, 223),
+    Rgb(164, 255, 255),
+    Rgb(255, 255, 255),
+]);
+
+/// [Gruvbox](https://github.com/morhetz/gruvbox) dark
+pub const GRUVBOX_DARK: Palette = Palette([
+    Rgb(40, 40, 40),
+    Rgb(204, 36, 29),
+    Rgb(152, 151, 26),
+    Rgb(215, 153, 33),
+    Rgb(69, 133, 136),
+    Rgb(177, 98, 134),
+    Rgb(104, 157, 106),
+    Rgb(168, 153, 132),
+    Rgb(146, 131, 116),
+    Rgb(251, 73, 52),
+    Rgb(184, 187, 38),
+    Rgb(250, 189, 47),
+    Rgb(131, 165, 152),
+    Rgb(211, 134, 155),
+    Rgb(142, 192, 124),
+    Rgb(235, 219, 178),
+]);
+
+/// Bundled palettes by name, for [`Palette::from_name`]
+pub const NAMED: &[(&str, Palette)] = &[
+    ("vga", VGA),
+    ("win10-console", WIN10_CONSOLE),
+    ("xterm", XTERM),
+    ("terminal-app", TERMINAL_APP),
+    ("putty", PUTTY),
+    ("tango", TANGO),
+    ("solarized", SOLARIZED),
+    ("dracula", DRACULA),
+    ("gruvbox-dark", GRUVBOX_DARK),
+];
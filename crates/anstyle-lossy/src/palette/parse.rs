@@ -0,0 +1,594 @@
+use anstyle::AnsiColor;
+use anstyle::RgbColor as Rgb;
+
+use super::Palette;
+
+/// Failed to build a [`Palette`] from a theme
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The theme doesn't define this color
+    Missing(AnsiColor),
+    /// A color's value isn't understood
+    InvalidColor(String),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(color) => write!(f, "missing color {}", color_index(*color)),
+            Self::InvalidColor(value) => write!(f, "invalid color `{value}`"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// # Theme formats
+impl Palette {
+    /// Load from X resources, like `~/.Xresources`
+    ///
+    /// Recognizes any resource ending in `color0` through `color15`, like `*.color0` or
+    /// `URxvt*color0`, including values from simple `#define`s.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use anstyle_lossy::palette::Palette;
+    /// let content = (0..16)
+    ///     .map(|i| format!("*.color{i}: #{i:02x}{i:02x}{i:02x}\n"))
+    ///     .collect::<String>();
+    /// let palette = Palette::from_xresources(&content).unwrap();
+    /// assert_eq!(palette[anstyle::AnsiColor::BrightWhite], anstyle::RgbColor(15, 15, 15));
+    /// ```
+    pub fn from_xresources(content: &str) -> Result<Self, ParseError> {
+        let mut defines = Vec::new();
+        let mut builder = Builder::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if let Some(define) = line.strip_prefix("#define") {
+                let mut parts = define.split_whitespace();
+                if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                    defines.push((name, value));
+                }
+                continue;
+            }
+            if line.starts_with('!') || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim();
+            let key = key.rsplit(['*', '.']).next().unwrap_or(key);
+            let Some(index) = key.strip_prefix("color").and_then(|i| i.parse().ok()) else {
+                continue;
+            };
+            let value = value.trim();
+            let value = defines
+                .iter()
+                .rev()
+                .find(|(name, _)| *name == value)
+                .map(|(_, value)| *value)
+                .unwrap_or(value);
+            builder.set(index, value)?;
+        }
+        builder.build()
+    }
+
+    /// Load from a [kitty](https://sw.kovidgoyal.net/kitty/) config or theme
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use anstyle_lossy::palette::Palette;
+    /// let content = (0..16)
+    ///     .map(|i| format!("color{i} #{i:02x}{i:02x}{i:02x}\n"))
+    ///     .collect::<String>();
+    /// let palette = Palette::from_kitty(&content).unwrap();
+    /// assert_eq!(palette[anstyle::AnsiColor::Red], anstyle::RgbColor(1, 1, 1));
+    /// ```
+    pub fn from_kitty(content: &str) -> Result<Self, ParseError> {
+        let mut builder = Builder::new();
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let Some(index) = key.strip_prefix("color").and_then(|i| i.parse().ok()) else {
+                continue;
+            };
+            builder.set(index, value)?;
+        }
+        builder.build()
+    }
+
+    /// Load from an [alacritty](https://alacritty.org/) config or theme
+    ///
+    /// Reads the `[colors.normal]` and `[colors.bright]` tables of the TOML format, as well as the
+    /// `normal:` and `bright:` mappings of the legacy YAML format.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use anstyle_lossy::palette::Palette;
+    /// let content = r##"
+    /// [colors.normal]
+    /// black = "#000000"
+    /// red = "#cd0000"
+    /// green = "#00cd00"
+    /// yellow = "#cdcd00"
+    /// blue = "#0000ee"
+    /// magenta = "#cd00cd"
+    /// cyan = "#00cdcd"
+    /// white = "#e5e5e5"
+    ///
+    /// [colors.bright]
+    /// black = "#7f7f7f"
+    /// red = "#ff0000"
+    /// green = "#00ff00"
+    /// yellow = "#ffff00"
+    /// blue = "#5c5cff"
+    /// magenta = "#ff00ff"
+    /// cyan = "#00ffff"
+    /// white = "#ffffff"
+    /// "##;
+    /// let palette = Palette::from_alacritty(content).unwrap();
+    /// assert_eq!(palette, anstyle_lossy::palette::XTERM);
+    /// ```
+    pub fn from_alacritty(content: &str) -> Result<Self, ParseError> {
+        let mut builder = Builder::new();
+        let mut offset = None;
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some(table) = line.strip_prefix('[') {
+                offset = match table.trim_end_matches(']').trim() {
+                    "colors.normal" => Some(0),
+                    "colors.bright" => Some(8),
+                    _ => None,
+                };
+                continue;
+            }
+            let Some((key, value)) = line.split_once(['=', ':']) else {
+                continue;
+            };
+            let key = key.trim();
+            let value = value.trim();
+            if value.is_empty() {
+                // YAML mapping
+                offset = match key {
+                    "normal" => Some(0),
+                    "bright" => Some(8),
+                    _ => None,
+                };
+                continue;
+            }
+            let Some(offset) = offset else {
+                continue;
+            };
+            let Some(index) = ANSI_NAMES.iter().position(|name| *name == key) else {
+                continue;
+            };
+            builder.set(offset + index, unquote(value))?;
+        }
+        builder.build()
+    }
+
+    /// Load from a [wezterm](https://wezfurlong.org/wezterm/) TOML color scheme
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use anstyle_lossy::palette::Palette;
+    /// let content = r##"
+    /// [colors]
+    /// ansi = ["#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5"]
+    /// brights = [
+    ///     "#7f7f7f", "#ff0000", "#00ff00", "#ffff00",
+    ///     "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+    /// ]
+    /// "##;
+    /// let palette = Palette::from_wezterm(content).unwrap();
+    /// assert_eq!(palette, anstyle_lossy::palette::XTERM);
+    /// ```
+    pub fn from_wezterm(content: &str) -> Result<Self, ParseError> {
+        let mut builder = Builder::new();
+        for (key, offset) in [("ansi", 0), ("brights", 8)] {
+            let Some(array) = toml_array(content, key) else {
+                continue;
+            };
+            for (index, value) in array.split(',').map(str::trim).enumerate() {
+                if value.is_empty() || 8 <= index {
+                    continue;
+                }
+                builder.set(offset + index, unquote(value))?;
+            }
+        }
+        builder.build()
+    }
+
+    /// Load from an [iTerm2](https://iterm2.com/) `.itermcolors` file
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use anstyle_lossy::palette::Palette;
+    /// let content = (0..16)
+    ///     .map(|i| {
+    ///         let c = i as f64 / 255.0;
+    ///         format!(
+    ///             "<key>Ansi {i} Color</key>
+    ///             <dict>
+    ///                 <key>Blue Component</key><real>{c}</real>
+    ///                 <key>Green Component</key><real>{c}</real>
+    ///                 <key>Red Component</key><real>{c}</real>
+    ///             </dict>"
+    ///         )
+    ///     })
+    ///     .collect::<String>();
+    /// let palette = Palette::from_itermcolors(&content).unwrap();
+    /// assert_eq!(palette[anstyle::AnsiColor::Green], anstyle::RgbColor(2, 2, 2));
+    /// ```
+    pub fn from_itermcolors(content: &str) -> Result<Self, ParseError> {
+        let mut builder = Builder::new();
+        let mut remaining = content;
+        while let Some((key, after)) = next_xml_element(remaining, "key") {
+            remaining = after;
+            let Some(index) = key
+                .strip_prefix("Ansi ")
+                .and_then(|k| k.strip_suffix(" Color"))
+                .and_then(|i| i.trim().parse::<usize>().ok())
+            else {
+                continue;
+            };
+            let Some((dict, after)) = next_xml_element(remaining, "dict") else {
+                break;
+            };
+            remaining = after;
+
+            let mut components = [None; 3];
+            let mut entries = dict;
+            while let Some((key, after)) = next_xml_element(entries, "key") {
+                let component = match key.trim() {
+                    "Red Component" => 0,
+                    "Green Component" => 1,
+                    "Blue Component" => 2,
+                    _ => {
+                        entries = after;
+                        continue;
+                    }
+                };
+                let Some((value, after)) = next_xml_element(after, "real") else {
+                    break;
+                };
+                entries = after;
+                let value = value
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|v| (0.0..=1.0).contains(v))
+                    .ok_or_else(|| ParseError::InvalidColor(value.to_owned()))?;
+                components[component] = Some((value * 255.0).round() as u8);
+            }
+            if let [Some(r), Some(g), Some(b)] = components {
+                builder.set_rgb(index, Rgb(r, g, b));
+            }
+        }
+        builder.build()
+    }
+
+    /// Load from a [Windows Terminal](https://learn.microsoft.com/en-us/windows/terminal/customize-settings/color-schemes)
+    /// color scheme
+    ///
+    /// `content` should be a single scheme's JSON object, rather than all of `settings.json`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use anstyle_lossy::palette::Palette;
+    /// let content = r##"{
+    ///     "name": "Campbell",
+    ///     "black": "#0C0C0C", "red": "#C50F1F", "green": "#13A10E", "yellow": "#C19C00",
+    ///     "blue": "#0037DA", "purple": "#881798", "cyan": "#3A96DD", "white": "#CCCCCC",
+    ///     "brightBlack": "#767676", "brightRed": "#E74856", "brightGreen": "#16C60C",
+    ///     "brightYellow": "#F9F1A5", "brightBlue": "#3B78FF", "brightPurple": "#B4009E",
+    ///     "brightCyan": "#61D6D6", "brightWhite": "#F2F2F2"
+    /// }"##;
+    /// let palette = Palette::from_windows_terminal(content).unwrap();
+    /// assert_eq!(palette, anstyle_lossy::palette::WIN10_CONSOLE);
+    /// ```
+    pub fn from_windows_terminal(content: &str) -> Result<Self, ParseError> {
+        const NAMES: [&str; 16] = [
+            "black",
+            "red",
+            "green",
+            "yellow",
+            "blue",
+            "purple",
+            "cyan",
+            "white",
+            "brightBlack",
+            "brightRed",
+            "brightGreen",
+            "brightYellow",
+            "brightBlue",
+            "brightPurple",
+            "brightCyan",
+            "brightWhite",
+        ];
+
+        let mut builder = Builder::new();
+        for (key, value) in json_string_pairs(content) {
+            if let Some(index) = NAMES.iter().position(|name| *name == key) {
+                if builder.get(index).is_none() {
+                    builder.set(index, value)?;
+                }
+            }
+        }
+        builder.build()
+    }
+}
+
+/// Parse a color value, like `#rrggbb`, `#rgb`, `0xrrggbb`, or `rgb:rr/gg/bb`
+pub(crate) fn parse_rgb(value: &str) -> Option<Rgb> {
+    if let Some(hex) = value.strip_prefix('#').or_else(|| value.strip_prefix("0x")) {
+        if !hex.is_ascii() {
+            return None;
+        }
+        let digits = match hex.len() {
+            3 => 1,
+            6 => 2,
+            _ => return None,
+        };
+        let component =
+            |i: usize| anstyle_query::parse_x11_component(&hex[i * digits..(i + 1) * digits]);
+        Some(Rgb(component(0)?, component(1)?, component(2)?))
+    } else if let Some(components) = value.strip_prefix("rgb:") {
+        let mut components = components
+            .split('/')
+            .map(anstyle_query::parse_x11_component);
+        let color = Rgb(
+            components.next()??,
+            components.next()??,
+            components.next()??,
+        );
+        components.next().is_none().then_some(color)
+    } else {
+        None
+    }
+}
+
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+struct Builder {
+    colors: [Option<Rgb>; 16],
+}
+
+impl Builder {
+    fn new() -> Self {
+        Self { colors: [None; 16] }
+    }
+
+    fn get(&self, index: usize) -> Option<Rgb> {
+        self.colors.get(index).copied().flatten()
+    }
+
+    fn set(&mut self, index: usize, value: &str) -> Result<(), ParseError> {
+        if index < self.colors.len() {
+            let color =
+                parse_rgb(value).ok_or_else(|| ParseError::InvalidColor(value.to_owned()))?;
+            self.colors[index] = Some(color);
+        }
+        Ok(())
+    }
+
+    fn set_rgb(&mut self, index: usize, color: Rgb) {
+        if let Some(slot) = self.colors.get_mut(index) {
+            *slot = Some(color);
+        }
+    }
+
+    fn build(self) -> Result<Palette, ParseError> {
+        let mut raw = [Rgb(0, 0, 0); 16];
+        for (index, color) in self.colors.into_iter().enumerate() {
+            raw[index] = color.ok_or_else(|| {
+                let color = anstyle::Ansi256Color(index as u8)
+                    .into_ansi()
+                    .expect("always within 16 colors");
+                ParseError::Missing(color)
+            })?;
+        }
+        Ok(Palette(raw))
+    }
+}
+
+fn color_index(color: AnsiColor) -> u8 {
+    anstyle::Ansi256Color::from_ansi(color).index()
+}
+
+/// Extract a possibly quoted value, dropping anything after it like comments
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(value) = value.strip_prefix(quote) {
+            return value.split(quote).next().unwrap_or(value);
+        }
+    }
+    value.split_whitespace().next().unwrap_or(value)
+}
+
+/// Find the contents of the `key = [ ... ]` array, which may span lines
+fn toml_array<'c>(content: &'c str, key: &str) -> Option<&'c str> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let Some((line_key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if line_key.trim() != key {
+            continue;
+        }
+        let value_start = start + line_key.len() + 1;
+        let value = value.trim_start();
+        if !value.starts_with('[') {
+            return None;
+        }
+        let array_start = content[value_start..].find('[')? + value_start + 1;
+        let array_end = content[array_start..].find(']')? + array_start;
+        return Some(&content[array_start..array_end]);
+    }
+    None
+}
+
+/// Find the text of the next `<tag>...</tag>`, returning what follows it
+fn next_xml_element<'c>(content: &'c str, tag: &str) -> Option<(&'c str, &'c str)> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let start = content.find(&open)? + open.len();
+    let end = content[start..].find(&close)? + start;
+    Some((&content[start..end], &content[end + close.len()..]))
+}
+
+/// Iterate over each `"key": "value"` pair in JSON, ignoring nesting
+fn json_string_pairs(content: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut strings = json_strings(content).peekable();
+    std::iter::from_fn(move || loop {
+        let (key, after_key) = strings.next()?;
+        let &(value, _) = strings.peek()?;
+        // Skip the opening quote
+        let value_start = value.as_ptr() as usize - content.as_ptr() as usize - 1;
+        let between = &content[after_key..value_start];
+        if between.trim() == ":" {
+            strings.next();
+            return Some((key, value));
+        }
+    })
+}
+
+/// Iterate over each JSON string's contents and the offset just past it
+///
+/// Escapes are not decoded.
+fn json_strings(content: &str) -> impl Iterator<Item = (&str, usize)> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let start = content[offset..].find('"')? + offset + 1;
+        let mut escaped = false;
+        let len = content[start..].find(|c| {
+            let end = c == '"' && !escaped;
+            escaped = c == '\\' && !escaped;
+            end
+        })?;
+        let end = start + len;
+        offset = end + 1;
+        Some((&content[start..end], offset))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_rgb_forms() {
+        assert_eq!(parse_rgb("#ff8000"), Some(Rgb(0xff, 0x80, 0x00)));
+        assert_eq!(parse_rgb("#FF8000"), Some(Rgb(0xff, 0x80, 0x00)));
+        assert_eq!(parse_rgb("#f80"), Some(Rgb(0xff, 0x88, 0x00)));
+        assert_eq!(parse_rgb("0xff8000"), Some(Rgb(0xff, 0x80, 0x00)));
+        assert_eq!(parse_rgb("rgb:ff/80/00"), Some(Rgb(0xff, 0x80, 0x00)));
+        assert_eq!(parse_rgb("rgb:ffff/8080/0000"), Some(Rgb(0xff, 0x80, 0x00)));
+        assert_eq!(parse_rgb("rgb:ff/80"), None);
+        assert_eq!(parse_rgb("#ff80"), None);
+        assert_eq!(parse_rgb("#ff80zz"), None);
+        assert_eq!(parse_rgb("#ff80é"), None);
+        assert_eq!(parse_rgb("red"), None);
+    }
+
+    #[test]
+    fn xresources_defines() {
+        let mut content = String::from(
+            "! base16\n#define base00 #181818\n*.background: base00\n*.color0: base00\n",
+        );
+        for i in 1..16 {
+            content.push_str(&format!("URxvt*color{i}:  #{i:02x}0000\n"));
+        }
+        let palette = Palette::from_xresources(&content).unwrap();
+        assert_eq!(palette[AnsiColor::Black], Rgb(0x18, 0x18, 0x18));
+        assert_eq!(palette[AnsiColor::BrightWhite], Rgb(0x0f, 0, 0));
+    }
+
+    #[test]
+    fn xresources_missing() {
+        let content = "*.color0: #000000\n";
+        assert_eq!(
+            Palette::from_xresources(content),
+            Err(ParseError::Missing(AnsiColor::Red))
+        );
+    }
+
+    #[test]
+    fn kitty_invalid() {
+        let content = "# comment\ncolor0 black\n";
+        assert_eq!(
+            Palette::from_kitty(content),
+            Err(ParseError::InvalidColor("black".to_owned()))
+        );
+    }
+
+    #[test]
+    fn alacritty_yaml() {
+        let content = "colors:
+  primary:
+    background: '0x000000'
+  normal:
+    black:   '0x000000'
+    red:     '0xcd0000'
+    green:   '0x00cd00'
+    yellow:  '0xcdcd00'
+    blue:    '0x0000ee'
+    magenta: '0xcd00cd'
+    cyan:    '0x00cdcd'
+    white:   '0xe5e5e5'
+  bright:
+    black:   '0x7f7f7f'
+    red:     '0xff0000'
+    green:   '0x00ff00'
+    yellow:  '0xffff00'
+    blue:    '0x5c5cff'
+    magenta: '0xff00ff'
+    cyan:    '0x00ffff'
+    white:   '0xffffff'
+";
+        assert_eq!(Palette::from_alacritty(content), Ok(super::super::XTERM));
+    }
+
+    #[test]
+    fn alacritty_ignores_other_tables() {
+        let content = "[colors.primary]\nblack = \"#123456\"\n";
+        assert_eq!(
+            Palette::from_alacritty(content),
+            Err(ParseError::Missing(AnsiColor::Black))
+        );
+    }
+
+    #[test]
+    fn windows_terminal_settings_order() {
+        let content = r##"{"name": "black", "black": "#010101", "red": "#020202"}"##;
+        assert_eq!(
+            Palette::from_windows_terminal(content),
+            Err(ParseError::Missing(AnsiColor::Green))
+        );
+    }
+
+    #[test]
+    fn json_pairs() {
+        let content = r#"{"a": "b", "c": ["d", "e"], "f\"g" : "h"}"#;
+        let pairs = json_string_pairs(content).collect::<Vec<_>>();
+        assert_eq!(pairs, [("a", "b"), ("f\\\"g", "h")]);
+    }
+}
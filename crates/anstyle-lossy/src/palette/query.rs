@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use anstyle_query::osc::TermColor;
+use anstyle_query::osc::Tty;
+
+use super::Palette;
+
+/// # Live terminal
+impl Palette {
+    /// Query the controlling terminal for its 16 colors over OSC 4
+    ///
+    /// Returns `Ok(None)` when the terminal doesn't reply for all of the colors.  `timeout`
+    /// applies to the overall query, see [`anstyle_query::osc`] for when this is appropriate to
+    /// call.
+    pub fn from_terminal(timeout: Duration) -> std::io::Result<Option<Self>> {
+        let replies = anstyle_query::osc::query_terminal(&PALETTE_COLORS, timeout)?;
+        Ok(Self::from_replies(replies))
+    }
+
+    /// Query `tty` for its 16 colors over OSC 4
+    ///
+    /// Returns `Ok(None)` when the terminal doesn't reply for all of the colors.  `timeout`
+    /// applies to the overall query, see [`anstyle_query::osc::query`].
+    pub fn from_tty(tty: impl Tty, timeout: Duration) -> std::io::Result<Option<Self>> {
+        let replies = anstyle_query::osc::query(tty, &PALETTE_COLORS, timeout)?;
+        Ok(Self::from_replies(replies))
+    }
+
+    fn from_replies(replies: Vec<Option<anstyle::RgbColor>>) -> Option<Self> {
+        let mut palette = Self::default();
+        for (color, reply) in palette.0.iter_mut().zip(replies) {
+            *color = reply?;
+        }
+        Some(palette)
+    }
+}
+
+const PALETTE_COLORS: [TermColor; 16] = {
+    let mut colors = [TermColor::Palette(0); 16];
+    let mut i = 0;
+    while i < colors.len() {
+        colors[i] = TermColor::Palette(i as u8);
+        i += 1;
+    }
+    colors
+};
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::palette::XTERM;
+
+    /// Pseudo-terminal stand-in that replies to OSC 4 queries from a palette
+    struct FakeTty {
+        palette: Option<Palette>,
+        pending: Vec<u8>,
+    }
+
+    impl Tty for FakeTty {
+        fn write_all(&mut self, _buf: &[u8]) -> std::io::Result<()> {
+            if let Some(palette) = self.palette {
+                for (index, rgb) in palette.0.iter().enumerate() {
+                    let reply = format!(
+                        "\x1b]4;{index};rgb:{:02x}/{:02x}/{:02x}\x1b\\",
+                        rgb.r(),
+                        rgb.g(),
+                        rgb.b()
+                    );
+                    self.pending.extend_from_slice(reply.as_bytes());
+                }
+            }
+            self.pending.extend_from_slice(b"\x1b[?64;1;2c");
+            Ok(())
+        }
+
+        fn read_timeout(&mut self, buf: &mut [u8], _timeout: Duration) -> std::io::Result<usize> {
+            let len = buf.len().min(self.pending.len());
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+            Ok(len)
+        }
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn from_tty() {
+        let tty = FakeTty {
+            palette: Some(XTERM),
+            pending: Vec::new(),
+        };
+        assert_eq!(Palette::from_tty(tty, TIMEOUT).unwrap(), Some(XTERM));
+    }
+
+    #[test]
+    fn from_tty_unsupported() {
+        let tty = FakeTty {
+            palette: None,
+            pending: Vec::new(),
+        };
+        assert_eq!(Palette::from_tty(tty, TIMEOUT).unwrap(), None);
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt;
+
+use serde::de;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use super::Palette;
+use super::NAMED;
+
+/// Serialize as a list of the 16 colors, like `["#000000", "#aa0000", ...]`
+impl Serialize for Palette {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// Deserialize from one of, with only the list for formats that aren't human-readable:
+/// - A name in [`NAMED`], see [`Palette::from_name`]
+/// - A list of the 16 colors, ordered by [`anstyle::AnsiColor`]
+/// - A table of all 16 colors, keyed by [`anstyle::AnsiColor`] names like `bright_red`
+impl<'de> Deserialize<'de> for Palette {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(PaletteVisitor)
+        } else {
+            deserializer.deserialize_tuple(16, PaletteVisitor)
+        }
+    }
+}
+
+struct PaletteVisitor;
+
+impl<'de> de::Visitor<'de> for PaletteVisitor {
+    type Value = Palette;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a palette name, list of 16 colors, or table of colors")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Palette::from_name(v).ok_or_else(|| {
+            let names = NAMED
+                .iter()
+                .map(|(name, _)| format!("`{name}`"))
+                .collect::<Vec<_>>();
+            E::custom(format_args!(
+                "unknown palette `{v}`, expected one of {}",
+                names.join(", ")
+            ))
+        })
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut palette = Palette::default();
+        for (i, color) in palette.0.iter_mut().enumerate() {
+            *color = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        if seq.next_element::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(palette.0.len() + 1, &self));
+        }
+        Ok(palette)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut colors = [None; 16];
+        while let Some(key) = map.next_key::<anstyle::AnsiColor>()? {
+            let color = &mut colors[key as usize];
+            if color.is_some() {
+                return Err(de::Error::custom(format_args!("duplicate color `{key}`")));
+            }
+            *color = Some(map.next_value()?);
+        }
+        let mut palette = Palette::default();
+        for (i, color) in colors.into_iter().enumerate() {
+            let key = anstyle::Ansi256Color(i as u8)
+                .into_ansi()
+                .expect("16 colors");
+            palette.0[i] =
+                color.ok_or_else(|| de::Error::custom(format_args!("missing color `{key}`")))?;
+        }
+        Ok(palette)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::palette::DRACULA;
+    use crate::palette::VGA;
+    use crate::palette::XTERM;
+
+    #[test]
+    fn round_trip() {
+        let json = serde_json::to_string(&VGA).unwrap();
+        assert_eq!(
+            json,
+            r##"["#000000","#aa0000","#00aa00","#aa5500","#0000aa","#aa00aa","#00aaaa","#aaaaaa","#555555","#ff5555","#55ff55","#ffff55","#5555ff","#ff55ff","#55ffff","#ffffff"]"##
+        );
+        let actual = serde_json::from_str::<Palette>(&json).unwrap();
+        assert_eq!(actual, VGA);
+    }
+
+    #[test]
+    fn not_human_readable() {
+        let bytes = bincode::serialize(&XTERM).unwrap();
+        let actual = bincode::deserialize::<Palette>(&bytes).unwrap();
+        assert_eq!(actual, XTERM);
+    }
+
+    #[test]
+    fn name() {
+        let actual = serde_json::from_str::<Palette>(r#""dracula""#).unwrap();
+        assert_eq!(actual, DRACULA);
+
+        let actual = serde_json::from_str::<Palette>(r#""unknown""#)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            actual.starts_with("unknown palette `unknown`, expected one of `"),
+            "{actual}"
+        );
+    }
+
+    #[test]
+    fn table() {
+        let json = serde_json::to_value(XTERM).unwrap();
+        let table = (0..16)
+            .map(|i| {
+                let color = anstyle::Ansi256Color(i).into_ansi().unwrap();
+                (color.to_string(), json[i as usize].clone())
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let actual = serde_json::from_value::<Palette>(table.clone().into()).unwrap();
+        assert_eq!(actual, XTERM);
+
+        let mut partial = table;
+        partial.remove("bright_cyan");
+        let actual = serde_json::from_value::<Palette>(partial.into())
+            .unwrap_err()
+            .to_string();
+        assert_eq!(actual, "missing color `bright_cyan`");
+    }
+
+    #[test]
+    fn length() {
+        let actual = serde_json::from_str::<Palette>(r##"["#000000"]"##)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            actual,
+            "invalid length 1, expected a palette name, list of 16 colors, or table of colors at line 1 column 11"
+        );
+    }
+}
@@ -43,7 +43,7 @@ pub fn parse(code: &str) -> Option<anstyle::Style> {
             3 => effects |= anstyle::Effects::ITALIC,
             4 => effects |= anstyle::Effects::UNDERLINE,
             5 => effects |= anstyle::Effects::BLINK,
-            6 => effects |= anstyle::Effects::BLINK,
+            6 => effects |= anstyle::Effects::RAPID_BLINK,
             7 => effects |= anstyle::Effects::INVERT,
             8 => effects |= anstyle::Effects::HIDDEN,
             9 => effects |= anstyle::Effects::STRIKETHROUGH,
@@ -59,8 +59,11 @@ pub fn parse(code: &str) -> Option<anstyle::Style> {
                 effects = effects.remove(anstyle::Effects::UNDERLINE);
             }
             25 => {
-                effects = effects.remove(anstyle::Effects::BLINK);
+                effects = effects
+                    .remove(anstyle::Effects::BLINK)
+                    .remove(anstyle::Effects::RAPID_BLINK);
             }
+            26 => effects |= anstyle::Effects::PROPORTIONAL_SPACING,
             27 => {
                 effects = effects.remove(anstyle::Effects::INVERT);
             }
@@ -116,6 +119,20 @@ pub fn parse(code: &str) -> Option<anstyle::Style> {
                 }
             },
             49 => bg_color = None,
+            50 => {
+                effects = effects.remove(anstyle::Effects::PROPORTIONAL_SPACING);
+            }
+            51 => effects |= anstyle::Effects::FRAMED,
+            52 => effects |= anstyle::Effects::ENCIRCLED,
+            53 => effects |= anstyle::Effects::OVERLINE,
+            54 => {
+                effects = effects
+                    .remove(anstyle::Effects::FRAMED)
+                    .remove(anstyle::Effects::ENCIRCLED);
+            }
+            55 => {
+                effects = effects.remove(anstyle::Effects::OVERLINE);
+            }
             58 => match (parts.pop_front(), parts.pop_front()) {
                 (Some(5), Some(color)) => {
                     underline_color = Some(anstyle::Ansi256Color(color).into());
@@ -133,6 +150,13 @@ pub fn parse(code: &str) -> Option<anstyle::Style> {
                 }
             },
             59 => underline_color = None,
+            73 => effects |= anstyle::Effects::SUPERSCRIPT,
+            74 => effects |= anstyle::Effects::SUBSCRIPT,
+            75 => {
+                effects = effects
+                    .remove(anstyle::Effects::SUPERSCRIPT)
+                    .remove(anstyle::Effects::SUBSCRIPT);
+            }
             90 => fg_color = Some(anstyle::AnsiColor::BrightBlack.into()),
             91 => fg_color = Some(anstyle::AnsiColor::BrightRed.into()),
             92 => fg_color = Some(anstyle::AnsiColor::BrightGreen.into()),
@@ -206,9 +230,18 @@ mod tests {
             anstyle::AnsiColor::Blue.on_default() | anstyle::Effects::ITALIC,
         );
         assert_style(
-            "06;34",
+            "05;34",
             anstyle::AnsiColor::Blue.on_default() | anstyle::Effects::BLINK,
         );
+        assert_style(
+            "06;34",
+            anstyle::AnsiColor::Blue.on_default() | anstyle::Effects::RAPID_BLINK,
+        );
+        assert_style(
+            "53;51",
+            anstyle::Effects::OVERLINE | anstyle::Effects::FRAMED,
+        );
+        assert_style("53;51;55;54", anstyle::Style::new());
         assert_style(
             "01;36",
             anstyle::AnsiColor::Cyan.on_default() | anstyle::Effects::BOLD,
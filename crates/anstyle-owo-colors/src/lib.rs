@@ -42,6 +42,9 @@ pub fn to_owo_style(style: anstyle::Style) -> owo_colors::Style {
     if effects.contains(anstyle::Effects::STRIKETHROUGH) {
         style = style.strikethrough();
     }
+    if effects.contains(anstyle::Effects::RAPID_BLINK) {
+        style = style.blink_fast();
+    }
     style
 }
 
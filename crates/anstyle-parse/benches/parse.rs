@@ -29,6 +29,24 @@ fn advance_strip(data: &Data) -> String {
     black_box(stripped.0)
 }
 
+#[divan::bench(args = DATA)]
+fn advance_bytes(data: &Data) {
+    let mut dispatcher = BenchDispatcher;
+    let mut parser = Parser::<DefaultCharAccumulator>::new();
+
+    parser.advance_bytes(&mut dispatcher, data.content());
+}
+
+#[divan::bench(args = DATA)]
+fn advance_bytes_strip(data: &Data) -> String {
+    let mut stripped = Strip::with_capacity(data.content().len());
+    let mut parser = Parser::<DefaultCharAccumulator>::new();
+
+    parser.advance_bytes(&mut stripped, data.content());
+
+    black_box(stripped.0)
+}
+
 #[divan::bench(args = DATA)]
 fn state_change(data: &Data) {
     let mut state = anstyle_parse::state::State::default();
@@ -58,6 +76,10 @@ impl Perform for BenchDispatcher {
         black_box(c);
     }
 
+    fn print_str(&mut self, s: &str) {
+        black_box(s);
+    }
+
     fn execute(&mut self, byte: u8) {
         black_box(byte);
     }
@@ -95,6 +117,10 @@ impl Perform for Strip {
         self.0.push(c);
     }
 
+    fn print_str(&mut self, s: &str) {
+        self.0.push_str(s);
+    }
+
     fn execute(&mut self, byte: u8) {
         if byte.is_ascii_whitespace() {
             self.0.push(byte as char);
@@ -198,6 +224,11 @@ fn verify_data() {
                 parser.advance(&mut stripped, *byte);
             }
             assert_eq!(stripped.0, strip_str(content));
+
+            let mut batched = Strip::with_capacity(content.len());
+            let mut parser = Parser::<DefaultCharAccumulator>::new();
+            parser.advance_bytes(&mut batched, content.as_bytes());
+            assert_eq!(batched.0, stripped.0);
         }
     }
 }
@@ -1,7 +1,7 @@
 //! Parse input from stdin and log actions on stdout
 use std::io::{self, Read};
 
-use anstyle_parse::{DefaultCharAccumulator, Params, Parser, Perform};
+use anstyle_parse::{DefaultCharAccumulator, Overflow, Params, Parser, Perform};
 
 /// A type implementing Perform that just logs actions
 struct Log;
@@ -33,6 +33,18 @@ impl Perform for Log {
         println!("[osc_dispatch] params={params:?} bell_terminated={bell_terminated}");
     }
 
+    fn sos_pm_apc_start(&mut self, kind: u8) {
+        println!("[sos_pm_apc_start] kind={:?}", kind as char);
+    }
+
+    fn sos_pm_apc_put(&mut self, byte: u8) {
+        println!("[sos_pm_apc_put] {byte:02x}");
+    }
+
+    fn sos_pm_apc_end(&mut self) {
+        println!("[sos_pm_apc_end]");
+    }
+
     fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: u8) {
         println!(
             "[csi_dispatch] params={params:#?}, intermediates={intermediates:?}, ignore={ignore:?}, char={c:?}"
@@ -44,6 +56,10 @@ impl Perform for Log {
             "[esc_dispatch] intermediates={intermediates:?}, ignore={ignore:?}, byte={byte:02x}"
         );
     }
+
+    fn overflow(&mut self, overflow: Overflow) {
+        println!("[overflow] {overflow:?}");
+    }
 }
 
 fn main() {
@@ -0,0 +1,294 @@
+#[cfg(any(not(feature = "core"), feature = "alloc"))]
+use alloc::vec::Vec;
+
+use crate::{Params, Perform};
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// Write [`Perform`] actions back out as escape sequences, the inverse of [`Parser`][crate::Parser]
+///
+/// Sequences are written in a canonical form:
+/// - Subparameters are kept with `:` separators, empty parameters become `0`, and a lone `0`
+///   parameter is dropped
+/// - OSC strings keep their BEL or ST terminator
+/// - DCS, SOS, PM, and APC strings are terminated with ST
+///
+/// As the [`Parser`][crate::Parser] reports ST as a string ending followed by an `ESC \`
+/// sequence, that `ESC \` is dropped when it immediately follows a string.
+///
+/// Sequences flagged with `ignore` are dropped since the [`Parser`][crate::Parser] did not keep
+/// all of their content.
+///
+/// # Examples
+///
+/// ```rust
+/// use anstyle_parse::{DefaultCharAccumulator, Encoder, Parser, Perform};
+///
+/// /// Drop OSC 52 clipboard access
+/// struct Filter(Encoder<Vec<u8>>);
+///
+/// impl Perform for Filter {
+///     fn print_str(&mut self, s: &str) {
+///         self.0.print_str(s);
+///     }
+///
+///     fn print(&mut self, c: char) {
+///         self.0.print(c);
+///     }
+///
+///     fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+///         if params.first() != Some(&&b"52"[..]) {
+///             self.0.osc_dispatch(params, bell_terminated);
+///         }
+///     }
+///
+///     fn csi_dispatch(
+///         &mut self,
+///         params: &anstyle_parse::Params,
+///         intermediates: &[u8],
+///         ignore: bool,
+///         action: u8,
+///     ) {
+///         self.0.csi_dispatch(params, intermediates, ignore, action);
+///     }
+/// }
+///
+/// let mut parser = Parser::<DefaultCharAccumulator>::new();
+/// let mut filter = Filter(Encoder::new(Vec::new()));
+/// parser.advance_bytes(&mut filter, b"\x1b]52;c;aGVsbG8=\x07\x1b[1mbold\x1b[0m");
+/// assert_eq!(filter.0.into_inner(), b"\x1b[1mbold\x1b[m");
+/// ```
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct Encoder<W> {
+    out: W,
+    /// A string was just terminated with ST, so the `ESC \` that follows is part of it
+    string_terminated: bool,
+    /// Inside of a DCS string flagged with `ignore`
+    ignoring_dcs: bool,
+}
+
+impl<W> Encoder<W>
+where
+    W: Extend<u8>,
+{
+    /// Write escape sequences to `out`
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            string_terminated: false,
+            ignoring_dcs: false,
+        }
+    }
+
+    /// Get the wrapped output
+    #[inline]
+    pub fn as_inner(&self) -> &W {
+        &self.out
+    }
+
+    /// Get the wrapped output
+    #[inline]
+    pub fn as_inner_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    /// Get the wrapped output
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    /// Write an [`Event`][crate::Event]
+    #[cfg(any(not(feature = "core"), feature = "alloc"))]
+    pub fn encode(&mut self, event: &crate::Event<'_>) {
+        use crate::Event;
+
+        match event {
+            Event::Print(text) => self.print_str(text),
+            Event::Execute(byte) => self.execute(*byte),
+            Event::Csi {
+                params,
+                intermediates,
+                ignore,
+                action,
+            } => self.csi_dispatch(params, intermediates, *ignore, *action),
+            Event::Esc {
+                intermediates,
+                ignore,
+                byte,
+            } => self.esc_dispatch(intermediates, *ignore, *byte),
+            Event::Osc {
+                params,
+                bell_terminated,
+            } => {
+                let params = params.iter().map(Vec::as_slice).collect::<Vec<_>>();
+                self.osc_dispatch(&params, *bell_terminated);
+            }
+            Event::DcsHook {
+                params,
+                intermediates,
+                ignore,
+                action,
+            } => self.hook(params, intermediates, *ignore, *action),
+            Event::DcsPut(byte) => self.put(*byte),
+            Event::DcsUnhook => self.unhook(),
+            Event::SosPmApcStart(kind) => self.sos_pm_apc_start(*kind),
+            Event::SosPmApcPut(byte) => self.sos_pm_apc_put(*byte),
+            Event::SosPmApcEnd => self.sos_pm_apc_end(),
+            Event::Overflow(overflow) => self.overflow(*overflow),
+        }
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        self.string_terminated = false;
+        self.out.extend(bytes.iter().copied());
+    }
+
+    fn write_string_terminator(&mut self) {
+        self.write(&[ESC, b'\\']);
+        self.string_terminated = true;
+    }
+
+    /// Write the parameters and intermediates of a CSI or DCS sequence
+    fn write_sequence(&mut self, params: &Params, intermediates: &[u8], action: u8) {
+        // Private markers can only come before the parameters
+        let split = intermediates
+            .iter()
+            .position(|b| !(0x3c..=0x3f).contains(b))
+            .unwrap_or(intermediates.len());
+        let (markers, intermediates) = intermediates.split_at(split);
+        self.write(markers);
+
+        let is_default = params.len() == 1 && params.iter().eq([&[0][..]]);
+        if !is_default {
+            for (i, param) in params.iter().enumerate() {
+                if i != 0 {
+                    self.write(b";");
+                }
+                for (j, subparam) in param.iter().enumerate() {
+                    if j != 0 {
+                        self.write(b":");
+                    }
+                    self.write_number(*subparam);
+                }
+            }
+        }
+
+        self.write(intermediates);
+        self.write(&[action]);
+    }
+
+    fn write_number(&mut self, mut value: u16) {
+        let mut digits = [0; 5];
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        self.write(&digits[start..]);
+    }
+}
+
+impl<W> Perform for Encoder<W>
+where
+    W: Extend<u8>,
+{
+    fn print(&mut self, c: char) {
+        let mut buffer = [0; 4];
+        self.write(c.encode_utf8(&mut buffer).as_bytes());
+    }
+
+    fn print_str(&mut self, s: &str) {
+        self.write(s.as_bytes());
+    }
+
+    fn execute(&mut self, byte: u8) {
+        self.write(&[byte]);
+    }
+
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: u8) {
+        if ignore {
+            self.string_terminated = false;
+            self.ignoring_dcs = true;
+            return;
+        }
+        self.write(&[ESC, b'P']);
+        self.write_sequence(params, intermediates, action);
+    }
+
+    fn put(&mut self, byte: u8) {
+        if self.ignoring_dcs {
+            return;
+        }
+        self.write(&[byte]);
+    }
+
+    fn unhook(&mut self) {
+        if self.ignoring_dcs {
+            self.ignoring_dcs = false;
+            self.string_terminated = true;
+            return;
+        }
+        self.write_string_terminator();
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        self.write(&[ESC, b']']);
+        for (i, param) in params.iter().enumerate() {
+            if i != 0 {
+                self.write(b";");
+            }
+            self.write(param);
+        }
+        if bell_terminated {
+            self.write(&[BEL]);
+        } else {
+            self.write_string_terminator();
+        }
+    }
+
+    fn sos_pm_apc_start(&mut self, kind: u8) {
+        if kind.is_ascii() {
+            self.write(&[ESC, kind]);
+        } else {
+            self.write(&[kind]);
+        }
+    }
+
+    fn sos_pm_apc_put(&mut self, byte: u8) {
+        self.write(&[byte]);
+    }
+
+    fn sos_pm_apc_end(&mut self) {
+        self.write_string_terminator();
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: u8) {
+        if ignore {
+            self.string_terminated = false;
+            return;
+        }
+        self.write(&[ESC, b'[']);
+        self.write_sequence(params, intermediates, action);
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        if self.string_terminated && intermediates.is_empty() && byte == b'\\' {
+            self.string_terminated = false;
+            return;
+        }
+        if ignore {
+            self.string_terminated = false;
+            return;
+        }
+        self.write(&[ESC]);
+        self.write(intermediates);
+        self.write(&[byte]);
+    }
+}
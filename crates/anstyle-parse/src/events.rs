@@ -0,0 +1,310 @@
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use alloc::string::ToString as _;
+use alloc::vec::Vec;
+
+use crate::state::State;
+use crate::MAX_INTERMEDIATES;
+use crate::{
+    printable_prefix, CharAccumulator, DefaultCharAccumulator, Overflow, Params, Parser, Perform,
+};
+
+/// Pull [`Event`]s out of chunks of bytes, as an alternative to implementing [`Perform`]
+///
+/// Parser state carries over between calls to [`Events::parse_next`], so sequences may be split
+/// across chunks.
+///
+/// # Examples
+///
+/// ```rust
+/// use anstyle_parse::Event;
+///
+/// let mut events = anstyle_parse::Events::<anstyle_parse::DefaultCharAccumulator>::new();
+/// let mut stripped = String::new();
+/// let mut csi = Vec::new();
+/// for chunk in [&b"Hello \x1b[1"[..], &b"mworld\x1b[0m!"[..]] {
+///     for event in events.parse_next(chunk) {
+///         match event {
+///             Event::Print(text) => stripped.push_str(&text),
+///             Event::Csi { action, .. } => csi.push(action as char),
+///             _ => {}
+///         }
+///     }
+/// }
+/// assert_eq!(stripped, "Hello world!");
+/// assert_eq!(csi, ['m', 'm']);
+/// ```
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct Events<C = DefaultCharAccumulator> {
+    parser: Parser<C>,
+    collector: Collector,
+}
+
+impl<C> Events<C>
+where
+    C: CharAccumulator,
+{
+    /// Initial state
+    pub fn new() -> Self {
+        Self {
+            parser: Parser::default(),
+            collector: Collector::default(),
+        }
+    }
+
+    /// Parse the next chunk of data
+    pub fn parse_next<'s>(&'s mut self, bytes: &'s [u8]) -> EventsIter<'s, C> {
+        EventsIter {
+            bytes,
+            parser: &mut self.parser,
+            collector: &mut self.collector,
+        }
+    }
+}
+
+/// See [`Events`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct EventsIter<'s, C = DefaultCharAccumulator> {
+    bytes: &'s [u8],
+    parser: &'s mut Parser<C>,
+    collector: &'s mut Collector,
+}
+
+impl<'s, C> Iterator for EventsIter<'s, C>
+where
+    C: CharAccumulator,
+{
+    type Item = Event<'s>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.collector.pending.pop_front() {
+                return Some(event);
+            }
+
+            if self.parser.state == State::Ground {
+                let text = printable_prefix::<C>(self.bytes);
+                if !text.is_empty() {
+                    self.bytes = &self.bytes[text.len()..];
+                    return Some(Event::Print(Cow::Borrowed(text)));
+                }
+            }
+
+            let (byte, remainder) = self.bytes.split_first()?;
+            self.bytes = remainder;
+            self.parser.advance(self.collector, *byte);
+        }
+    }
+}
+
+/// An action requested by the [`Parser`], see [`Perform`]
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<'s> {
+    /// Text to draw to the screen, see [`Perform::print_str`]
+    ///
+    /// This is borrowed from the input, except for characters split across chunks or replacing
+    /// invalid UTF-8.
+    Print(Cow<'s, str>),
+    /// A C0 or C1 control function, see [`Perform::execute`]
+    Execute(u8),
+    /// A CSI sequence, see [`Perform::csi_dispatch`]
+    Csi {
+        params: Params,
+        intermediates: Intermediates,
+        ignore: bool,
+        action: u8,
+    },
+    /// An escape sequence, see [`Perform::esc_dispatch`]
+    Esc {
+        intermediates: Intermediates,
+        ignore: bool,
+        byte: u8,
+    },
+    /// An operating system command, see [`Perform::osc_dispatch`]
+    Osc {
+        params: Vec<Vec<u8>>,
+        bell_terminated: bool,
+    },
+    /// The start of a device control string, see [`Perform::hook`]
+    DcsHook {
+        params: Params,
+        intermediates: Intermediates,
+        ignore: bool,
+        action: u8,
+    },
+    /// Content of a device control string, see [`Perform::put`]
+    DcsPut(u8),
+    /// The end of a device control string, see [`Perform::unhook`]
+    DcsUnhook,
+    /// The start of a SOS, PM, or APC string, see [`Perform::sos_pm_apc_start`]
+    SosPmApcStart(u8),
+    /// Content of a SOS, PM, or APC string, see [`Perform::sos_pm_apc_put`]
+    SosPmApcPut(u8),
+    /// The end of a SOS, PM, or APC string, see [`Perform::sos_pm_apc_end`]
+    SosPmApcEnd,
+    /// A sequence went over a limit of the [`Parser`], see [`Perform::overflow`]
+    Overflow(Overflow),
+}
+
+impl Event<'_> {
+    /// Detach the event from the input
+    pub fn into_owned(self) -> Event<'static> {
+        match self {
+            Event::Print(text) => Event::Print(Cow::Owned(text.into_owned())),
+            Event::Execute(byte) => Event::Execute(byte),
+            Event::Csi {
+                params,
+                intermediates,
+                ignore,
+                action,
+            } => Event::Csi {
+                params,
+                intermediates,
+                ignore,
+                action,
+            },
+            Event::Esc {
+                intermediates,
+                ignore,
+                byte,
+            } => Event::Esc {
+                intermediates,
+                ignore,
+                byte,
+            },
+            Event::Osc {
+                params,
+                bell_terminated,
+            } => Event::Osc {
+                params,
+                bell_terminated,
+            },
+            Event::DcsHook {
+                params,
+                intermediates,
+                ignore,
+                action,
+            } => Event::DcsHook {
+                params,
+                intermediates,
+                ignore,
+                action,
+            },
+            Event::DcsPut(byte) => Event::DcsPut(byte),
+            Event::DcsUnhook => Event::DcsUnhook,
+            Event::SosPmApcStart(kind) => Event::SosPmApcStart(kind),
+            Event::SosPmApcPut(byte) => Event::SosPmApcPut(byte),
+            Event::SosPmApcEnd => Event::SosPmApcEnd,
+            Event::Overflow(overflow) => Event::Overflow(overflow),
+        }
+    }
+}
+
+/// Intermediate bytes of an escape sequence
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct Intermediates {
+    bytes: [u8; MAX_INTERMEDIATES],
+    len: usize,
+}
+
+impl Intermediates {
+    fn new(intermediates: &[u8]) -> Self {
+        let mut bytes = [0; MAX_INTERMEDIATES];
+        bytes[..intermediates.len()].copy_from_slice(intermediates);
+        Self {
+            bytes,
+            len: intermediates.len(),
+        }
+    }
+
+    /// The intermediate bytes
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl core::ops::Deref for Intermediates {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+/// Events that could not be borrowed from the input
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+struct Collector {
+    pending: VecDeque<Event<'static>>,
+}
+
+impl Perform for Collector {
+    fn print(&mut self, c: char) {
+        self.pending
+            .push_back(Event::Print(Cow::Owned(c.to_string())));
+    }
+
+    fn execute(&mut self, byte: u8) {
+        self.pending.push_back(Event::Execute(byte));
+    }
+
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: u8) {
+        self.pending.push_back(Event::DcsHook {
+            params: params.clone(),
+            intermediates: Intermediates::new(intermediates),
+            ignore,
+            action,
+        });
+    }
+
+    fn put(&mut self, byte: u8) {
+        self.pending.push_back(Event::DcsPut(byte));
+    }
+
+    fn unhook(&mut self) {
+        self.pending.push_back(Event::DcsUnhook);
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        self.pending.push_back(Event::Osc {
+            params: params.iter().map(|param| param.to_vec()).collect(),
+            bell_terminated,
+        });
+    }
+
+    fn sos_pm_apc_start(&mut self, kind: u8) {
+        self.pending.push_back(Event::SosPmApcStart(kind));
+    }
+
+    fn sos_pm_apc_put(&mut self, byte: u8) {
+        self.pending.push_back(Event::SosPmApcPut(byte));
+    }
+
+    fn sos_pm_apc_end(&mut self) {
+        self.pending.push_back(Event::SosPmApcEnd);
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: u8) {
+        self.pending.push_back(Event::Csi {
+            params: params.clone(),
+            intermediates: Intermediates::new(intermediates),
+            ignore,
+            action,
+        });
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        self.pending.push_back(Event::Esc {
+            intermediates: Intermediates::new(intermediates),
+            ignore,
+            byte,
+        });
+    }
+
+    fn overflow(&mut self, overflow: Overflow) {
+        self.pending.push_back(Event::Overflow(overflow));
+    }
+}
@@ -34,47 +34,91 @@
 #![warn(clippy::print_stderr)]
 #![warn(clippy::print_stdout)]
 
-#[cfg(not(feature = "core"))]
+#[cfg(any(not(feature = "core"), feature = "alloc"))]
 extern crate alloc;
 
+#[cfg(any(not(feature = "core"), feature = "alloc"))]
+use alloc::vec::Vec;
 use core::mem::MaybeUninit;
 
-#[cfg(feature = "core")]
+#[cfg(all(feature = "core", not(feature = "alloc")))]
 use arrayvec::ArrayVec;
 #[cfg(feature = "utf8")]
 use utf8parse as utf8;
 
+mod encode;
+#[cfg(any(not(feature = "core"), feature = "alloc"))]
+mod events;
 mod params;
+#[cfg(feature = "anstyle")]
+pub mod sgr;
 pub mod state;
 
+pub use encode::Encoder;
+#[cfg(any(not(feature = "core"), feature = "alloc"))]
+pub use events::{Event, Events, EventsIter, Intermediates};
 pub use params::{Params, ParamsIter};
 
 use state::{state_change, Action, State};
 
 const MAX_INTERMEDIATES: usize = 2;
 const MAX_OSC_PARAMS: usize = 16;
-#[cfg(feature = "core")]
+#[cfg(all(feature = "core", not(feature = "alloc")))]
 const MAX_OSC_RAW: usize = 1024;
 
 /// Parser for raw _VTE_ protocol which delegates actions to a [`Perform`]
 #[allow(unused_qualifications)]
-#[derive(Default, Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Parser<C = DefaultCharAccumulator> {
     state: State,
     intermediates: [u8; MAX_INTERMEDIATES],
     intermediate_idx: usize,
     params: Params,
     param: u16,
-    #[cfg(feature = "core")]
+    #[cfg(all(feature = "core", not(feature = "alloc")))]
     osc_raw: ArrayVec<u8, MAX_OSC_RAW>,
-    #[cfg(not(feature = "core"))]
-    osc_raw: alloc::vec::Vec<u8>,
+    #[cfg(any(not(feature = "core"), feature = "alloc"))]
+    osc_raw: Vec<u8>,
+    #[cfg(all(feature = "core", not(feature = "alloc")))]
     osc_params: [(usize, usize); MAX_OSC_PARAMS],
+    #[cfg(any(not(feature = "core"), feature = "alloc"))]
+    osc_params: Vec<(usize, usize)>,
     osc_num_params: usize,
+    osc_overflow: bool,
     ignoring: bool,
+    max_params: usize,
+    max_osc_params: usize,
+    max_osc_len: usize,
     utf8_parser: C,
 }
 
+impl<C> Default for Parser<C>
+where
+    C: Default,
+{
+    fn default() -> Self {
+        Self {
+            state: Default::default(),
+            intermediates: Default::default(),
+            intermediate_idx: 0,
+            params: Default::default(),
+            param: 0,
+            osc_raw: Default::default(),
+            osc_params: Default::default(),
+            osc_num_params: 0,
+            osc_overflow: false,
+            ignoring: false,
+            max_params: params::MAX_PARAMS,
+            max_osc_params: MAX_OSC_PARAMS,
+            #[cfg(all(feature = "core", not(feature = "alloc")))]
+            max_osc_len: MAX_OSC_RAW,
+            #[cfg(any(not(feature = "core"), feature = "alloc"))]
+            max_osc_len: usize::MAX,
+            utf8_parser: Default::default(),
+        }
+    }
+}
+
 impl<C> Parser<C>
 where
     C: CharAccumulator,
@@ -84,6 +128,72 @@ where
         Parser::default()
     }
 
+    /// Limit the number of parameters and subparameters collected for CSI and DCS sequences
+    ///
+    /// Sequences over the limit are reported to [`Perform::overflow`] and dispatched with
+    /// `ignore` set.
+    /// `None` removes the limit.
+    ///
+    /// Defaults to 32, which is also the most supported with the `core` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use anstyle_parse::{DefaultCharAccumulator, Params, Parser, Perform};
+    /// # #[derive(Default)]
+    /// # struct Count(usize);
+    /// # impl Perform for Count {
+    /// #     fn csi_dispatch(&mut self, params: &Params, _: &[u8], ignore: bool, _: u8) {
+    /// #         self.0 = if ignore { 0 } else { params.len() };
+    /// #     }
+    /// # }
+    /// let sgr = format!("\x1b[{}m", ["1"; 40].join(";"));
+    ///
+    /// let mut parser = Parser::<DefaultCharAccumulator>::default().max_params(None);
+    /// let mut count = Count::default();
+    /// parser.advance_bytes(&mut count, sgr.as_bytes());
+    /// # #[cfg(not(feature = "core"))]
+    /// assert_eq!(count.0, 40);
+    /// ```
+    #[must_use]
+    pub fn max_params(mut self, max: Option<usize>) -> Self {
+        self.max_params = limit(max, params::MAX_PARAMS);
+        self
+    }
+
+    /// Limit the number of parameters collected for OSC strings
+    ///
+    /// Parameters over the limit are dropped and reported to [`Perform::overflow`].
+    /// `None` removes the limit.
+    ///
+    /// Defaults to 16, which is also the most supported with the `core` feature but not `alloc`.
+    #[must_use]
+    pub fn max_osc_params(mut self, max: Option<usize>) -> Self {
+        #[cfg(all(feature = "core", not(feature = "alloc")))]
+        let capacity = MAX_OSC_PARAMS;
+        #[cfg(any(not(feature = "core"), feature = "alloc"))]
+        let capacity = usize::MAX;
+        self.max_osc_params = limit(max, capacity);
+        self
+    }
+
+    /// Limit the number of bytes collected for OSC strings
+    ///
+    /// Content over the limit is dropped and reported to [`Perform::overflow`].
+    /// `None` removes the limit.
+    ///
+    /// Defaults to no limit, except with the `core` feature but not `alloc` where it is 1024 bytes
+    /// at most.
+    #[must_use]
+    pub fn max_osc_len(mut self, max: Option<usize>) -> Self {
+        #[cfg(all(feature = "core", not(feature = "alloc")))]
+        let capacity = MAX_OSC_RAW;
+        #[cfg(any(not(feature = "core"), feature = "alloc"))]
+        let capacity = usize::MAX;
+        self.max_osc_len = limit(max, capacity);
+        self
+    }
+
     #[inline]
     fn params(&self) -> &Params {
         &self.params
@@ -109,6 +219,49 @@ where
         self.perform_state_change(performer, state, action, byte);
     }
 
+    /// Advance the parser state over a slice of bytes
+    ///
+    /// This is equivalent to calling [`Parser::advance`] for each byte, except runs of printable
+    /// text are found in bulk and passed to [`Perform::print_str`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use anstyle_parse::Perform;
+    /// #[derive(Default)]
+    /// struct Strip(String);
+    ///
+    /// impl Perform for Strip {
+    ///     fn print(&mut self, c: char) {
+    ///         self.0.push(c);
+    ///     }
+    ///
+    ///     fn print_str(&mut self, s: &str) {
+    ///         self.0.push_str(s);
+    ///     }
+    /// }
+    ///
+    /// let mut parser = anstyle_parse::Parser::<anstyle_parse::DefaultCharAccumulator>::new();
+    /// let mut strip = Strip::default();
+    /// parser.advance_bytes(&mut strip, b"Hello \x1b[1mworld\x1b[0m!");
+    /// assert_eq!(strip.0, "Hello world!");
+    /// ```
+    pub fn advance_bytes<P: Perform>(&mut self, performer: &mut P, mut bytes: &[u8]) {
+        while let Some((&byte, remainder)) = bytes.split_first() {
+            if self.state == State::Ground {
+                let text = printable_prefix::<C>(bytes);
+                if !text.is_empty() {
+                    performer.print_str(text);
+                    bytes = &bytes[text.len()..];
+                    continue;
+                }
+            }
+
+            self.advance(performer, byte);
+            bytes = remainder;
+        }
+    }
+
     #[inline]
     fn process_utf8<P>(&mut self, performer: &mut P, byte: u8)
     where
@@ -138,6 +291,7 @@ where
                     State::OscString => {
                         self.perform_action(performer, Action::OscEnd, byte);
                     }
+                    State::SosPmApcString => performer.sos_pm_apc_end(),
                     _ => (),
                 }
 
@@ -158,6 +312,7 @@ where
                     State::OscString => {
                         self.perform_action(performer, Action::OscStart, byte);
                     }
+                    State::SosPmApcString => performer.sos_pm_apc_start(byte),
                     _ => (),
                 }
 
@@ -172,6 +327,16 @@ where
     /// The aliasing is needed here for multiple slices into `self.osc_raw`
     #[inline]
     fn osc_dispatch<P: Perform>(&self, performer: &mut P, byte: u8) {
+        #[cfg(any(not(feature = "core"), feature = "alloc"))]
+        if MAX_OSC_PARAMS < self.osc_num_params {
+            let params = self.osc_params[..self.osc_num_params]
+                .iter()
+                .map(|&(start, end)| &self.osc_raw[start..end])
+                .collect::<Vec<_>>();
+            performer.osc_dispatch(&params, byte == 0x07);
+            return;
+        }
+
         let mut slices: [MaybeUninit<&[u8]>; MAX_OSC_PARAMS] =
             unsafe { MaybeUninit::uninit().assume_init() };
 
@@ -193,82 +358,56 @@ where
             Action::Print => performer.print(byte as char),
             Action::Execute => performer.execute(byte),
             Action::Hook => {
-                if self.params.is_full() {
-                    self.ignoring = true;
+                if self.max_params <= self.params.len() {
+                    self.ignore(performer, Overflow::Params);
                 } else {
                     self.params.push(self.param);
                 }
 
                 performer.hook(self.params(), self.intermediates(), self.ignoring, byte);
             }
-            Action::Put => performer.put(byte),
+            Action::Put => {
+                if self.state == State::SosPmApcString {
+                    performer.sos_pm_apc_put(byte);
+                } else {
+                    performer.put(byte);
+                }
+            }
             Action::OscStart => {
                 self.osc_raw.clear();
                 self.osc_num_params = 0;
+                self.osc_overflow = false;
             }
             Action::OscPut => {
-                #[cfg(feature = "core")]
-                {
-                    if self.osc_raw.is_full() {
-                        return;
-                    }
+                if self.max_osc_len <= self.osc_raw.len() {
+                    self.osc_overflow(performer, Overflow::OscLen);
+                    return;
                 }
 
-                let idx = self.osc_raw.len();
+                // Only process up to `max_osc_params`
+                if self.max_osc_params <= self.osc_num_params {
+                    self.osc_overflow(performer, Overflow::OscParams);
+                    return;
+                }
 
                 // Param separator
                 if byte == b';' {
-                    let param_idx = self.osc_num_params;
-                    match param_idx {
-                        // Only process up to MAX_OSC_PARAMS
-                        MAX_OSC_PARAMS => return,
-
-                        // First param is special - 0 to current byte index
-                        0 => {
-                            self.osc_params[param_idx] = (0, idx);
-                        }
-
-                        // All other params depend on previous indexing
-                        _ => {
-                            let prev = self.osc_params[param_idx - 1];
-                            let begin = prev.1;
-                            self.osc_params[param_idx] = (begin, idx);
-                        }
-                    }
-
-                    self.osc_num_params += 1;
+                    self.finish_osc_param();
                 } else {
                     self.osc_raw.push(byte);
                 }
             }
             Action::OscEnd => {
-                let param_idx = self.osc_num_params;
-                let idx = self.osc_raw.len();
-
-                match param_idx {
-                    // Finish last parameter if not already maxed
-                    MAX_OSC_PARAMS => (),
-
-                    // First param is special - 0 to current byte index
-                    0 => {
-                        self.osc_params[param_idx] = (0, idx);
-                        self.osc_num_params += 1;
-                    }
-
-                    // All other params depend on previous indexing
-                    _ => {
-                        let prev = self.osc_params[param_idx - 1];
-                        let begin = prev.1;
-                        self.osc_params[param_idx] = (begin, idx);
-                        self.osc_num_params += 1;
-                    }
+                // Finish last parameter if not already maxed
+                if self.osc_num_params < self.max_osc_params {
+                    self.finish_osc_param();
                 }
                 self.osc_dispatch(performer, byte);
             }
             Action::Unhook => performer.unhook(),
             Action::CsiDispatch => {
-                if self.params.is_full() {
-                    self.ignoring = true;
+                if self.max_params <= self.params.len() {
+                    self.ignore(performer, Overflow::Params);
                 } else {
                     self.params.push(self.param);
                 }
@@ -280,15 +419,15 @@ where
             }
             Action::Collect => {
                 if self.intermediate_idx == MAX_INTERMEDIATES {
-                    self.ignoring = true;
+                    self.ignore(performer, Overflow::Intermediates);
                 } else {
                     self.intermediates[self.intermediate_idx] = byte;
                     self.intermediate_idx += 1;
                 }
             }
             Action::Param => {
-                if self.params.is_full() {
-                    self.ignoring = true;
+                if self.max_params <= self.params.len() {
+                    self.ignore(performer, Overflow::Params);
                     return;
                 }
 
@@ -317,10 +456,117 @@ where
             Action::Nop => (),
         }
     }
+
+    /// Ignore the rest of the sequence, reporting the first reason why
+    #[inline]
+    fn ignore<P: Perform>(&mut self, performer: &mut P, overflow: Overflow) {
+        if !self.ignoring {
+            self.ignoring = true;
+            performer.overflow(overflow);
+        }
+    }
+
+    /// Report the first limit hit in an OSC string
+    #[inline]
+    fn osc_overflow<P: Perform>(&mut self, performer: &mut P, overflow: Overflow) {
+        if !self.osc_overflow {
+            self.osc_overflow = true;
+            performer.overflow(overflow);
+        }
+    }
+
+    /// End the current OSC parameter at the end of `osc_raw`
+    #[inline]
+    fn finish_osc_param(&mut self) {
+        let param_idx = self.osc_num_params;
+        let end = self.osc_raw.len();
+        // First param is special - 0 to current byte index
+        let begin = match param_idx {
+            0 => 0,
+            // All other params depend on previous indexing
+            _ => self.osc_params[param_idx - 1].1,
+        };
+
+        #[cfg(all(feature = "core", not(feature = "alloc")))]
+        {
+            self.osc_params[param_idx] = (begin, end);
+        }
+        #[cfg(any(not(feature = "core"), feature = "alloc"))]
+        {
+            self.osc_params.truncate(param_idx);
+            self.osc_params.push((begin, end));
+        }
+        self.osc_num_params += 1;
+    }
+}
+
+/// The effective limit for a buffer that, with the `core` feature, holds at most `capacity` items
+#[inline]
+fn limit(max: Option<usize>, capacity: usize) -> usize {
+    let max = max.unwrap_or(usize::MAX);
+    if cfg!(feature = "core") {
+        max.min(capacity)
+    } else {
+        max
+    }
+}
+
+/// The printable text at the start of `bytes`, as [`Parser::advance`] would print it from
+/// [`State::Ground`]
+#[inline]
+fn printable_prefix<C: CharAccumulator>(bytes: &[u8]) -> &str {
+    let len = printable_len(bytes);
+    let valid = if C::UTF8 {
+        match core::str::from_utf8(&bytes[..len]) {
+            Ok(text) => return text,
+            Err(err) => err.valid_up_to(),
+        }
+    } else {
+        bytes[..len]
+            .iter()
+            .position(|b| !b.is_ascii())
+            .unwrap_or(len)
+    };
+    // SAFETY: `valid` is the length of the valid UTF-8 at the start of `bytes`
+    unsafe { core::str::from_utf8_unchecked(&bytes[..valid]) }
+}
+
+/// Length of the run at the start of `bytes` without C0 control characters
+///
+/// This checks a word at a time, falling back to individual bytes for the remainder.
+#[inline]
+fn printable_len(bytes: &[u8]) -> usize {
+    const LANES: usize = (usize::BITS / 8) as usize;
+    const ONES: usize = usize::MAX / 0xff;
+    const HIGHS: usize = ONES * 0x80;
+
+    let mut offset = 0;
+    for chunk in bytes.chunks_exact(LANES) {
+        let mut word = [0; LANES];
+        word.copy_from_slice(chunk);
+        let word = usize::from_ne_bytes(word);
+        // Non-zero when any byte is less than 0x20
+        if word.wrapping_sub(ONES * 0x20) & !word & HIGHS != 0 {
+            break;
+        }
+        offset += LANES;
+    }
+    let remainder = &bytes[offset..];
+    offset
+        + remainder
+            .iter()
+            .position(|b| *b < 0x20)
+            .unwrap_or(remainder.len())
 }
 
 /// Build a `char` out of bytes
 pub trait CharAccumulator: Default {
+    /// Whether [`CharAccumulator::add`] decodes UTF-8
+    ///
+    /// When `true`, [`Parser::advance_bytes`] may decode valid UTF-8 in bulk without calling
+    /// [`CharAccumulator::add`].
+    const UTF8: bool = false;
+
     /// Build a `char` out of bytes
     ///
     /// Return `None` when more data is needed
@@ -353,6 +599,8 @@ pub struct Utf8Parser {
 
 #[cfg(feature = "utf8")]
 impl CharAccumulator for Utf8Parser {
+    const UTF8: bool = true;
+
     fn add(&mut self, byte: u8) -> Option<char> {
         let mut c = None;
         let mut receiver = VtUtf8Receiver(&mut c);
@@ -389,6 +637,17 @@ pub trait Perform {
     /// Draw a character to the screen and update states.
     fn print(&mut self, _c: char) {}
 
+    /// Draw a run of characters to the screen and update states.
+    ///
+    /// Only called by [`Parser::advance_bytes`]; by default, this calls [`Perform::print`] for
+    /// each character.
+    #[inline]
+    fn print_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.print(c);
+        }
+    }
+
     /// Execute a C0 or C1 control function.
     fn execute(&mut self, _byte: u8) {}
 
@@ -416,6 +675,18 @@ pub trait Perform {
     /// Dispatch an operating system command.
     fn osc_dispatch(&mut self, _params: &[&[u8]], _bell_terminated: bool) {}
 
+    /// Invoked when a SOS, PM, or APC string starts.
+    ///
+    /// `kind` is the final byte of the introducer: `X` for SOS, `^` for PM, and `_` for APC.
+    /// The content of the string will be passed to `sos_pm_apc_put`.
+    fn sos_pm_apc_start(&mut self, _kind: u8) {}
+
+    /// Pass bytes as part of a SOS, PM, or APC string. C0 controls are dropped.
+    fn sos_pm_apc_put(&mut self, _byte: u8) {}
+
+    /// Called when a SOS, PM, or APC string is terminated.
+    fn sos_pm_apc_end(&mut self) {}
+
     /// A final character has arrived for a CSI sequence
     ///
     /// The `ignore` flag indicates that either more than two intermediates arrived
@@ -435,6 +706,33 @@ pub trait Perform {
     /// The `ignore` flag indicates that more than two intermediates arrived and
     /// subsequent characters were ignored.
     fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {}
+
+    /// A sequence did not fit within the limits of the [`Parser`].
+    ///
+    /// This is called when the limit is first hit, at most once per sequence.
+    fn overflow(&mut self, _overflow: Overflow) {}
+}
+
+/// A limit of the [`Parser`] that a sequence went over, see [`Perform::overflow`]
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// More parameters than [`Parser::max_params`]
+    ///
+    /// The sequence will be dispatched with `ignore` set.
+    Params,
+    /// More than two intermediates
+    ///
+    /// The sequence will be dispatched with `ignore` set.
+    Intermediates,
+    /// More OSC parameters than [`Parser::max_osc_params`]
+    ///
+    /// The remaining parameters are dropped.
+    OscParams,
+    /// More OSC content than [`Parser::max_osc_len`]
+    ///
+    /// The remaining content is dropped.
+    OscLen,
 }
 
 #[doc = include_str!("../README.md")]
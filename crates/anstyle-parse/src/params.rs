@@ -1,7 +1,10 @@
-//! Fixed size parameters list with optional subparameters.
+//! Parameters list with optional subparameters.
 
+#[cfg(not(feature = "core"))]
+use alloc::vec::Vec;
 use core::fmt::{self, Debug, Formatter};
 
+/// Default limit on the number of parameters and subparameters
 pub(crate) const MAX_PARAMS: usize = 32;
 
 #[derive(Default, Clone, PartialEq, Eq)]
@@ -12,15 +15,22 @@ pub struct Params {
     /// subparams at the same index as the param in the `params` slice.
     ///
     /// At the subparam positions the length will always be `0`.
+    #[cfg(feature = "core")]
     subparams: [u8; MAX_PARAMS],
+    #[cfg(not(feature = "core"))]
+    subparams: Vec<usize>,
 
     /// All parameters and subparameters.
+    #[cfg(feature = "core")]
     params: [u16; MAX_PARAMS],
+    #[cfg(not(feature = "core"))]
+    params: Vec<u16>,
 
     /// Number of suparameters in the current parameter.
-    current_subparams: u8,
+    current_subparams: usize,
 
     /// Total number of parameters and subparameters.
+    #[cfg(feature = "core")]
     len: usize,
 }
 
@@ -28,13 +38,20 @@ impl Params {
     /// Returns the number of parameters.
     #[inline]
     pub fn len(&self) -> usize {
-        self.len
+        #[cfg(feature = "core")]
+        {
+            self.len
+        }
+        #[cfg(not(feature = "core"))]
+        {
+            self.params.len()
+        }
     }
 
     /// Returns `true` if there are no parameters present.
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.len == 0
+        self.len() == 0
     }
 
     /// Returns an iterator over all parameters and subparameters.
@@ -43,35 +60,64 @@ impl Params {
         ParamsIter::new(self)
     }
 
-    /// Returns `true` if there is no more space for additional parameters.
-    #[inline]
-    pub(crate) fn is_full(&self) -> bool {
-        self.len == MAX_PARAMS
-    }
-
     /// Clear all parameters.
     #[inline]
     pub(crate) fn clear(&mut self) {
         self.current_subparams = 0;
-        self.len = 0;
+        #[cfg(feature = "core")]
+        {
+            self.len = 0;
+        }
+        #[cfg(not(feature = "core"))]
+        {
+            self.subparams.clear();
+            self.params.clear();
+        }
     }
 
     /// Add an additional parameter.
     #[inline]
     pub(crate) fn push(&mut self, item: u16) {
-        self.subparams[self.len - self.current_subparams as usize] = self.current_subparams + 1;
-        self.params[self.len] = item;
+        self.add(item);
         self.current_subparams = 0;
-        self.len += 1;
     }
 
     /// Add an additional subparameter to the current parameter.
     #[inline]
     pub(crate) fn extend(&mut self, item: u16) {
-        self.subparams[self.len - self.current_subparams as usize] = self.current_subparams + 1;
-        self.params[self.len] = item;
+        self.add(item);
         self.current_subparams += 1;
-        self.len += 1;
+    }
+
+    #[inline]
+    fn add(&mut self, item: u16) {
+        let start = self.len() - self.current_subparams;
+        let num_subparams = self.current_subparams + 1;
+        #[cfg(feature = "core")]
+        {
+            self.subparams[start] = num_subparams as u8;
+            self.params[self.len] = item;
+            self.len += 1;
+        }
+        #[cfg(not(feature = "core"))]
+        {
+            self.subparams.push(0);
+            self.subparams[start] = num_subparams;
+            self.params.push(item);
+        }
+    }
+
+    /// Number of subparameters for the parameter starting at `index`
+    #[inline]
+    fn num_subparams(&self, index: usize) -> usize {
+        #[cfg(feature = "core")]
+        {
+            self.subparams[index] as usize
+        }
+        #[cfg(not(feature = "core"))]
+        {
+            self.subparams[index]
+        }
     }
 }
 
@@ -105,11 +151,11 @@ impl<'a> Iterator for ParamsIter<'a> {
         }
 
         // Get all subparameters for the current parameter.
-        let num_subparams = self.params.subparams[self.index];
-        let param = &self.params.params[self.index..self.index + num_subparams as usize];
+        let num_subparams = self.params.num_subparams(self.index);
+        let param = &self.params.params[self.index..self.index + num_subparams];
 
         // Jump to the next parameter.
-        self.index += num_subparams as usize;
+        self.index += num_subparams;
 
         Some(param)
     }
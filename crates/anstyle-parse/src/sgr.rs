@@ -0,0 +1,667 @@
+//! Interpret SGR and OSC 8 sequences as [`anstyle::Style`]s and hyperlinks
+//!
+//! [`SgrState`] tracks the active style from within any [`Perform`][crate::Perform] while
+//! [`StyledSpans`] splits ANSI-escaped text into [`StyledSpan`]s.
+
+use alloc::string::String;
+
+use anstyle::{Ansi256Color, AnsiColor, Color, Effects, RgbColor, Style};
+
+use crate::state::State;
+use crate::{Params, Parser, Perform};
+
+const UNDERLINES: Effects = Effects::UNDERLINE
+    .insert(Effects::DOUBLE_UNDERLINE)
+    .insert(Effects::CURLY_UNDERLINE)
+    .insert(Effects::DOTTED_UNDERLINE)
+    .insert(Effects::DASHED_UNDERLINE);
+
+/// Apply the parameters of a Select Graphic Rendition sequence (`CSI ... m`) to `style`
+///
+/// Colors may be given as `;` separated parameters (`38;5;208`) or `:` separated
+/// subparameters (`38:2::255:128:0`).  Unrecognized parameters are skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// # use anstyle_parse::{Params, Parser, Perform};
+/// #[derive(Default)]
+/// struct Capture(anstyle::Style);
+///
+/// impl Perform for Capture {
+///     fn csi_dispatch(&mut self, params: &Params, _intermediates: &[u8], _ignore: bool, action: u8) {
+///         if action == b'm' {
+///             self.0 = anstyle_parse::sgr::apply_sgr(self.0, params);
+///         }
+///     }
+/// }
+///
+/// let mut parser = Parser::<anstyle_parse::DefaultCharAccumulator>::new();
+/// let mut capture = Capture::default();
+/// for byte in b"\x1b[1;38:5:208m" {
+///     parser.advance(&mut capture, *byte);
+/// }
+/// assert_eq!(capture.0, anstyle::Ansi256Color(208).on_default().bold());
+/// ```
+pub fn apply_sgr(mut style: Style, params: &Params) -> Style {
+    if params.is_empty() {
+        return Style::new();
+    }
+
+    let mut params = params.iter();
+    while let Some(param) = params.next() {
+        let Some((&code, subparams)) = param.split_first() else {
+            continue;
+        };
+        match code {
+            0 => style = Style::new(),
+            1 => style = style.bold(),
+            2 => style = style.dimmed(),
+            3 => style = style.italic(),
+            4 => {
+                let effect = match subparams.first() {
+                    None | Some(1) => Effects::UNDERLINE,
+                    Some(0) => {
+                        style -= UNDERLINES;
+                        continue;
+                    }
+                    Some(2) => Effects::DOUBLE_UNDERLINE,
+                    Some(3) => Effects::CURLY_UNDERLINE,
+                    Some(4) => Effects::DOTTED_UNDERLINE,
+                    Some(5) => Effects::DASHED_UNDERLINE,
+                    Some(_) => continue,
+                };
+                style |= effect;
+            }
+            5 => style = style.blink(),
+            6 => style |= Effects::RAPID_BLINK,
+            7 => style = style.invert(),
+            8 => style = style.hidden(),
+            9 => style = style.strikethrough(),
+            21 => style |= Effects::DOUBLE_UNDERLINE,
+            22 => style -= Effects::BOLD | Effects::DIMMED,
+            23 => style -= Effects::ITALIC,
+            24 => style -= UNDERLINES,
+            25 => style -= Effects::BLINK | Effects::RAPID_BLINK,
+            26 => style |= Effects::PROPORTIONAL_SPACING,
+            27 => style -= Effects::INVERT,
+            28 => style -= Effects::HIDDEN,
+            29 => style -= Effects::STRIKETHROUGH,
+            30..=37 => style = style.fg_color(Some(to_ansi_color(code - 30).into())),
+            38 => {
+                if let Some(color) = extended_color(subparams, &mut params) {
+                    style = style.fg_color(Some(color));
+                }
+            }
+            39 => style = style.fg_color(None),
+            40..=47 => style = style.bg_color(Some(to_ansi_color(code - 40).into())),
+            48 => {
+                if let Some(color) = extended_color(subparams, &mut params) {
+                    style = style.bg_color(Some(color));
+                }
+            }
+            49 => style = style.bg_color(None),
+            50 => style -= Effects::PROPORTIONAL_SPACING,
+            51 => style |= Effects::FRAMED,
+            52 => style |= Effects::ENCIRCLED,
+            53 => style = style.overline(),
+            54 => style -= Effects::FRAMED | Effects::ENCIRCLED,
+            55 => style -= Effects::OVERLINE,
+            58 => {
+                if let Some(color) = extended_color(subparams, &mut params) {
+                    style = style.underline_color(Some(color));
+                }
+            }
+            59 => style = style.underline_color(None),
+            73 => style |= Effects::SUPERSCRIPT,
+            74 => style |= Effects::SUBSCRIPT,
+            75 => style -= Effects::SUPERSCRIPT | Effects::SUBSCRIPT,
+            90..=97 => {
+                style = style.fg_color(Some(to_ansi_color(code - 90).bright(true).into()));
+            }
+            100..=107 => {
+                style = style.bg_color(Some(to_ansi_color(code - 100).bright(true).into()));
+            }
+            _ => {}
+        }
+    }
+
+    style
+}
+
+/// Read the color for `38`, `48`, or `58`
+///
+/// With subparameters, everything is self-contained.  Otherwise, the color is read from (and
+/// consumes) the following parameters.
+fn extended_color<'p>(
+    subparams: &[u16],
+    params: &mut impl Iterator<Item = &'p [u16]>,
+) -> Option<Color> {
+    if let Some((&kind, values)) = subparams.split_first() {
+        return match (kind, values) {
+            (5, [index]) => Some(Ansi256Color(to_u8(*index)?).into()),
+            // Skip the color space ID
+            (2, [r, g, b] | [_, r, g, b]) => {
+                Some(RgbColor(to_u8(*r)?, to_u8(*g)?, to_u8(*b)?).into())
+            }
+            _ => None,
+        };
+    }
+
+    let mut next = || params.next().and_then(|param| param.first().copied());
+    match next()? {
+        5 => Some(Ansi256Color(to_u8(next()?)?).into()),
+        2 => {
+            let r = next()?;
+            let g = next()?;
+            let b = next()?;
+            Some(RgbColor(to_u8(r)?, to_u8(g)?, to_u8(b)?).into())
+        }
+        _ => None,
+    }
+}
+
+fn to_u8(value: u16) -> Option<u8> {
+    u8::try_from(value).ok()
+}
+
+fn to_ansi_color(digit: u16) -> AnsiColor {
+    match digit {
+        0 => AnsiColor::Black,
+        1 => AnsiColor::Red,
+        2 => AnsiColor::Green,
+        3 => AnsiColor::Yellow,
+        4 => AnsiColor::Blue,
+        5 => AnsiColor::Magenta,
+        6 => AnsiColor::Cyan,
+        7 => AnsiColor::White,
+        _ => unreachable!("only called within the 4-bit range"),
+    }
+}
+
+/// Track the [`Style`] and hyperlink selected by SGR and OSC 8 sequences
+///
+/// Forward [`Perform::csi_dispatch`] and [`Perform::osc_dispatch`] calls from your own
+/// [`Perform`] to keep this up-to-date, including across chunk boundaries.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct SgrState {
+    style: Style,
+    hyperlink: Option<String>,
+}
+
+impl SgrState {
+    /// Start from the terminal's default style
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The currently active style
+    #[inline]
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// The currently active OSC 8 hyperlink URL
+    #[inline]
+    pub fn hyperlink(&self) -> Option<&str> {
+        self.hyperlink.as_deref()
+    }
+
+    /// Return to the terminal's default style, without a hyperlink
+    pub fn reset(&mut self) {
+        self.style = Style::new();
+        self.hyperlink = None;
+    }
+
+    /// Apply a CSI sequence, if it is SGR or a soft reset (`CSI ! p`)
+    ///
+    /// Returns `true` if the style or hyperlink changed.
+    pub fn csi_dispatch(
+        &mut self,
+        params: &Params,
+        intermediates: &[u8],
+        ignore: bool,
+        action: u8,
+    ) -> bool {
+        if ignore {
+            return false;
+        }
+
+        match (intermediates, action) {
+            ([], b'm') => {
+                let style = apply_sgr(self.style, params);
+                let changed = style != self.style;
+                self.style = style;
+                changed
+            }
+            ([b'!'], b'p') => {
+                let changed = *self != Self::new();
+                self.reset();
+                changed
+            }
+            _ => false,
+        }
+    }
+
+    /// Apply an OSC sequence, if it is an OSC 8 hyperlink
+    ///
+    /// Returns `true` if the hyperlink changed.
+    pub fn osc_dispatch(&mut self, params: &[&[u8]], _bell_terminated: bool) -> bool {
+        let [b"8", _params, uri @ ..] = params else {
+            return false;
+        };
+
+        let hyperlink = if uri.iter().all(|part| part.is_empty()) {
+            None
+        } else {
+            // URIs may contain `;`, so rejoin what the parser split apart
+            let uri = uri.join(&b';');
+            Some(String::from_utf8_lossy(&uri).into_owned())
+        };
+        let changed = hyperlink != self.hyperlink;
+        self.hyperlink = hyperlink;
+        changed
+    }
+}
+
+/// A run of text sharing the same [`Style`] and hyperlink
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct StyledSpan {
+    text: String,
+    style: Style,
+    url: Option<String>,
+}
+
+impl StyledSpan {
+    /// The printable text, without escape codes
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The style the text was printed with
+    #[inline]
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// The OSC 8 hyperlink the text was printed in
+    #[inline]
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// Take ownership of the printable text
+    #[inline]
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    /// Take ownership of all parts
+    #[inline]
+    pub fn into_parts(self) -> (String, Style, Option<String>) {
+        (self.text, self.style, self.url)
+    }
+}
+
+/// Incrementally split ANSI-escaped text into [`StyledSpan`]s for non-contiguous data
+///
+/// Styles and hyperlinks carry over between calls to [`StyledSpans::extract_next`], though a
+/// span may be split at a chunk boundary.
+///
+/// Only printable text and whitespace control characters are kept; all other escape
+/// sequences are dropped.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut spans = anstyle_parse::sgr::StyledSpans::new();
+/// let mut actual = Vec::new();
+/// for chunk in [&b"Hello \x1b[1"[..], &b"mworld\x1b[0m!"[..]] {
+///     actual.extend(spans.extract_next(chunk).map(|s| (s.style(), s.into_text())));
+/// }
+///
+/// let bold = anstyle::Style::new().bold();
+/// assert_eq!(
+///     actual,
+///     [
+///         (anstyle::Style::new(), "Hello ".to_owned()),
+///         (bold, "world".to_owned()),
+///         (anstyle::Style::new(), "!".to_owned()),
+///     ]
+/// );
+/// ```
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct StyledSpans {
+    parser: Parser,
+    capture: SpanCapture,
+}
+
+impl StyledSpans {
+    /// Initial state
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Split the next segment of data
+    pub fn extract_next<'s>(&'s mut self, bytes: &'s [u8]) -> StyledSpansIter<'s> {
+        self.capture.ready = false;
+        StyledSpansIter {
+            bytes,
+            parser: &mut self.parser,
+            capture: &mut self.capture,
+        }
+    }
+
+    /// The style and hyperlink at the current position
+    #[inline]
+    pub fn state(&self) -> &SgrState {
+        &self.capture.state
+    }
+}
+
+/// See [`StyledSpans`]
+#[derive(Debug, PartialEq, Eq)]
+pub struct StyledSpansIter<'s> {
+    bytes: &'s [u8],
+    parser: &'s mut Parser,
+    capture: &'s mut SpanCapture,
+}
+
+impl Iterator for StyledSpansIter<'_> {
+    type Item = StyledSpan;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        next_span(&mut self.bytes, self.parser, self.capture)
+    }
+}
+
+#[inline]
+fn next_span(
+    bytes: &mut &[u8],
+    parser: &mut Parser,
+    capture: &mut SpanCapture,
+) -> Option<StyledSpan> {
+    capture.ready = false;
+    while !capture.ready {
+        let Some((byte, remainder)) = (*bytes).split_first() else {
+            break;
+        };
+        if parser.state == State::Ground {
+            // Styles can only change within an escape sequence, so text up to the next one can
+            // be handled in bulk
+            let text_len = bytes.iter().position(|b| *b == 0x1b).unwrap_or(bytes.len());
+            if text_len != 0 {
+                let (text, remainder) = bytes.split_at(text_len);
+                *bytes = remainder;
+                parser.advance_bytes(capture, text);
+                continue;
+            }
+        }
+        *bytes = remainder;
+        parser.advance(capture, *byte);
+    }
+    if capture.printable.is_empty() {
+        return None;
+    }
+
+    let (style, url) = core::mem::take(&mut capture.printable_state);
+    Some(StyledSpan {
+        text: core::mem::take(&mut capture.printable),
+        style,
+        url,
+    })
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+struct SpanCapture {
+    state: SgrState,
+    printable: String,
+    /// The style and hyperlink `printable` was written with
+    printable_state: (Style, Option<String>),
+    ready: bool,
+}
+
+impl SpanCapture {
+    fn push(&mut self, c: char) {
+        self.start_printable();
+        self.printable.push(c);
+    }
+
+    fn push_str(&mut self, s: &str) {
+        self.start_printable();
+        self.printable.push_str(s);
+    }
+
+    fn start_printable(&mut self) {
+        if self.printable.is_empty() {
+            self.printable_state = (self.state.style(), self.state.hyperlink().map(String::from));
+        }
+    }
+
+    fn check_ready(&mut self) {
+        if !self.printable.is_empty()
+            && (self.printable_state.0 != self.state.style()
+                || self.printable_state.1.as_deref() != self.state.hyperlink())
+        {
+            self.ready = true;
+        }
+    }
+}
+
+impl Perform for SpanCapture {
+    fn print(&mut self, c: char) {
+        self.push(c);
+    }
+
+    fn print_str(&mut self, s: &str) {
+        self.push_str(s);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        if byte.is_ascii_whitespace() {
+            self.push(byte as char);
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: u8) {
+        if self
+            .state
+            .csi_dispatch(params, intermediates, ignore, action)
+        {
+            self.check_ready();
+        }
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        if self.state.osc_dispatch(params, bell_terminated) {
+            self.check_ready();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::borrow::ToOwned;
+    use std::format;
+    use std::vec::Vec;
+
+    #[track_caller]
+    fn verify_style(input: &str, expected: Style) {
+        let mut spans = StyledSpans::new();
+        let _ = spans.extract_next(input.as_bytes()).count();
+        assert_eq!(spans.state().style(), expected, "{input:?}");
+    }
+
+    #[test]
+    fn reset() {
+        verify_style("\x1b[1;31m\x1b[m", Style::new());
+        verify_style("\x1b[1;31m\x1b[0m", Style::new());
+        verify_style("\x1b[1;31;0;3m", Style::new().italic());
+    }
+
+    #[test]
+    fn effects() {
+        let all = Style::new()
+            .bold()
+            .dimmed()
+            .italic()
+            .underline()
+            .blink()
+            .invert()
+            .hidden()
+            .strikethrough();
+        verify_style("\x1b[1;2;3;4;5;7;8;9m", all);
+        verify_style("\x1b[1;2;3;4;5;7;8;9;22;23;24;25;27;28;29m", Style::new());
+        verify_style("\x1b[6m", Style::new() | Effects::RAPID_BLINK);
+        verify_style("\x1b[5;6m\x1b[25m", Style::new());
+    }
+
+    #[test]
+    fn extended_effects() {
+        let all = Style::new().overline()
+            | Effects::RAPID_BLINK
+            | Effects::FRAMED
+            | Effects::ENCIRCLED
+            | Effects::SUPERSCRIPT
+            | Effects::SUBSCRIPT
+            | Effects::PROPORTIONAL_SPACING;
+        verify_style("\x1b[53;6;51;52;73;74;26m", all);
+        verify_style("\x1b[53;6;51;52;73;74;26;55;25;54;75;50m", Style::new());
+    }
+
+    #[test]
+    fn underline_styles() {
+        verify_style("\x1b[4:3m", Style::new() | Effects::CURLY_UNDERLINE);
+        verify_style("\x1b[21m", Style::new() | Effects::DOUBLE_UNDERLINE);
+        verify_style("\x1b[4:2;4:4;4:5m\x1b[4:0m", Style::new());
+        verify_style("\x1b[4:3m\x1b[24m", Style::new());
+    }
+
+    #[test]
+    fn ansi_colors() {
+        verify_style("\x1b[31;42m", AnsiColor::Red.on(AnsiColor::Green));
+        verify_style(
+            "\x1b[91;102m",
+            AnsiColor::BrightRed.on(AnsiColor::BrightGreen),
+        );
+        verify_style("\x1b[31;42m\x1b[39;49m", Style::new());
+    }
+
+    #[test]
+    fn extended_colors() {
+        let orange = RgbColor(255, 128, 0);
+        verify_style("\x1b[38;5;208m", Ansi256Color(208).on_default());
+        verify_style("\x1b[38:5:208m", Ansi256Color(208).on_default());
+        verify_style(
+            "\x1b[48;2;255;128;0m",
+            Style::new().bg_color(Some(orange.into())),
+        );
+        verify_style(
+            "\x1b[48:2:255:128:0m",
+            Style::new().bg_color(Some(orange.into())),
+        );
+        verify_style(
+            "\x1b[48:2::255:128:0m",
+            Style::new().bg_color(Some(orange.into())),
+        );
+        verify_style(
+            "\x1b[58:5:1;4m",
+            Style::new()
+                .underline()
+                .underline_color(Some(Ansi256Color(1).into())),
+        );
+        verify_style("\x1b[58;5;1m\x1b[59m", Style::new());
+        // Trailing parameters are still applied after `;` separated colors
+        verify_style("\x1b[38;2;255;128;0;1m", orange.on_default().bold());
+    }
+
+    #[test]
+    fn invalid_colors() {
+        verify_style("\x1b[38;5;300m", Style::new());
+        verify_style("\x1b[38;5m", Style::new());
+        verify_style("\x1b[38:2:1:2m", Style::new());
+        verify_style("\x1b[38;7;1m", Style::new().bold());
+    }
+
+    #[test]
+    fn round_trip() {
+        let styles = [
+            Style::new().bold().underline(),
+            Style::new() | Effects::DOTTED_UNDERLINE,
+            Style::new().overline() | Effects::FRAMED | Effects::SUBSCRIPT,
+            AnsiColor::Red.on(Ansi256Color(200)),
+            RgbColor(1, 2, 3)
+                .on(AnsiColor::BrightWhite)
+                .underline_color(Some(RgbColor(4, 5, 6).into()))
+                .italic(),
+        ];
+        for style in styles {
+            verify_style(&format!("{style}"), style);
+        }
+    }
+
+    #[test]
+    fn non_sgr() {
+        verify_style("\x1b[1;31H", Style::new());
+        verify_style("\x1b[>1m", Style::new());
+    }
+
+    #[test]
+    fn soft_reset() {
+        verify_style("\x1b[1;31m\x1b[!p", Style::new());
+
+        let mut spans = StyledSpans::new();
+        let _ = spans
+            .extract_next(b"\x1b]8;;https://example.com\x1b\\\x1b[!p")
+            .count();
+        assert_eq!(spans.state(), &SgrState::new());
+    }
+
+    #[test]
+    fn hyperlink() {
+        let mut state = SgrState::new();
+        assert!(state.osc_dispatch(&[b"8", b"", b"https://example.com"], false));
+        assert_eq!(state.hyperlink(), Some("https://example.com"));
+        assert!(!state.osc_dispatch(&[b"8", b"id=1", b"https://example.com"], false));
+        assert!(state.osc_dispatch(&[b"8", b"", b"https://example.com/?a=1", b"b=2"], true));
+        assert_eq!(state.hyperlink(), Some("https://example.com/?a=1;b=2"));
+        assert!(!state.osc_dispatch(&[b"2", b"title"], true));
+        assert!(state.osc_dispatch(&[b"8", b"", b""], false));
+        assert_eq!(state.hyperlink(), None);
+    }
+
+    #[test]
+    fn spans_across_chunks() {
+        let bold = Style::new().bold();
+        let input = format!("a{bold}b\x1b]8;;https://example.com\x1b\\c\x1b]8;;\x1b\\{bold:#}d");
+        for split in 0..input.len() {
+            let (first, second) = input.as_bytes().split_at(split);
+            let mut spans = StyledSpans::new();
+            let mut actual: Vec<(Style, Option<String>, String)> = Vec::new();
+            for chunk in [first, second] {
+                for span in spans.extract_next(chunk) {
+                    let (text, style, url) = span.into_parts();
+                    match actual.last_mut() {
+                        Some(last) if last.0 == style && last.1 == url => last.2.push_str(&text),
+                        _ => actual.push((style, url, text)),
+                    }
+                }
+            }
+            let url = Some("https://example.com".to_owned());
+            assert_eq!(
+                actual,
+                [
+                    (Style::new(), None, "a".to_owned()),
+                    (bold, None, "b".to_owned()),
+                    (bold, url, "c".to_owned()),
+                    (Style::new(), None, "d".to_owned()),
+                ],
+                "split at {split}"
+            );
+        }
+    }
+}
@@ -203,7 +203,7 @@ generate_state_changes!(state_changes, {
         0x00..=0x17 => (Anywhere, Ignore),
         0x19        => (Anywhere, Ignore),
         0x1c..=0x1f => (Anywhere, Ignore),
-        0x20..=0x7f => (Anywhere, Ignore),
+        0x20..=0x7f => (Anywhere, Put),
         0x9c        => (Ground, Nop),
     },
 
@@ -61,6 +61,18 @@ impl Perform for Dispatcher {
     fn unhook(&mut self) {
         self.dispatched.push(Sequence::DcsUnhook);
     }
+
+    fn sos_pm_apc_start(&mut self, kind: u8) {
+        self.dispatched.push(Sequence::SosPmApcStart(kind));
+    }
+
+    fn sos_pm_apc_put(&mut self, byte: u8) {
+        self.dispatched.push(Sequence::SosPmApcPut(byte));
+    }
+
+    fn sos_pm_apc_end(&mut self) {
+        self.dispatched.push(Sequence::SosPmApcEnd);
+    }
 }
 
 impl std::ops::Deref for Dispatcher {
@@ -129,6 +141,9 @@ enum Sequence {
     DcsHook(Vec<Vec<u16>>, Vec<u8>, bool, u8),
     DcsPut(u8),
     DcsUnhook,
+    SosPmApcStart(u8),
+    SosPmApcPut(u8),
+    SosPmApcEnd,
 }
 
 impl From<char> for Sequence {
@@ -154,6 +169,21 @@ macro_rules! advance {
     };
 }
 
+macro_rules! advance_bytes {
+    ($name: ident, $gen: ident) => {
+        #[test]
+        fn $name() {
+            let (input, expected) = $gen();
+            let mut dispatcher = Dispatcher::default();
+            let mut parser = Parser::<DefaultCharAccumulator>::new();
+
+            parser.advance_bytes(&mut dispatcher, &input);
+
+            assert_eq!(expected, dispatcher);
+        }
+    };
+}
+
 fn gen_osc() -> (Vec<u8>, Dispatcher) {
     let input = OSC_BYTES.to_vec();
     let expected = start()
@@ -249,9 +279,9 @@ fn gen_exceed_max_buffer_size() -> (Vec<u8>, Dispatcher) {
     input.resize(INPUT_START.len() + NUM_BYTES, b'a');
     input.extend(INPUT_END);
     let mut param = vec![115];
-    #[cfg(not(feature = "core"))]
+    #[cfg(any(not(feature = "core"), feature = "alloc"))]
     param.extend(vec![97; NUM_BYTES + INPUT_END.len() - 1]);
-    #[cfg(feature = "core")]
+    #[cfg(all(feature = "core", not(feature = "alloc")))]
     param.extend(vec![97; MAX_OSC_RAW - INPUT_END.len() - 2]);
     let expected = start() + Sequence::Osc(vec![b"52".to_vec(), param], true);
     (input, expected)
@@ -389,6 +419,55 @@ advance!(
     gen_intermediate_reset_on_dcs_exit
 );
 
+fn sos_pm_apc(kind: u8, content: &[u8]) -> Dispatcher {
+    let mut expected = start() + Sequence::SosPmApcStart(kind);
+    for byte in content {
+        expected = expected + Sequence::SosPmApcPut(*byte);
+    }
+    expected + Sequence::SosPmApcEnd
+}
+
+fn gen_apc() -> (Vec<u8>, Dispatcher) {
+    // Kitty graphics protocol
+    let input = b"\x1b_Gf=100;AAAA\x1b\\".to_vec();
+    let expected = sos_pm_apc(b'_', b"Gf=100;AAAA") + Sequence::Esc(vec![], false, b'\\');
+    (input, expected)
+}
+
+advance!(advance_apc, gen_apc);
+
+fn gen_pm() -> (Vec<u8>, Dispatcher) {
+    let input = b"\x1b^privacy\x9c".to_vec();
+    let expected = sos_pm_apc(b'^', b"privacy");
+    (input, expected)
+}
+
+advance!(advance_pm, gen_pm);
+
+fn gen_sos() -> (Vec<u8>, Dispatcher) {
+    let input = b"\x1bXstring\x1b\\".to_vec();
+    let expected = sos_pm_apc(b'X', b"string") + Sequence::Esc(vec![], false, b'\\');
+    (input, expected)
+}
+
+advance!(advance_sos, gen_sos);
+
+fn gen_apc_drops_c0() -> (Vec<u8>, Dispatcher) {
+    let input = b"\x1b_a\x07b\nc\x1b\\".to_vec();
+    let expected = sos_pm_apc(b'_', b"abc") + Sequence::Esc(vec![], false, b'\\');
+    (input, expected)
+}
+
+advance!(advance_apc_drops_c0, gen_apc_drops_c0);
+
+fn gen_apc_cancelled() -> (Vec<u8>, Dispatcher) {
+    let input = b"\x1b_ab\x18cd".to_vec();
+    let expected = sos_pm_apc(b'_', b"ab") + Sequence::Print('c') + Sequence::Print('d');
+    (input, expected)
+}
+
+advance!(advance_apc_cancelled, gen_apc_cancelled);
+
 fn gen_esc_reset() -> (Vec<u8>, Dispatcher) {
     let input = b"\x1b[3;1\x1b(A".to_vec();
     let expected = start() + Sequence::Esc(vec![b'('], false, b'A');
@@ -408,6 +487,592 @@ advance!(
     gen_params_buffer_filled_with_subparam
 );
 
+advance_bytes!(advance_bytes_osc, gen_osc);
+advance_bytes!(advance_bytes_empty_osc, gen_empty_osc);
+advance_bytes!(advance_bytes_osc_max_params, gen_osc_max_params);
+advance_bytes!(advance_bytes_osc_bell_terminated, gen_osc_bell_terminated);
+advance_bytes!(advance_bytes_osc_c0_st_terminated, gen_osc_c0_st_terminated);
+advance_bytes!(
+    advance_bytes_osc_with_utf8_arguments,
+    gen_osc_with_utf8_arguments
+);
+advance_bytes!(
+    advance_bytes_osc_containing_string_terminator,
+    gen_osc_containing_string_terminator
+);
+advance_bytes!(
+    advance_bytes_exceed_max_buffer_size,
+    gen_exceed_max_buffer_size
+);
+advance_bytes!(advance_bytes_csi_max_params, gen_csi_max_params);
+advance_bytes!(
+    advance_bytes_csi_params_ignore_long_params,
+    gen_csi_params_ignore_long_params
+);
+advance_bytes!(
+    advance_bytes_csi_params_trailing_semicolon,
+    gen_csi_params_trailing_semicolon
+);
+advance_bytes!(
+    advance_bytes_csi_params_leading_semicolon,
+    gen_csi_params_leading_semicolon
+);
+advance_bytes!(advance_bytes_csi_long_param, gen_csi_long_param);
+advance_bytes!(advance_bytes_csi_reset, gen_csi_reset);
+advance_bytes!(advance_bytes_csi_subparameters, gen_csi_subparameters);
+advance_bytes!(advance_bytes_dcs_max_params, gen_dcs_max_params);
+advance_bytes!(advance_bytes_dcs_reset, gen_dcs_reset);
+advance_bytes!(advance_bytes_dcs, gen_dcs);
+advance_bytes!(
+    advance_bytes_intermediate_reset_on_dcs_exit,
+    gen_intermediate_reset_on_dcs_exit
+);
+advance_bytes!(advance_bytes_esc_reset, gen_esc_reset);
+advance_bytes!(
+    advance_bytes_params_buffer_filled_with_subparam,
+    gen_params_buffer_filled_with_subparam
+);
+advance_bytes!(advance_bytes_apc, gen_apc);
+advance_bytes!(advance_bytes_pm, gen_pm);
+advance_bytes!(advance_bytes_sos, gen_sos);
+advance_bytes!(advance_bytes_apc_drops_c0, gen_apc_drops_c0);
+advance_bytes!(advance_bytes_apc_cancelled, gen_apc_cancelled);
+
+#[derive(Default, PartialEq, Eq, Debug)]
+struct Overflows {
+    dispatcher: Dispatcher,
+    overflows: Vec<Overflow>,
+}
+
+impl Perform for Overflows {
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        self.dispatcher.osc_dispatch(params, bell_terminated);
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: u8) {
+        self.dispatcher
+            .csi_dispatch(params, intermediates, ignore, c);
+    }
+
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: u8) {
+        self.dispatcher.hook(params, intermediates, ignore, c);
+    }
+
+    fn overflow(&mut self, overflow: Overflow) {
+        self.overflows.push(overflow);
+    }
+}
+
+fn overflows(mut parser: Parser<DefaultCharAccumulator>, input: &[u8]) -> Overflows {
+    let mut overflows = Overflows::default();
+    parser.advance_bytes(&mut overflows, input);
+    overflows
+}
+
+#[test]
+fn overflow_params() {
+    let (input, expected) = gen_csi_params_ignore_long_params();
+    let actual = overflows(Parser::default(), &input);
+    assert_eq!(actual.dispatcher, expected);
+    assert_eq!(actual.overflows, [Overflow::Params]);
+
+    let input = format!("\x1bP{}q\x1b\\", "1:".repeat(MAX_PARAMS + 8));
+    let actual = overflows(Parser::default(), input.as_bytes());
+    assert_eq!(actual.overflows, [Overflow::Params]);
+}
+
+#[test]
+fn overflow_intermediates() {
+    let actual = overflows(Parser::default(), b"\x1b[1 !\"#p\x1b[1 !p");
+    assert_eq!(
+        actual.dispatcher,
+        start()
+            + Sequence::Csi(vec![vec![1]], vec![b' ', b'!'], true, b'p')
+            + Sequence::Csi(vec![vec![1]], vec![b' ', b'!'], false, b'p')
+    );
+    assert_eq!(actual.overflows, [Overflow::Intermediates]);
+}
+
+#[test]
+fn overflow_osc_params() {
+    let (input, expected) = gen_osc_max_params();
+    let actual = overflows(Parser::default(), &input);
+    assert_eq!(actual.dispatcher, expected);
+    assert_eq!(actual.overflows, [Overflow::OscParams]);
+
+    let actual = overflows(
+        Parser::default().max_osc_params(Some(2)),
+        b"\x1b]8;;url\x07",
+    );
+    assert_eq!(
+        actual.dispatcher,
+        start() + Sequence::Osc(vec![b"8".to_vec(), vec![]], true)
+    );
+    assert_eq!(actual.overflows, [Overflow::OscParams]);
+}
+
+#[test]
+fn overflow_osc_len() {
+    let parser = Parser::default().max_osc_len(Some(4));
+    let actual = overflows(parser, b"\x1b]0;abcdef\x07\x1b]0;abc\x07");
+    assert_eq!(
+        actual.dispatcher,
+        start()
+            + Sequence::Osc(vec![b"0".to_vec(), b"abc".to_vec()], true)
+            + Sequence::Osc(vec![b"0".to_vec(), b"abc".to_vec()], true)
+    );
+    assert_eq!(actual.overflows, [Overflow::OscLen]);
+}
+
+#[test]
+#[cfg(not(feature = "core"))]
+fn unbounded_params() {
+    let input = format!("\x1b[{}m", "1;2:3;".repeat(MAX_PARAMS));
+    let actual = overflows(Parser::default().max_params(None), input.as_bytes());
+    let mut params = vec![vec![vec![1], vec![2, 3]]; MAX_PARAMS].concat();
+    params.push(vec![0]);
+    assert_eq!(
+        actual.dispatcher,
+        start() + Sequence::Csi(params, vec![], false, b'm')
+    );
+    assert_eq!(actual.overflows, []);
+}
+
+#[test]
+#[cfg(any(not(feature = "core"), feature = "alloc"))]
+fn unbounded_osc_params() {
+    let input = format!("\x1b]{}\x07", ";".repeat(MAX_OSC_PARAMS * 2));
+    let actual = overflows(Parser::default().max_osc_params(None), input.as_bytes());
+    assert_eq!(
+        actual.dispatcher,
+        start() + Sequence::Osc(vec![vec![]; MAX_OSC_PARAMS * 2 + 1], true)
+    );
+    assert_eq!(actual.overflows, []);
+}
+
+#[test]
+#[cfg(feature = "core")]
+fn unbounded_is_capped_with_core() {
+    let (input, expected) = gen_csi_params_ignore_long_params();
+    let actual = overflows(Parser::default().max_params(None), &input);
+    assert_eq!(actual.dispatcher, expected);
+    assert_eq!(actual.overflows, [Overflow::Params]);
+}
+
+#[test]
+#[cfg(all(feature = "core", not(feature = "alloc")))]
+fn unbounded_osc_is_capped_with_core() {
+    let (input, expected) = gen_exceed_max_buffer_size();
+    let actual = overflows(Parser::default().max_osc_len(None), &input);
+    assert_eq!(actual.dispatcher, expected);
+    assert_eq!(actual.overflows, [Overflow::OscLen]);
+}
+
+#[test]
+#[cfg(all(feature = "core", feature = "alloc"))]
+fn unbounded_osc_with_core_and_alloc() {
+    let url = format!("https://example.com/{}", "a".repeat(MAX_OSC_RAW * 2));
+    let input = format!("\x1b]8;;{url}\x1b\\");
+    let actual = overflows(
+        Parser::default().max_osc_len(None).max_osc_params(None),
+        input.as_bytes(),
+    );
+    assert_eq!(
+        actual.dispatcher,
+        start() + Sequence::Osc(vec![b"8".to_vec(), vec![], url.into_bytes()], false)
+    );
+    assert_eq!(actual.overflows, []);
+}
+
+#[derive(Default, PartialEq, Eq, Debug)]
+struct Printed(Vec<String>);
+
+impl Perform for Printed {
+    fn print(&mut self, c: char) {
+        self.0.push(c.to_string());
+    }
+
+    fn print_str(&mut self, s: &str) {
+        self.0.push(s.to_owned());
+    }
+}
+
+#[test]
+fn advance_bytes_batches_printable() {
+    let mut printed = Printed::default();
+    let mut parser = Parser::<DefaultCharAccumulator>::new();
+
+    parser.advance_bytes(
+        &mut printed,
+        b"Hello\x1b[1m world!\nThis is a long line of text",
+    );
+
+    assert_eq!(
+        printed.0,
+        ["Hello", " world!", "This is a long line of text"]
+    );
+}
+
+#[test]
+#[cfg(feature = "utf8")]
+fn advance_bytes_split_utf8() {
+    let input = "été 😀 fin".as_bytes();
+    for split in 0..input.len() {
+        let mut printed = Printed::default();
+        let mut parser = Parser::<Utf8Parser>::new();
+
+        let (left, right) = input.split_at(split);
+        parser.advance_bytes(&mut printed, left);
+        parser.advance_bytes(&mut printed, right);
+
+        assert_eq!(printed.0.concat(), "été 😀 fin", "split at {split}");
+    }
+}
+
+#[test]
+#[cfg(feature = "utf8")]
+fn advance_bytes_invalid_utf8() {
+    let input = b"ok\xffok\xe2\x82ok";
+
+    let mut expected = Printed::default();
+    let mut parser = Parser::<Utf8Parser>::new();
+    for byte in input {
+        parser.advance(&mut expected, *byte);
+    }
+
+    let mut actual = Printed::default();
+    let mut parser = Parser::<Utf8Parser>::new();
+    parser.advance_bytes(&mut actual, input);
+
+    assert_eq!(actual.0.concat(), expected.0.concat());
+    assert_eq!(actual.0, ["ok", "ok", "�", "k"]);
+}
+
+proptest! {
+    #[test]
+    #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+    fn advance_bytes_matches_advance(input in proptest::collection::vec(any::<u8>(), 0..256), split in any::<prop::sample::Index>()) {
+        let input = if DefaultCharAccumulator::UTF8 {
+            input
+        } else {
+            input.into_iter().filter(u8::is_ascii).collect()
+        };
+
+        let mut expected = Dispatcher::default();
+        let mut parser = Parser::<DefaultCharAccumulator>::new();
+        for byte in &input {
+            parser.advance(&mut expected, *byte);
+        }
+
+        let mut actual = Dispatcher::default();
+        let mut parser = Parser::<DefaultCharAccumulator>::new();
+        let (left, right) = input.split_at(split.index(input.len() + 1));
+        parser.advance_bytes(&mut actual, left);
+        parser.advance_bytes(&mut actual, right);
+
+        prop_assert_eq!(expected, actual);
+    }
+}
+
+#[cfg(any(not(feature = "core"), feature = "anstyle"))]
+fn collect_events(chunks: &[&[u8]]) -> Dispatcher {
+    let mut events = Events::<DefaultCharAccumulator>::new();
+    let mut dispatcher = Dispatcher::default();
+    for chunk in chunks {
+        for event in events.parse_next(chunk) {
+            let sequence = match event {
+                Event::Print(text) => {
+                    dispatcher = dispatcher + text.as_ref();
+                    continue;
+                }
+                Event::Execute(_) | Event::Overflow(_) => continue,
+                Event::Csi {
+                    params,
+                    intermediates,
+                    ignore,
+                    action,
+                } => Sequence::Csi(
+                    params.iter().map(|p| p.to_vec()).collect(),
+                    intermediates.to_vec(),
+                    ignore,
+                    action,
+                ),
+                Event::Esc {
+                    intermediates,
+                    ignore,
+                    byte,
+                } => Sequence::Esc(intermediates.to_vec(), ignore, byte),
+                Event::Osc {
+                    params,
+                    bell_terminated,
+                } => Sequence::Osc(params, bell_terminated),
+                Event::DcsHook {
+                    params,
+                    intermediates,
+                    ignore,
+                    action,
+                } => Sequence::DcsHook(
+                    params.iter().map(|p| p.to_vec()).collect(),
+                    intermediates.to_vec(),
+                    ignore,
+                    action,
+                ),
+                Event::DcsPut(byte) => Sequence::DcsPut(byte),
+                Event::DcsUnhook => Sequence::DcsUnhook,
+                Event::SosPmApcStart(kind) => Sequence::SosPmApcStart(kind),
+                Event::SosPmApcPut(byte) => Sequence::SosPmApcPut(byte),
+                Event::SosPmApcEnd => Sequence::SosPmApcEnd,
+                _ => unreachable!(),
+            };
+            dispatcher = dispatcher + sequence;
+        }
+    }
+    dispatcher
+}
+
+#[test]
+#[cfg(any(not(feature = "core"), feature = "anstyle"))]
+fn events_borrow_printable() {
+    let mut events = Events::<DefaultCharAccumulator>::new();
+    let actual = events
+        .parse_next(b"Hello\x1b[1m world!\r\n")
+        .collect::<Vec<_>>();
+    assert_eq!(actual.len(), 5, "{actual:?}");
+    assert!(matches!(
+        &actual[0],
+        Event::Print(std::borrow::Cow::Borrowed("Hello"))
+    ));
+    assert!(
+        matches!(&actual[1], Event::Csi { params, intermediates, ignore: false, action: b'm' } if params.iter().eq([&[1][..]]) && intermediates.is_empty()),
+        "{:?}",
+        actual[1]
+    );
+    assert!(matches!(
+        &actual[2],
+        Event::Print(std::borrow::Cow::Borrowed(" world!"))
+    ));
+    assert_eq!(actual[3], Event::Execute(b'\r'));
+    assert_eq!(actual[4], Event::Execute(b'\n'));
+}
+
+#[test]
+#[cfg(any(not(feature = "core"), feature = "anstyle"))]
+fn events_multiple_per_byte() {
+    let actual = collect_events(&[b"\x1bPqab\x18"]);
+    let expected = start()
+        + Sequence::DcsHook(vec![vec![0]], vec![], false, b'q')
+        + Sequence::DcsPut(b'a')
+        + Sequence::DcsPut(b'b')
+        + Sequence::DcsUnhook;
+    assert_eq!(actual, expected);
+}
+
+proptest! {
+    #[test]
+    #[cfg(any(not(feature = "core"), feature = "anstyle"))]
+    #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+    fn events_match_perform(input in proptest::collection::vec(any::<u8>(), 0..256), split in any::<prop::sample::Index>()) {
+        let input = if DefaultCharAccumulator::UTF8 {
+            input
+        } else {
+            input.into_iter().filter(u8::is_ascii).collect()
+        };
+
+        let mut expected = Dispatcher::default();
+        let mut parser = Parser::<DefaultCharAccumulator>::new();
+        for byte in &input {
+            parser.advance(&mut expected, *byte);
+        }
+
+        let (left, right) = input.split_at(split.index(input.len() + 1));
+        let actual = collect_events(&[left, right]);
+
+        prop_assert_eq!(expected, actual);
+    }
+}
+
+fn encode(input: &[u8]) -> Vec<u8> {
+    let mut encoder = Encoder::new(Vec::new());
+    let mut parser = Parser::<DefaultCharAccumulator>::new();
+    parser.advance_bytes(&mut encoder, input);
+    encoder.into_inner()
+}
+
+#[test]
+fn encode_csi() {
+    assert_eq!(
+        encode(b"\x1b[0m\x1b[;1;38:2::255:0:0m"),
+        b"\x1b[m\x1b[0;1;38:2:0:255:0:0m"
+    );
+    assert_eq!(
+        encode(b"\x1b[?1049h\x1b[4 q\x1b[>4;2m"),
+        b"\x1b[?1049h\x1b[4 q\x1b[>4;2m"
+    );
+}
+
+#[test]
+fn encode_esc() {
+    assert_eq!(encode(b"\x1b(B\x1b7\x1b#8"), b"\x1b(B\x1b7\x1b#8");
+}
+
+#[test]
+fn encode_osc_terminator() {
+    assert_eq!(
+        encode(b"\x1b]8;;https://example.com\x07link\x1b]8;;\x1b\\"),
+        b"\x1b]8;;https://example.com\x07link\x1b]8;;\x1b\\"
+    );
+    assert_eq!(encode(b"\x1b]0;title\x18"), b"\x1b]0;title\x1b\\\x18");
+}
+
+#[test]
+fn encode_strings() {
+    assert_eq!(encode(b"\x1bP1$r0m\x1b\\"), b"\x1bP1$r0m\x1b\\");
+    assert_eq!(encode(b"\x1b_Gf=100;AAAA\x1b\\"), b"\x1b_Gf=100;AAAA\x1b\\");
+    assert_eq!(encode(b"\x1bX\x9c"), b"\x1bX\x1b\\");
+}
+
+#[test]
+fn encode_drops_ignored() {
+    assert_eq!(encode(b"a\x1b[1;2;3;4;5;6;7;8;9;10;11;12;13;14;15;16;17;18;19;20;21;22;23;24;25;26;27;28;29;30;31;32;33mb"), b"ab");
+    assert_eq!(encode(b"a\x1bP!!!qdata\x1b\\b"), b"ab");
+}
+
+#[test]
+#[cfg(any(not(feature = "core"), feature = "anstyle"))]
+fn encode_events() {
+    let input = b"\x1b[1;31mred\x1b]8;;url\x1b\\link\x1b]8;;\x07\x1b[m\r\n";
+    let mut encoder = Encoder::new(Vec::new());
+    let mut events = Events::<DefaultCharAccumulator>::new();
+    for event in events.parse_next(input) {
+        encoder.encode(&event);
+    }
+    assert_eq!(encoder.into_inner(), input);
+}
+
+/// Records what [`Encoder`] preserves, for comparing parses of its input and output
+#[derive(Default, PartialEq, Eq, Debug)]
+struct Encoded {
+    dispatched: Vec<Sequence>,
+    executed: Vec<u8>,
+    string_terminated: bool,
+    ignoring_dcs: bool,
+}
+
+impl Encoded {
+    fn push(&mut self, seq: Sequence) {
+        self.string_terminated = false;
+        self.dispatched.push(seq);
+    }
+}
+
+impl Perform for Encoded {
+    fn print(&mut self, c: char) {
+        self.push(Sequence::Print(c));
+    }
+
+    fn execute(&mut self, byte: u8) {
+        self.string_terminated = false;
+        self.executed.push(byte);
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        let params = params.iter().map(|p| p.to_vec()).collect::<Vec<_>>();
+        self.push(Sequence::Osc(params, bell_terminated));
+        self.string_terminated = !bell_terminated;
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: u8) {
+        self.string_terminated = false;
+        if !ignore {
+            let params = params.iter().map(|subparam| subparam.to_vec()).collect();
+            self.push(Sequence::Csi(params, intermediates.to_vec(), ignore, c));
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        if self.string_terminated && intermediates.is_empty() && byte == b'\\' {
+            self.string_terminated = false;
+        } else if !ignore {
+            self.push(Sequence::Esc(intermediates.to_vec(), ignore, byte));
+        }
+        self.string_terminated = false;
+    }
+
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: u8) {
+        self.string_terminated = false;
+        self.ignoring_dcs = ignore;
+        if !ignore {
+            let params = params.iter().map(|subparam| subparam.to_vec()).collect();
+            self.push(Sequence::DcsHook(params, intermediates.to_vec(), ignore, c));
+        }
+    }
+
+    fn put(&mut self, byte: u8) {
+        if !self.ignoring_dcs {
+            self.push(Sequence::DcsPut(byte));
+        }
+    }
+
+    fn unhook(&mut self) {
+        if !self.ignoring_dcs {
+            self.push(Sequence::DcsUnhook);
+        }
+        self.ignoring_dcs = false;
+        self.string_terminated = true;
+    }
+
+    fn sos_pm_apc_start(&mut self, kind: u8) {
+        self.push(Sequence::SosPmApcStart(kind));
+    }
+
+    fn sos_pm_apc_put(&mut self, byte: u8) {
+        self.push(Sequence::SosPmApcPut(byte));
+    }
+
+    fn sos_pm_apc_end(&mut self) {
+        self.push(Sequence::SosPmApcEnd);
+        self.string_terminated = true;
+    }
+}
+
+fn encoded(input: &[u8]) -> (Vec<Sequence>, Vec<u8>) {
+    let mut encoded = Encoded::default();
+    let mut parser = Parser::<DefaultCharAccumulator>::new();
+    for byte in input {
+        parser.advance(&mut encoded, *byte);
+    }
+    (encoded.dispatched, encoded.executed)
+}
+
+fn escape_sequences() -> impl Strategy<Value = Vec<u8>> {
+    let piece = prop_oneof![
+        "[a-z ]{1,4}",
+        "[\\x00-\\x1f]",
+        "\\x1b\\[[<=>?]?[0-9:;]{0,8}[ -/]{0,3}[@-~]",
+        "\\x1b\\][0-9a-z;]{0,8}(\\x07|\\x1b\\\\|\\x18|\\x1b\\[m)",
+        "\\x1bP[0-9;]{0,4}[ -/]{0,3}[@-~][a-z\\x00-\\x1f]{0,4}(\\x1b\\\\|\\x18)",
+        "\\x1b[X^_][a-z\\x00-\\x1f]{0,4}\\x1b\\\\",
+        "\\x1b[ -/]{0,3}[0-~]",
+    ];
+    proptest::collection::vec(piece, 0..16).prop_map(|pieces| pieces.concat().into_bytes())
+}
+
+proptest! {
+    #[test]
+    #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+    fn encode_round_trips(input in prop_oneof![proptest::collection::vec(any::<u8>(), 0..256), escape_sequences()]) {
+        let input = if DefaultCharAccumulator::UTF8 {
+            input
+        } else {
+            input.into_iter().filter(u8::is_ascii).collect()
+        };
+
+        let encoded_input = encode(&input);
+        prop_assert_eq!(encoded(&input), encoded(&encoded_input));
+        prop_assert_eq!(&encode(&encoded_input), &encoded_input);
+    }
+}
+
 proptest! {
     #[test]
     #[cfg(feature = "utf8")]
@@ -13,6 +13,7 @@ fn main() {
         anstyle_query::term_supports_color()
     );
     println!("truecolor: {}", anstyle_query::truecolor());
+    println!("color_level: {:?}", anstyle_query::color_level());
     println!(
         "enable_ansi_colors: {:?}",
         anstyle_query::windows::enable_ansi_colors()
@@ -22,5 +23,22 @@ fn main() {
         "  enable_virtual_terminal_processing: {:?}",
         anstyle_query::windows::enable_virtual_terminal_processing()
     );
+    println!(
+        "windows build_number: {:?}",
+        anstyle_query::windows::build_number()
+    );
     println!("is_ci: {:?}", anstyle_query::is_ci());
+    #[cfg(feature = "osc")]
+    {
+        let timeout = std::time::Duration::from_millis(100);
+        let background = anstyle_query::osc::background(timeout);
+        println!("background: {background:?}");
+        if let Ok(Some(background)) = background {
+            println!(
+                "  theme: {:?}",
+                anstyle_query::osc::Theme::from_background(background)
+            );
+        }
+        println!("foreground: {:?}", anstyle_query::osc::foreground(timeout));
+    }
 }
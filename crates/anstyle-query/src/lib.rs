@@ -5,6 +5,8 @@
 #![warn(clippy::print_stderr)]
 #![warn(clippy::print_stdout)]
 
+#[cfg(feature = "osc")]
+pub mod osc;
 pub mod windows;
 
 /// Check [CLICOLOR] status
@@ -118,6 +120,95 @@ pub fn truecolor() -> bool {
     value == "truecolor" || value == "24bit"
 }
 
+/// Check for 256-color support
+///
+/// Terminals supporting truecolor are assumed to also support 256 colors.
+/// See [`color_level`] for what is checked.
+#[inline]
+pub fn term_supports_256color() -> bool {
+    ColorLevel::Ansi256 <= color_level()
+}
+
+/// How many colors a terminal supports
+#[allow(clippy::exhaustive_enums)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorLevel {
+    /// No color support
+    None,
+    /// The 16 colors of `anstyle::AnsiColor`
+    Ansi16,
+    /// The 256 colors of `anstyle::Ansi256Color`
+    Ansi256,
+    /// 24-bit color, like `anstyle::RgbColor`
+    TrueColor,
+}
+
+/// Report how many colors the terminal supports
+///
+/// This combines:
+/// - `TERM`, including suffixes like `-256color` and `-direct`
+/// - [`COLORTERM`](truecolor)
+/// - `TERM_PROGRAM` for terminals known to not set `COLORTERM`
+/// - CI providers known to support truecolor, falling back to [`is_ci`]
+/// - The Windows build number, see [`windows::build_number`]
+///
+/// This does not check whether the program is piped or for user preferences like [`no_color`].
+pub fn color_level() -> ColorLevel {
+    color_level_from(|name| std::env::var_os(name), windows::build_number())
+}
+
+fn color_level_from(
+    var: impl Fn(&str) -> Option<std::ffi::OsString>,
+    windows_build: Option<u32>,
+) -> ColorLevel {
+    let term = var("TERM");
+    let term = term.as_deref().and_then(|t| t.to_str());
+    if term == Some("dumb") {
+        return ColorLevel::None;
+    }
+    let term = term.unwrap_or_default();
+
+    let colorterm = var("COLORTERM");
+    let colorterm = colorterm.as_deref().unwrap_or_default();
+    let term_program = var("TERM_PROGRAM");
+    let term_program = term_program.as_deref().unwrap_or_default();
+    if colorterm == "truecolor"
+        || colorterm == "24bit"
+        || term.ends_with("-direct")
+        || TRUECOLOR_TERM_PROGRAMS.iter().any(|p| term_program == *p)
+        || TRUECOLOR_CI.iter().any(|ci| var(ci).is_some())
+    {
+        return ColorLevel::TrueColor;
+    }
+
+    if term.ends_with("-256color") || term_program == "Apple_Terminal" {
+        return ColorLevel::Ansi256;
+    }
+
+    if let Some(build) = windows_build {
+        // Windows 10 added support for 256 colors in 1511 and truecolor in 1703 while older
+        // consoles are limited to 16 colors
+        return if 14931 <= build {
+            ColorLevel::TrueColor
+        } else if 10586 <= build {
+            ColorLevel::Ansi256
+        } else {
+            ColorLevel::Ansi16
+        };
+    }
+
+    let cygwin = cfg!(windows) && term == "cygwin";
+    if (!term.is_empty() && !cygwin) || var("CI").is_some() {
+        ColorLevel::Ansi16
+    } else {
+        ColorLevel::None
+    }
+}
+
+const TRUECOLOR_TERM_PROGRAMS: &[&str] = &["iTerm.app", "WezTerm", "vscode", "ghostty"];
+
+const TRUECOLOR_CI: &[&str] = &["GITHUB_ACTIONS", "GITEA_ACTIONS"];
+
 /// Report whether this is running in CI
 ///
 /// CI is a common environment where, despite being piped, ansi color codes are supported
@@ -134,6 +225,27 @@ pub fn is_ci() -> bool {
     std::env::var_os("CI").is_some()
 }
 
+/// Scale a 1-4 digit hex component of an X11 color specification to 8 bits
+///
+/// Terminals reply to color queries with specifications like `rgb:ffff/8080/0000`, where each
+/// component may have a different number of digits.
+///
+/// # Example
+///
+/// ```rust
+/// assert_eq!(anstyle_query::parse_x11_component("f"), Some(255));
+/// assert_eq!(anstyle_query::parse_x11_component("8080"), Some(128));
+/// assert_eq!(anstyle_query::parse_x11_component("12345"), None);
+/// ```
+pub fn parse_x11_component(component: &str) -> Option<u8> {
+    if component.is_empty() || 4 < component.len() {
+        return None;
+    }
+    let value = u32::from_str_radix(component, 16).ok()?;
+    let max = (1 << (4 * component.len())) - 1;
+    Some(((value * 255 + max / 2) / max) as u8)
+}
+
 fn non_empty(var: Option<&std::ffi::OsStr>) -> bool {
     !var.unwrap_or_default().is_empty()
 }
@@ -156,6 +268,101 @@ mod test {
     fn non_empty_texty() {
         assert!(non_empty(Some(std::ffi::OsStr::new("hello"))));
     }
+
+    fn color_level_with(vars: &[(&str, &str)], windows_build: Option<u32>) -> ColorLevel {
+        color_level_from(
+            |name| {
+                vars.iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, v)| std::ffi::OsString::from(v))
+            },
+            windows_build,
+        )
+    }
+
+    #[test]
+    fn color_level_term() {
+        assert_eq!(color_level_with(&[], None), ColorLevel::None);
+        assert_eq!(
+            color_level_with(&[("TERM", "dumb")], None),
+            ColorLevel::None
+        );
+        assert_eq!(
+            color_level_with(&[("TERM", "xterm")], None),
+            ColorLevel::Ansi16
+        );
+        assert_eq!(
+            color_level_with(&[("TERM", "xterm-256color")], None),
+            ColorLevel::Ansi256
+        );
+        assert_eq!(
+            color_level_with(&[("TERM", "xterm-direct")], None),
+            ColorLevel::TrueColor
+        );
+    }
+
+    #[test]
+    fn color_level_truecolor() {
+        assert_eq!(
+            color_level_with(&[("TERM", "xterm"), ("COLORTERM", "truecolor")], None),
+            ColorLevel::TrueColor
+        );
+        assert_eq!(
+            color_level_with(&[("TERM", "xterm"), ("COLORTERM", "24bit")], None),
+            ColorLevel::TrueColor
+        );
+        assert_eq!(
+            color_level_with(&[("TERM", "dumb"), ("COLORTERM", "truecolor")], None),
+            ColorLevel::None
+        );
+        assert_eq!(
+            color_level_with(
+                &[("TERM", "xterm-256color"), ("TERM_PROGRAM", "iTerm.app")],
+                None
+            ),
+            ColorLevel::TrueColor
+        );
+        assert_eq!(
+            color_level_with(
+                &[
+                    ("TERM", "xterm-256color"),
+                    ("TERM_PROGRAM", "Apple_Terminal")
+                ],
+                None
+            ),
+            ColorLevel::Ansi256
+        );
+    }
+
+    #[test]
+    fn color_level_ci() {
+        assert_eq!(
+            color_level_with(&[("CI", "true")], None),
+            ColorLevel::Ansi16
+        );
+        assert_eq!(
+            color_level_with(&[("CI", "true"), ("GITHUB_ACTIONS", "true")], None),
+            ColorLevel::TrueColor
+        );
+    }
+
+    #[test]
+    fn color_level_windows() {
+        assert_eq!(color_level_with(&[], Some(10240)), ColorLevel::Ansi16);
+        assert_eq!(color_level_with(&[], Some(10586)), ColorLevel::Ansi256);
+        assert_eq!(color_level_with(&[], Some(19045)), ColorLevel::TrueColor);
+        assert_eq!(
+            color_level_with(&[("TERM", "dumb")], Some(19045)),
+            ColorLevel::None
+        );
+    }
+
+    #[test]
+    fn color_level_order() {
+        assert!(ColorLevel::None < ColorLevel::Ansi16);
+        assert!(ColorLevel::Ansi16 < ColorLevel::Ansi256);
+        assert!(ColorLevel::Ansi256 < ColorLevel::TrueColor);
+    }
 }
 
 #[doc = include_str!("../README.md")]
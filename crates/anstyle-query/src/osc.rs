@@ -0,0 +1,513 @@
+//! Query the terminal's colors with [OSC 10, 11, and 4][xterm]
+//!
+//! Unlike the rest of this crate, these queries talk to the terminal: they write a request and
+//! wait for the reply.  Only call them when the user is at an interactive terminal and expect them
+//! to take up to the `timeout` when the terminal doesn't reply.
+//!
+//! A [Device Attributes][xterm] request is sent after the color queries.  As nearly all terminals
+//! answer that one, there is usually no need to wait for the full `timeout` when colors aren't
+//! supported.
+//!
+//! [xterm]: https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h3-Operating-System-Commands
+
+use std::time::Duration;
+use std::time::Instant;
+
+use anstyle::RgbColor;
+
+/// A color to query
+#[allow(clippy::exhaustive_enums)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TermColor {
+    /// Default foreground color (OSC 10)
+    Foreground,
+    /// Default background color (OSC 11)
+    Background,
+    /// Entry in the 256-color palette (OSC 4)
+    Palette(u8),
+}
+
+impl TermColor {
+    fn write_request(self, request: &mut Vec<u8>) {
+        use std::io::Write as _;
+
+        let _ = match self {
+            Self::Foreground => write!(request, "\x1b]10;?\x1b\\"),
+            Self::Background => write!(request, "\x1b]11;?\x1b\\"),
+            Self::Palette(index) => write!(request, "\x1b]4;{index};?\x1b\\"),
+        };
+    }
+
+    /// Strip this color's prefix from an OSC reply's body
+    fn strip_reply(self, body: &[u8]) -> Option<&[u8]> {
+        match self {
+            Self::Foreground => body.strip_prefix(b"10;"),
+            Self::Background => body.strip_prefix(b"11;"),
+            Self::Palette(index) => {
+                let body = body.strip_prefix(b"4;")?;
+                let (number, body) = split_once(body, b';')?;
+                let number = std::str::from_utf8(number).ok()?.parse::<u8>().ok()?;
+                (number == index).then_some(body)
+            }
+        }
+    }
+}
+
+/// Whether a theme is light or dark
+#[allow(clippy::exhaustive_enums)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Theme {
+    /// Dark text on a light background
+    Light,
+    /// Light text on a dark background
+    Dark,
+}
+
+impl Theme {
+    /// Classify a theme by its background color
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use anstyle_query::osc::Theme;
+    /// assert_eq!(Theme::from_background(anstyle::RgbColor(0xfd, 0xf6, 0xe3)), Theme::Light);
+    /// assert_eq!(Theme::from_background(anstyle::RgbColor(0x00, 0x2b, 0x36)), Theme::Dark);
+    /// ```
+    pub fn from_background(background: RgbColor) -> Self {
+        // Perceived brightness, see https://www.w3.org/TR/AERT/#color-contrast
+        let brightness = (background.r() as u32 * 299
+            + background.g() as u32 * 587
+            + background.b() as u32 * 114)
+            / 1000;
+        if brightness < 128 {
+            Self::Dark
+        } else {
+            Self::Light
+        }
+    }
+}
+
+/// Terminal connection to send queries over
+///
+/// Implement this to run queries against something other than the controlling terminal, like a
+/// pseudo-terminal in tests.
+pub trait Tty {
+    /// Write the entire request
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()>;
+
+    /// Read the reply, waiting at most `timeout`
+    ///
+    /// Returns `Ok(0)` when the `timeout` is reached.
+    fn read_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> std::io::Result<usize>;
+}
+
+impl<T: Tty + ?Sized> Tty for &mut T {
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        (**self).write_all(buf)
+    }
+
+    fn read_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> std::io::Result<usize> {
+        (**self).read_timeout(buf, timeout)
+    }
+}
+
+/// Query the terminal's default foreground color
+///
+/// Returns `Ok(None)` when the terminal doesn't reply.
+pub fn foreground(timeout: Duration) -> std::io::Result<Option<RgbColor>> {
+    query_one(TermColor::Foreground, timeout)
+}
+
+/// Query the terminal's default background color
+///
+/// Returns `Ok(None)` when the terminal doesn't reply.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use anstyle_query::osc::Theme;
+/// let background = anstyle_query::osc::background(std::time::Duration::from_millis(100));
+/// let theme = background
+///     .ok()
+///     .flatten()
+///     .map(Theme::from_background)
+///     .unwrap_or(Theme::Dark);
+/// ```
+pub fn background(timeout: Duration) -> std::io::Result<Option<RgbColor>> {
+    query_one(TermColor::Background, timeout)
+}
+
+/// Query an entry of the terminal's 256-color palette
+///
+/// Returns `Ok(None)` when the terminal doesn't reply.
+pub fn palette(index: u8, timeout: Duration) -> std::io::Result<Option<RgbColor>> {
+    query_one(TermColor::Palette(index), timeout)
+}
+
+fn query_one(color: TermColor, timeout: Duration) -> std::io::Result<Option<RgbColor>> {
+    let colors = query_terminal(&[color], timeout)?;
+    Ok(colors.into_iter().next().flatten())
+}
+
+/// Query several colors at once from the controlling terminal
+///
+/// See [`query`] for the result.
+pub fn query_terminal(
+    colors: &[TermColor],
+    timeout: Duration,
+) -> std::io::Result<Vec<Option<RgbColor>>> {
+    let tty = ControllingTty::open()?;
+    query(tty, colors, timeout)
+}
+
+/// Query several colors at once from `tty`
+///
+/// The result has an entry for each of `colors`, being `None` when the terminal didn't reply for
+/// it.  `timeout` applies to the overall query.
+pub fn query(
+    mut tty: impl Tty,
+    colors: &[TermColor],
+    timeout: Duration,
+) -> std::io::Result<Vec<Option<RgbColor>>> {
+    let mut request = Vec::new();
+    for color in colors {
+        color.write_request(&mut request);
+    }
+    request.extend_from_slice(DA1_REQUEST);
+    tty.write_all(&request)?;
+
+    let start = Instant::now();
+    let mut reply = Vec::new();
+    let mut buffer = [0; 256];
+    while let Some(remaining) = timeout.checked_sub(start.elapsed()) {
+        let read = tty.read_timeout(&mut buffer, remaining)?;
+        if read == 0 {
+            break;
+        }
+        reply.extend_from_slice(&buffer[..read]);
+        if has_da1_reply(&reply) {
+            break;
+        }
+    }
+
+    let bodies = osc_replies(&reply).collect::<Vec<_>>();
+    let colors = colors
+        .iter()
+        .map(|color| {
+            bodies
+                .iter()
+                .find_map(|body| color.strip_reply(body))
+                .and_then(parse_color)
+        })
+        .collect();
+    Ok(colors)
+}
+
+const DA1_REQUEST: &[u8] = b"\x1b[c";
+
+fn has_da1_reply(reply: &[u8]) -> bool {
+    // `CSI ? ... c`
+    let mut remaining = reply;
+    while let Some(start) = find(remaining, b"\x1b[?") {
+        let params = &remaining[start + 3..];
+        match params
+            .iter()
+            .position(|b| !(b.is_ascii_digit() || *b == b';'))
+        {
+            Some(end) if params[end] == b'c' => return true,
+            Some(_) => remaining = params,
+            None => return false,
+        }
+    }
+    false
+}
+
+/// Iterate over the body of each complete OSC in `reply`
+fn osc_replies(reply: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut remaining = reply;
+    std::iter::from_fn(move || {
+        let start = find(remaining, b"\x1b]")? + 2;
+        remaining = &remaining[start..];
+        let (end, terminator_len) = match (find(remaining, b"\x07"), find(remaining, b"\x1b\\")) {
+            (Some(bel), Some(st)) if bel < st => (bel, 1),
+            (_, Some(st)) => (st, 2),
+            (Some(bel), None) => (bel, 1),
+            (None, None) => return None,
+        };
+        let body = &remaining[..end];
+        remaining = &remaining[end + terminator_len..];
+        Some(body)
+    })
+}
+
+/// Parse an X11 color specification, like `rgb:ffff/8080/0000`
+fn parse_color(spec: &[u8]) -> Option<RgbColor> {
+    let spec = std::str::from_utf8(spec).ok()?;
+    let (scheme, components) = spec.split_once(':')?;
+    let mut components = components.split('/');
+    let r = crate::parse_x11_component(components.next()?)?;
+    let g = crate::parse_x11_component(components.next()?)?;
+    let b = crate::parse_x11_component(components.next()?)?;
+    match (scheme, components.next()) {
+        ("rgb", None) => {}
+        // Sent by rxvt-unicode; the alpha is irrelevant for us
+        ("rgba", Some(_)) => {}
+        _ => return None,
+    }
+    Some(RgbColor(r, g, b))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn split_once(bytes: &[u8], delimiter: u8) -> Option<(&[u8], &[u8])> {
+    let index = bytes.iter().position(|b| *b == delimiter)?;
+    Some((&bytes[..index], &bytes[index + 1..]))
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::os::unix::io::AsRawFd as _;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    /// The controlling terminal, in raw mode for as long as this is alive
+    pub(super) struct ControllingTty {
+        file: std::fs::File,
+        original: libc::termios,
+    }
+
+    impl ControllingTty {
+        pub(super) fn open() -> std::io::Result<Self> {
+            let file = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/tty")?;
+            let fd = file.as_raw_fd();
+            unsafe {
+                let mut original: libc::termios = std::mem::zeroed();
+                if libc::tcgetattr(fd, &mut original) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                let mut raw = original;
+                // Don't wait for a newline or show the reply to the user
+                raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+                if libc::tcsetattr(fd, libc::TCSANOW, &raw) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(Self { file, original })
+            }
+        }
+    }
+
+    impl super::Tty for ControllingTty {
+        fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+            use std::io::Write as _;
+
+            self.file.write_all(buf)?;
+            self.file.flush()
+        }
+
+        fn read_timeout(&mut self, buf: &mut [u8], timeout: Duration) -> std::io::Result<usize> {
+            use std::io::Read as _;
+
+            let deadline = Instant::now() + timeout;
+            loop {
+                let mut fd = libc::pollfd {
+                    fd: self.file.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let remaining = remaining.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+                let ready = unsafe { libc::poll(&mut fd, 1, remaining) };
+                let result = match ready {
+                    0 => return Ok(0),
+                    ready if ready < 0 => Err(std::io::Error::last_os_error()),
+                    _ => self.file.read(buf),
+                };
+                match result {
+                    // Interrupted by a signal, so wait out the rest of the timeout
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                    result => return result,
+                }
+            }
+        }
+    }
+
+    impl Drop for ControllingTty {
+        fn drop(&mut self) {
+            unsafe {
+                libc::tcsetattr(self.file.as_raw_fd(), libc::TCSANOW, &self.original);
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod unsupported {
+    use std::time::Duration;
+
+    pub(super) struct ControllingTty {
+        _private: (),
+    }
+
+    impl ControllingTty {
+        pub(super) fn open() -> std::io::Result<Self> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "querying terminal colors is only supported on unix",
+            ))
+        }
+    }
+
+    impl super::Tty for ControllingTty {
+        fn write_all(&mut self, _buf: &[u8]) -> std::io::Result<()> {
+            unreachable!("cannot be constructed")
+        }
+
+        fn read_timeout(&mut self, _buf: &mut [u8], _timeout: Duration) -> std::io::Result<usize> {
+            unreachable!("cannot be constructed")
+        }
+    }
+}
+
+#[cfg(unix)]
+use unix::ControllingTty;
+#[cfg(not(unix))]
+use unsupported::ControllingTty;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Pseudo-terminal stand-in that replies to each request
+    struct FakeTty {
+        reply: fn(&[u8]) -> Vec<u8>,
+        request: Vec<u8>,
+        pending: Vec<u8>,
+    }
+
+    impl FakeTty {
+        fn new(reply: fn(&[u8]) -> Vec<u8>) -> Self {
+            Self {
+                reply,
+                request: Vec::new(),
+                pending: Vec::new(),
+            }
+        }
+    }
+
+    impl Tty for FakeTty {
+        fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+            self.request.extend_from_slice(buf);
+            self.pending.extend((self.reply)(buf));
+            Ok(())
+        }
+
+        fn read_timeout(&mut self, buf: &mut [u8], _timeout: Duration) -> std::io::Result<usize> {
+            // Trickle out the reply to exercise partial reads
+            let len = buf.len().min(self.pending.len()).min(3);
+            buf[..len].copy_from_slice(&self.pending[..len]);
+            self.pending.drain(..len);
+            Ok(len)
+        }
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn query_xterm() {
+        let mut tty = FakeTty::new(|_| {
+            b"\x1b]10;rgb:ffff/ffff/ffff\x1b\\\x1b]11;rgb:0000/2b2b/3636\x07\x1b]4;1;rgb:cd/00/00\x1b\\\x1b[?64;1;2c"
+                .to_vec()
+        });
+        let colors = query(
+            &mut tty,
+            &[
+                TermColor::Foreground,
+                TermColor::Background,
+                TermColor::Palette(1),
+            ],
+            TIMEOUT,
+        )
+        .unwrap();
+        assert_eq!(
+            colors,
+            [
+                Some(RgbColor(0xff, 0xff, 0xff)),
+                Some(RgbColor(0x00, 0x2b, 0x36)),
+                Some(RgbColor(0xcd, 0x00, 0x00)),
+            ]
+        );
+        assert_eq!(
+            tty.request,
+            b"\x1b]10;?\x1b\\\x1b]11;?\x1b\\\x1b]4;1;?\x1b\\\x1b[c"
+        );
+    }
+
+    #[test]
+    fn query_unsupported() {
+        let mut tty = FakeTty::new(|_| b"\x1b[?1;2c".to_vec());
+        let colors = query(&mut tty, &[TermColor::Background], TIMEOUT).unwrap();
+        assert_eq!(colors, [None]);
+    }
+
+    #[test]
+    fn query_no_reply() {
+        let mut tty = FakeTty::new(|_| Vec::new());
+        let colors = query(&mut tty, &[TermColor::Background], TIMEOUT).unwrap();
+        assert_eq!(colors, [None]);
+    }
+
+    #[test]
+    fn query_mismatched_palette() {
+        let mut tty = FakeTty::new(|_| b"\x1b]4;2;rgb:00/cd/00\x1b\\\x1b[?1;2c".to_vec());
+        let colors = query(&mut tty, &[TermColor::Palette(1)], TIMEOUT).unwrap();
+        assert_eq!(colors, [None]);
+    }
+
+    #[test]
+    fn parse_color_scales() {
+        assert_eq!(parse_color(b"rgb:f/8/0"), Some(RgbColor(0xff, 0x88, 0x00)));
+        assert_eq!(
+            parse_color(b"rgb:ff/80/00"),
+            Some(RgbColor(0xff, 0x80, 0x00))
+        );
+        assert_eq!(
+            parse_color(b"rgb:fff/800/000"),
+            Some(RgbColor(0xff, 0x80, 0x00))
+        );
+        assert_eq!(
+            parse_color(b"rgb:ffff/8080/0000"),
+            Some(RgbColor(0xff, 0x80, 0x00))
+        );
+        assert_eq!(
+            parse_color(b"rgba:ffff/8080/0000/ffff"),
+            Some(RgbColor(0xff, 0x80, 0x00))
+        );
+    }
+
+    #[test]
+    fn parse_color_invalid() {
+        assert_eq!(parse_color(b""), None);
+        assert_eq!(parse_color(b"?"), None);
+        assert_eq!(parse_color(b"rgb:ff/ff"), None);
+        assert_eq!(parse_color(b"rgb:ff/ff/ff/ff"), None);
+        assert_eq!(parse_color(b"rgb:fffff/0/0"), None);
+        assert_eq!(parse_color(b"rgb:gg/0/0"), None);
+        assert_eq!(parse_color(b"hsv:0/0/0"), None);
+    }
+
+    #[test]
+    fn theme() {
+        assert_eq!(
+            Theme::from_background(RgbColor(0xff, 0xff, 0xff)),
+            Theme::Light
+        );
+        assert_eq!(Theme::from_background(RgbColor(0, 0, 0)), Theme::Dark);
+    }
+}
@@ -55,6 +55,20 @@ mod windows_console {
                 .unwrap_or(false),
         )
     }
+
+    pub(crate) fn build_number() -> Option<u32> {
+        use windows_sys::Win32::System::SystemInformation::OSVERSIONINFOW;
+
+        unsafe {
+            let mut info: OSVERSIONINFOW = std::mem::zeroed();
+            info.dwOSVersionInfoSize = std::mem::size_of::<OSVERSIONINFOW>() as u32;
+            // Unlike `GetVersionExW`, this isn't subject to application manifests
+            if windows_sys::Wdk::System::SystemServices::RtlGetVersion(&mut info) != 0 {
+                return None;
+            }
+            Some(info.dwBuildNumber)
+        }
+    }
 }
 
 #[cfg(not(windows))]
@@ -63,6 +77,11 @@ mod windows_console {
     pub(crate) fn enable_ansi_colors() -> Option<bool> {
         None
     }
+
+    #[inline]
+    pub(crate) fn build_number() -> Option<u32> {
+        None
+    }
 }
 
 /// Enable ANSI escape codes ([`ENABLE_VIRTUAL_TERMINAL_PROCESSING`](https://learn.microsoft.com/en-us/windows/console/console-virtual-terminal-sequences#output-sequences))
@@ -77,3 +96,10 @@ pub fn enable_ansi_colors() -> Option<bool> {
 pub fn enable_virtual_terminal_processing() -> std::io::Result<()> {
     windows_console::enable_virtual_terminal_processing()
 }
+
+/// Windows build number, like `19045` for Windows 10 22H2
+///
+/// For non-windows systems, returns `None`
+pub fn build_number() -> Option<u32> {
+    windows_console::build_number()
+}
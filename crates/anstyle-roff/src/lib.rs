@@ -10,8 +10,8 @@
 mod styled_str;
 use anstyle::{Ansi256Color, AnsiColor, Color, RgbColor, Style};
 use anstyle_lossy::palette::Palette;
+use anstyle_parse::sgr::StyledSpan;
 use roff::{bold, italic, Roff};
-use styled_str::StyledStr;
 
 /// Static Strings defining ROFF Control Requests
 mod control_requests {
@@ -41,28 +41,29 @@ pub fn to_roff(styled_text: &str) -> Roff {
     let mut previous_fg_color = None;
     let mut previous_bg_color = None;
     for styled in styled_str::styled_stream(styled_text) {
-        if previous_fg_color != styled.style.get_fg_color() {
+        let style = styled.style();
+        if previous_fg_color != style.get_fg_color() {
             add_color_to_roff(
                 &mut doc,
                 control_requests::FOREGROUND,
-                &styled.style.get_fg_color(),
+                &style.get_fg_color(),
             );
-            previous_fg_color = styled.style.get_fg_color();
+            previous_fg_color = style.get_fg_color();
         }
-        if previous_bg_color != styled.style.get_bg_color() {
+        if previous_bg_color != style.get_bg_color() {
             add_color_to_roff(
                 &mut doc,
                 control_requests::BACKGROUND,
-                &styled.style.get_bg_color(),
+                &style.get_bg_color(),
             );
-            previous_bg_color = styled.style.get_bg_color();
+            previous_bg_color = style.get_bg_color();
         }
         set_effects_and_text(&styled, &mut doc);
     }
     doc
 }
 
-fn set_effects_and_text(styled: &StyledStr<'_>, doc: &mut Roff) {
+fn set_effects_and_text(styled: &StyledSpan, doc: &mut Roff) {
     // Roff (the crate) only supports these inline commands
     //  - Bold
     //  - Italic
@@ -70,13 +71,17 @@ fn set_effects_and_text(styled: &StyledStr<'_>, doc: &mut Roff) {
     // If we want more support, or even support combined formats, we will need
     // to push improvements to roff upstream or implement a more thorough roff crate
     // perhaps by spinning off some of this code
-    let effects = styled.style.get_effects();
-    if effects.contains(anstyle::Effects::BOLD) | has_bright_fg(&styled.style) {
-        doc.text([bold(styled.text)]);
+    //
+    // Other effects, like underlines, strikethrough, overline, framed, encircled, and
+    // superscript / subscript, have no font in roff to switch to, so their text is roman
+    let style = styled.style();
+    let effects = style.get_effects();
+    if effects.contains(anstyle::Effects::BOLD) | has_bright_fg(&style) {
+        doc.text([bold(styled.text())]);
     } else if effects.contains(anstyle::Effects::ITALIC) {
-        doc.text([italic(styled.text)]);
+        doc.text([italic(styled.text())]);
     } else {
-        doc.text([roff::roman(styled.text)]);
+        doc.text([roff::roman(styled.text())]);
     }
 }
 
@@ -110,9 +115,6 @@ fn is_bright(fg_color: &Color) -> bool {
 fn add_color_to_roff(doc: &mut Roff, control_request: &str, color: &Option<Color>) {
     match color {
         Some(Color::Rgb(c)) => {
-            // Adding Support for RGB colors, however cansi does not support
-            // RGB Colors, so this is not executed. If we switch to a provider
-            // That has RGB support we will also get it for Roff
             let name = rgb_name(c);
             doc.control(
                 control_requests::CREATE_COLOR,
@@ -125,9 +127,7 @@ fn add_color_to_roff(doc: &mut Roff, control_request: &str, color: &Option<Color
             doc.control(control_request, [ansi_color_to_roff(c)]);
         }
         Some(Color::Ansi256(c)) => {
-            // Adding Support for Ansi256 colors, however cansi does not support
-            // Ansi256 Colors, so this is not executed. If we switch to a provider
-            // That has Xterm support we will also get it for Roff
+            // Roff has no indexed colors beyond the 8 named ones
             add_color_to_roff(doc, control_request, &Some(xterm_to_ansi_or_rgb(*c)));
         }
         None => {
@@ -180,6 +180,12 @@ mod tests {
         assert_eq!(to_hex(&RgbColor(0, 255, 0)).as_str(), "#00ff00");
         assert_eq!(to_hex(&RgbColor(0, 0, 255)).as_str(), "#0000ff");
     }
+
+    #[test]
+    fn unsupported_effects_are_roman() {
+        let text = "\x1b[53moverline\x1b[0m \x1b[51;73mframed superscript\x1b[0m";
+        assert_eq!(to_roff(text).to_roff(), "overline\n \nframed superscript\n");
+    }
 }
 
 #[doc = include_str!("../README.md")]
@@ -0,0 +1,30 @@
+//! Virtual terminal screen for rendering captured ANSI output
+//!
+//! Output meant for a terminal is more than styled text: progress bars rewrite a line with `\r`,
+//! spinners move the cursor up and erase what they printed.
+//! [`Screen`] replays all of that onto a grid of styled [`Cell`]s so you get what a user would
+//! have ended up seeing.
+//!
+//! # Example
+//!
+//! ```rust
+//! let mut screen = anstyle_screen::Screen::new(24, 80);
+//! screen.process(b"\x1b[1mCompiling\x1b[0m foo\n[=>  ]\r[===>]\n");
+//! assert_eq!(screen.render_plain(), "Compiling foo\n[===>]\n");
+//!
+//! // Pass the result on to a converter like `anstyle-svg`
+//! let ansi = screen.render_ansi();
+//! ```
+
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![warn(missing_docs)]
+#![warn(clippy::print_stderr)]
+#![warn(clippy::print_stdout)]
+
+mod line;
+mod screen;
+
+pub use line::Cell;
+pub use line::Line;
+pub use line::Span;
+pub use screen::Screen;
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use anstyle::Style;
+
+/// A single character position on the [`Screen`][crate::Screen]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    ch: char,
+    combining: Vec<char>,
+    width: u8,
+    style: Style,
+    url: Option<Arc<str>>,
+}
+
+impl Cell {
+    pub(crate) fn new(ch: char, width: u8, style: Style, url: Option<Arc<str>>) -> Self {
+        Self {
+            ch,
+            combining: Vec::new(),
+            width,
+            style,
+            url,
+        }
+    }
+
+    /// An empty cell, keeping only the background color of `style`
+    pub(crate) fn blank(style: Style) -> Self {
+        let style = Style::new().bg_color(style.get_bg_color());
+        Self::new(' ', 1, style, None)
+    }
+
+    /// The second half of a wide character
+    pub(crate) fn continuation(style: Style, url: Option<Arc<str>>) -> Self {
+        Self::new(' ', 0, style, url)
+    }
+
+    pub(crate) fn push_combining(&mut self, c: char) {
+        self.combining.push(c);
+    }
+
+    /// The base character
+    #[inline]
+    pub fn ch(&self) -> char {
+        self.ch
+    }
+
+    /// Zero-width characters, like combining accents, that follow [`Cell::ch`]
+    #[inline]
+    pub fn combining(&self) -> &[char] {
+        &self.combining
+    }
+
+    /// Number of columns taken up by this cell
+    ///
+    /// This is `2` for wide characters, with the column after it being `0`.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width as usize
+    }
+
+    /// Whether this is the second column of a wide character
+    #[inline]
+    pub fn is_continuation(&self) -> bool {
+        self.width == 0
+    }
+
+    /// The style this cell was written with
+    #[inline]
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// The OSC 8 hyperlink this cell was written with
+    #[inline]
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// Whether this cell would be indistinguishable from a never-written one
+    fn is_blank(&self) -> bool {
+        self.ch == ' '
+            && self.combining.is_empty()
+            && self.style == Style::new()
+            && self.url.is_none()
+    }
+
+    fn push_text(&self, text: &mut String) {
+        if self.is_continuation() {
+            return;
+        }
+        text.push(self.ch);
+        text.extend(self.combining.iter());
+    }
+}
+
+/// A row of [`Cell`]s on the [`Screen`][crate::Screen] or in its scrollback
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    cells: Vec<Cell>,
+    wrapped: bool,
+}
+
+impl Line {
+    pub(crate) fn new(cols: usize, style: Style) -> Self {
+        Self {
+            cells: vec![Cell::blank(style); cols],
+            wrapped: false,
+        }
+    }
+
+    pub(crate) fn cells_mut(&mut self) -> &mut Vec<Cell> {
+        &mut self.cells
+    }
+
+    pub(crate) fn set_wrapped(&mut self, yes: bool) {
+        self.wrapped = yes;
+    }
+
+    /// Every column of the line
+    #[inline]
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    /// Whether the text ran past the last column and continues on the next line
+    #[inline]
+    pub fn is_wrapped(&self) -> bool {
+        self.wrapped
+    }
+
+    /// The text of the line, without styling or trailing blanks
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for cell in self.trimmed() {
+            cell.push_text(&mut text);
+        }
+        text.truncate(text.trim_end().len());
+        text
+    }
+
+    /// Runs of text sharing the same [`Style`] and hyperlink, without trailing blanks
+    pub fn spans(&self) -> Vec<Span> {
+        let mut spans: Vec<Span> = Vec::new();
+        for cell in self.trimmed() {
+            if cell.is_continuation() {
+                continue;
+            }
+            match spans.last_mut() {
+                Some(span) if span.style == cell.style && span.url == cell.url => {
+                    cell.push_text(&mut span.text);
+                }
+                _ => {
+                    let mut text = String::new();
+                    cell.push_text(&mut text);
+                    spans.push(Span {
+                        text,
+                        style: cell.style,
+                        url: cell.url.clone(),
+                    });
+                }
+            }
+        }
+        spans
+    }
+
+    fn trimmed(&self) -> &[Cell] {
+        let end = self
+            .cells
+            .iter()
+            .rposition(|cell| !cell.is_blank())
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        &self.cells[..end]
+    }
+}
+
+/// Renders the line with SGR and OSC 8 escape codes, without a trailing newline
+impl std::fmt::Display for Line {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for span in self.spans() {
+            span.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A run of text from a [`Line`] sharing the same [`Style`] and hyperlink
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    text: String,
+    style: Style,
+    url: Option<Arc<str>>,
+}
+
+impl Span {
+    /// The text of the span
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The style of the span
+    #[inline]
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// The OSC 8 hyperlink URL of the span
+    #[inline]
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+}
+
+/// Renders the span with SGR and OSC 8 escape codes
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(url) = self.url() {
+            write!(f, "\x1B]8;;{url}\x1B\\")?;
+        }
+        let style = self.style;
+        write!(f, "{style}{}{style:#}", self.text)?;
+        if self.url.is_some() {
+            write!(f, "\x1B]8;;\x1B\\")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,979 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use anstyle::Style;
+use anstyle_parse::sgr::SgrState;
+use anstyle_parse::{Params, Parser, Perform};
+use unicode_width::UnicodeWidthChar as _;
+
+use crate::Cell;
+use crate::Line;
+
+const TAB_WIDTH: usize = 8;
+
+/// A grid of styled [`Cell`]s, updated by replaying escape codes
+///
+/// Feed bytes with [`Screen::process`] (or [`std::io::Write`]) or drive it from your own
+/// [`Parser`] as a [`Perform`].
+///
+/// Supported:
+/// - Printable text, including wide and combining characters, with deferred auto-wrap
+/// - C0 controls: `\b`, `\t`, `\n` (and `\v`, `\f`), `\r`
+/// - SGR and OSC 8 hyperlinks
+/// - Cursor movement: CUU, CUD, CUF, CUB, CNL, CPL, CHA, HPA, HPR, VPA, VPR, CUP, HVP, save and
+///   restore (DECSC, DECRC, SCOSC, SCORC)
+/// - Erasing: ED, EL, ECH, DCH, ICH, IL, DL, REP
+/// - Scrolling: scroll regions (DECSTBM), SU, SD, IND, NEL, RI
+/// - Tabs: HTS, TBC, CHT, CBT
+/// - Modes: auto-wrap (DECAWM) and the alternate screen (`?47`, `?1047`, `?1049`)
+/// - Reset: RIS, DECSTR
+///
+/// Everything else is ignored.
+///
+/// # Example
+///
+/// ```rust
+/// let mut screen = anstyle_screen::Screen::new(24, 80);
+/// screen.process(b"Downloading  10%\rDownloading 100%\n\x1b[32mDone\x1b[0m\n");
+/// assert_eq!(screen.render_plain(), "Downloading 100%\nDone\n");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Screen {
+    parser: Parser,
+    rows: usize,
+    cols: usize,
+    grid: Vec<Line>,
+    scrollback: VecDeque<Line>,
+    scrollback_limit: usize,
+    alternate: Option<Alternate>,
+    cursor: Cursor,
+    saved: Option<SavedCursor>,
+    sgr: SgrState,
+    url: Option<Arc<str>>,
+    scroll_top: usize,
+    scroll_bottom: usize,
+    tabs: Vec<bool>,
+    autowrap: bool,
+    onlcr: bool,
+    last_char: Option<char>,
+}
+
+impl Screen {
+    /// Blank screen of `rows` by `cols`
+    ///
+    /// Each dimension is at least `1`.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let rows = rows.max(1);
+        let cols = cols.max(1);
+        Self {
+            parser: Parser::default(),
+            rows,
+            cols,
+            grid: vec![Line::new(cols, Style::new()); rows],
+            scrollback: VecDeque::new(),
+            scrollback_limit: usize::MAX,
+            alternate: None,
+            cursor: Cursor::default(),
+            saved: None,
+            sgr: SgrState::new(),
+            url: None,
+            scroll_top: 0,
+            scroll_bottom: rows - 1,
+            tabs: default_tabs(cols),
+            autowrap: true,
+            onlcr: true,
+            last_char: None,
+        }
+    }
+
+    /// Maximum number of lines kept after scrolling off the top (default: unlimited)
+    pub fn scrollback_limit(mut self, lines: usize) -> Self {
+        self.scrollback_limit = lines;
+        self.trim_scrollback();
+        self
+    }
+
+    /// Treat `\n` as `\r\n`, like a TTY's `onlcr` output processing (default: `true`)
+    ///
+    /// Captured output is usually missing the `\r` that the TTY would have added.
+    pub fn onlcr(mut self, yes: bool) -> Self {
+        self.onlcr = yes;
+        self
+    }
+
+    /// Replay escape codes and text onto the screen
+    pub fn process(&mut self, bytes: &[u8]) {
+        let mut parser = std::mem::take(&mut self.parser);
+        parser.advance_bytes(self, bytes);
+        self.parser = parser;
+    }
+
+    /// Number of lines on the screen
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns on the screen
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Zero-based `(row, col)` of the cursor
+    #[inline]
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.cursor.row, self.cursor.col)
+    }
+
+    /// The lines currently on the screen
+    #[inline]
+    pub fn screen(&self) -> &[Line] {
+        &self.grid
+    }
+
+    /// The lines that scrolled off the top of the screen, oldest first
+    #[inline]
+    pub fn scrollback(&self) -> impl DoubleEndedIterator<Item = &Line> + ExactSizeIterator {
+        self.scrollback.iter()
+    }
+
+    /// The scrollback followed by the screen
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &Line> {
+        self.scrollback.iter().chain(self.grid.iter())
+    }
+
+    /// Render [`Screen::lines`] as plain text
+    ///
+    /// Trailing blank lines are dropped and every remaining line ends in a newline.
+    pub fn render_plain(&self) -> String {
+        self.render(|line, out| out.push_str(&line.text()))
+    }
+
+    /// Render [`Screen::lines`] with SGR and OSC 8 escape codes
+    ///
+    /// The result can be passed on to converters like `anstyle-svg` or `anstyle-roff`.
+    /// Trailing blank lines are dropped and every remaining line ends in a newline.
+    pub fn render_ansi(&self) -> String {
+        use std::fmt::Write as _;
+        self.render(|line, out| {
+            let _ = write!(out, "{line}");
+        })
+    }
+
+    fn render(&self, mut render_line: impl FnMut(&Line, &mut String)) -> String {
+        let mut out = String::new();
+        let mut pending_newlines = 0;
+        for line in self.lines() {
+            let start = out.len();
+            render_line(line, &mut out);
+            if out.len() == start {
+                pending_newlines += 1;
+            } else {
+                let rendered = out.split_off(start);
+                out.extend(std::iter::repeat('\n').take(pending_newlines));
+                out.push_str(&rendered);
+                out.push('\n');
+                pending_newlines = 0;
+            }
+        }
+        out
+    }
+
+    fn style(&self) -> Style {
+        self.sgr.style()
+    }
+
+    fn blank_line(&self) -> Line {
+        Line::new(self.cols, self.style())
+    }
+
+    fn trim_scrollback(&mut self) {
+        while self.scrollback_limit < self.scrollback.len() {
+            self.scrollback.pop_front();
+        }
+    }
+
+    fn put_char(&mut self, c: char) {
+        let width = c.width().unwrap_or(0);
+        if width == 0 {
+            self.put_combining(c);
+            return;
+        }
+        if width == 2 && self.cols < 2 {
+            return;
+        }
+
+        if self.cursor.pending_wrap {
+            self.grid[self.cursor.row].set_wrapped(true);
+            self.linefeed();
+            self.cursor.col = 0;
+        }
+        if self.cols < self.cursor.col + width {
+            if self.autowrap {
+                self.erase_cells(self.cursor.row, self.cursor.col, self.cols);
+                self.grid[self.cursor.row].set_wrapped(true);
+                self.linefeed();
+                self.cursor.col = 0;
+            } else {
+                self.cursor.col = self.cols - width;
+            }
+        }
+
+        let row = self.cursor.row;
+        let col = self.cursor.col;
+        self.split_wide(row, col);
+        self.split_wide(row, col + width - 1);
+        let style = self.style();
+        let cells = self.grid[row].cells_mut();
+        cells[col] = Cell::new(c, width as u8, style, self.url.clone());
+        if width == 2 {
+            cells[col + 1] = Cell::continuation(style, self.url.clone());
+        }
+        self.last_char = Some(c);
+
+        if self.cols <= col + width {
+            self.cursor.col = self.cols - 1;
+            self.cursor.pending_wrap = self.autowrap;
+        } else {
+            self.cursor.col = col + width;
+        }
+    }
+
+    fn put_combining(&mut self, c: char) {
+        let row = self.cursor.row;
+        let mut col = if self.cursor.pending_wrap {
+            self.cursor.col
+        } else if let Some(col) = self.cursor.col.checked_sub(1) {
+            col
+        } else {
+            return;
+        };
+        let cells = self.grid[row].cells_mut();
+        if cells[col].is_continuation() && 0 < col {
+            col -= 1;
+        }
+        cells[col].push_combining(c);
+    }
+
+    /// Blank out the other half of a wide character at `col`, before it gets overwritten
+    fn split_wide(&mut self, row: usize, col: usize) {
+        let style = self.style();
+        let cells = self.grid[row].cells_mut();
+        let Some(cell) = cells.get(col) else {
+            return;
+        };
+        if cell.is_continuation() {
+            if let Some(prev) = col.checked_sub(1) {
+                cells[prev] = Cell::blank(style);
+            }
+        } else if cell.width() == 2 {
+            if let Some(next) = cells.get_mut(col + 1) {
+                *next = Cell::blank(style);
+            }
+        }
+    }
+
+    /// Blank out `start..end` on `row`
+    fn erase_cells(&mut self, row: usize, start: usize, end: usize) {
+        let end = end.min(self.cols);
+        if end <= start {
+            return;
+        }
+        self.split_wide(row, start);
+        self.split_wide(row, end - 1);
+        let style = self.style();
+        for cell in &mut self.grid[row].cells_mut()[start..end] {
+            *cell = Cell::blank(style);
+        }
+    }
+
+    fn erase_lines(&mut self, start: usize, end: usize) {
+        for row in start..end.min(self.rows) {
+            self.grid[row] = self.blank_line();
+        }
+    }
+
+    fn linefeed(&mut self) {
+        self.cursor.pending_wrap = false;
+        if self.cursor.row == self.scroll_bottom {
+            self.scroll_up(1);
+        } else if self.cursor.row + 1 < self.rows {
+            self.cursor.row += 1;
+        }
+    }
+
+    fn reverse_index(&mut self) {
+        self.cursor.pending_wrap = false;
+        if self.cursor.row == self.scroll_top {
+            self.scroll_down(1);
+        } else {
+            self.cursor.row = self.cursor.row.saturating_sub(1);
+        }
+    }
+
+    /// Scroll the scroll region up, saving lines to the scrollback if it starts at the top
+    fn scroll_up(&mut self, count: usize) {
+        let count = count.min(self.scroll_bottom - self.scroll_top + 1);
+        for _ in 0..count {
+            let line = self.grid.remove(self.scroll_top);
+            if self.scroll_top == 0 && self.alternate.is_none() && self.scrollback_limit != 0 {
+                self.scrollback.push_back(line);
+            }
+            self.grid.insert(self.scroll_bottom, self.blank_line());
+        }
+        self.trim_scrollback();
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        let count = count.min(self.scroll_bottom - self.scroll_top + 1);
+        for _ in 0..count {
+            self.grid.remove(self.scroll_bottom);
+            self.grid.insert(self.scroll_top, self.blank_line());
+        }
+    }
+
+    fn insert_lines(&mut self, count: usize) {
+        if !self.in_scroll_region() {
+            return;
+        }
+        let count = count.min(self.scroll_bottom - self.cursor.row + 1);
+        for _ in 0..count {
+            self.grid.remove(self.scroll_bottom);
+            self.grid.insert(self.cursor.row, self.blank_line());
+        }
+        self.cursor.col = 0;
+        self.cursor.pending_wrap = false;
+    }
+
+    fn delete_lines(&mut self, count: usize) {
+        if !self.in_scroll_region() {
+            return;
+        }
+        let count = count.min(self.scroll_bottom - self.cursor.row + 1);
+        for _ in 0..count {
+            self.grid.remove(self.cursor.row);
+            self.grid.insert(self.scroll_bottom, self.blank_line());
+        }
+        self.cursor.col = 0;
+        self.cursor.pending_wrap = false;
+    }
+
+    fn insert_cells(&mut self, count: usize) {
+        let row = self.cursor.row;
+        let col = self.cursor.col;
+        let count = count.min(self.cols - col);
+        self.split_wide(row, col);
+        self.split_wide(row, self.cols - count);
+        let blank = Cell::blank(self.style());
+        let cells = self.grid[row].cells_mut();
+        cells.truncate(self.cols - count);
+        cells.splice(col..col, std::iter::repeat(blank).take(count));
+        self.cursor.pending_wrap = false;
+    }
+
+    fn delete_cells(&mut self, count: usize) {
+        let row = self.cursor.row;
+        let col = self.cursor.col;
+        let count = count.min(self.cols - col);
+        self.split_wide(row, col);
+        self.split_wide(row, col + count - 1);
+        let blank = Cell::blank(self.style());
+        let cells = self.grid[row].cells_mut();
+        cells.drain(col..col + count);
+        cells.extend(std::iter::repeat(blank).take(count));
+        self.cursor.pending_wrap = false;
+    }
+
+    fn in_scroll_region(&self) -> bool {
+        (self.scroll_top..=self.scroll_bottom).contains(&self.cursor.row)
+    }
+
+    fn move_to(&mut self, row: usize, col: usize) {
+        self.cursor.row = row.min(self.rows - 1);
+        self.cursor.col = col.min(self.cols - 1);
+        self.cursor.pending_wrap = false;
+    }
+
+    fn move_up(&mut self, count: usize) {
+        let top = if self.scroll_top <= self.cursor.row {
+            self.scroll_top
+        } else {
+            0
+        };
+        let row = self.cursor.row.saturating_sub(count).max(top);
+        self.move_to(row, self.cursor.col);
+    }
+
+    fn move_down(&mut self, count: usize) {
+        let bottom = if self.cursor.row <= self.scroll_bottom {
+            self.scroll_bottom
+        } else {
+            self.rows - 1
+        };
+        let row = self.cursor.row.saturating_add(count).min(bottom);
+        self.move_to(row, self.cursor.col);
+    }
+
+    fn tab_forward(&mut self, count: usize) {
+        let mut col = self.cursor.col;
+        for _ in 0..count {
+            col = (col + 1..self.cols)
+                .find(|col| self.tabs[*col])
+                .unwrap_or(self.cols - 1);
+        }
+        self.move_to(self.cursor.row, col);
+    }
+
+    fn tab_backward(&mut self, count: usize) {
+        let mut col = self.cursor.col;
+        for _ in 0..count {
+            col = (0..col).rev().find(|col| self.tabs[*col]).unwrap_or(0);
+        }
+        self.move_to(self.cursor.row, col);
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved = Some(SavedCursor {
+            cursor: self.cursor,
+            sgr: self.sgr.clone(),
+            url: self.url.clone(),
+        });
+    }
+
+    fn restore_cursor(&mut self) {
+        match self.saved.clone() {
+            Some(saved) => {
+                self.cursor = saved.cursor;
+                self.sgr = saved.sgr;
+                self.url = saved.url;
+            }
+            None => {
+                self.cursor = Cursor::default();
+                self.sgr.reset();
+                self.url = None;
+            }
+        }
+    }
+
+    fn set_scroll_region(&mut self, top: usize, bottom: usize) {
+        let bottom = bottom.min(self.rows - 1);
+        if top < bottom {
+            self.scroll_top = top;
+            self.scroll_bottom = bottom;
+            self.move_to(0, 0);
+        }
+    }
+
+    fn enter_alternate(&mut self, save_cursor: bool) {
+        if self.alternate.is_some() {
+            return;
+        }
+        let saved = save_cursor.then(|| SavedCursor {
+            cursor: self.cursor,
+            sgr: self.sgr.clone(),
+            url: self.url.clone(),
+        });
+        let blank = vec![Line::new(self.cols, Style::new()); self.rows];
+        let grid = std::mem::replace(&mut self.grid, blank);
+        self.alternate = Some(Alternate { grid, saved });
+    }
+
+    fn exit_alternate(&mut self) {
+        let Some(alternate) = self.alternate.take() else {
+            return;
+        };
+        self.grid = alternate.grid;
+        if let Some(saved) = alternate.saved {
+            self.cursor = saved.cursor;
+            self.sgr = saved.sgr;
+            self.url = saved.url;
+        }
+    }
+
+    fn set_private_mode(&mut self, params: &Params, enable: bool) {
+        for param in params.iter() {
+            match param.first().copied().unwrap_or(0) {
+                7 => {
+                    self.autowrap = enable;
+                    if !enable {
+                        self.cursor.pending_wrap = false;
+                    }
+                }
+                47 | 1047 if enable => self.enter_alternate(false),
+                1049 if enable => self.enter_alternate(true),
+                47 | 1047 | 1049 => self.exit_alternate(),
+                _ => {}
+            }
+        }
+    }
+
+    fn sync_url(&mut self) {
+        self.url = self.sgr.hyperlink().map(Arc::from);
+    }
+
+    fn full_reset(&mut self) {
+        let parser = std::mem::take(&mut self.parser);
+        let scrollback = std::mem::take(&mut self.scrollback);
+        *self = Self::new(self.rows, self.cols)
+            .scrollback_limit(self.scrollback_limit)
+            .onlcr(self.onlcr);
+        self.parser = parser;
+        self.scrollback = scrollback;
+    }
+}
+
+impl Perform for Screen {
+    fn print(&mut self, c: char) {
+        self.put_char(c);
+    }
+
+    fn execute(&mut self, byte: u8) {
+        match byte {
+            b'\x08' => {
+                let col = self.cursor.col.saturating_sub(1);
+                self.move_to(self.cursor.row, col);
+            }
+            b'\t' => self.tab_forward(1),
+            b'\n' | b'\x0B' | b'\x0C' => {
+                self.linefeed();
+                if self.onlcr {
+                    self.cursor.col = 0;
+                }
+            }
+            b'\r' => self.move_to(self.cursor.row, 0),
+            _ => {}
+        }
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        if self.sgr.osc_dispatch(params, bell_terminated) {
+            self.sync_url();
+        }
+    }
+
+    fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], ignore: bool, action: u8) {
+        if ignore {
+            return;
+        }
+
+        let args = params
+            .iter()
+            .map(|param| param.first().copied().unwrap_or(0) as usize)
+            .collect::<Vec<_>>();
+        let arg = |i: usize, default: usize| match args.get(i).copied() {
+            None | Some(0) => default,
+            Some(arg) => arg,
+        };
+
+        match (intermediates, action) {
+            ([], b'm') | ([b'!'], b'p') => {
+                if intermediates == b"!" {
+                    self.autowrap = true;
+                    self.scroll_top = 0;
+                    self.scroll_bottom = self.rows - 1;
+                }
+                self.sgr.csi_dispatch(params, intermediates, ignore, action);
+                self.sync_url();
+            }
+            ([b'?'], b'h') => self.set_private_mode(params, true),
+            ([b'?'], b'l') => self.set_private_mode(params, false),
+            ([], b'@') => self.insert_cells(arg(0, 1)),
+            ([], b'A') => self.move_up(arg(0, 1)),
+            ([], b'B') | ([], b'e') => self.move_down(arg(0, 1)),
+            ([], b'C') | ([], b'a') => {
+                let col = self.cursor.col.saturating_add(arg(0, 1));
+                self.move_to(self.cursor.row, col);
+            }
+            ([], b'D') => {
+                let col = self.cursor.col.saturating_sub(arg(0, 1));
+                self.move_to(self.cursor.row, col);
+            }
+            ([], b'E') => {
+                self.move_down(arg(0, 1));
+                self.cursor.col = 0;
+            }
+            ([], b'F') => {
+                self.move_up(arg(0, 1));
+                self.cursor.col = 0;
+            }
+            ([], b'G') | ([], b'`') => self.move_to(self.cursor.row, arg(0, 1) - 1),
+            ([], b'H') | ([], b'f') => self.move_to(arg(0, 1) - 1, arg(1, 1) - 1),
+            ([], b'd') => self.move_to(arg(0, 1) - 1, self.cursor.col),
+            ([], b'I') => self.tab_forward(arg(0, 1)),
+            ([], b'Z') => self.tab_backward(arg(0, 1)),
+            ([], b'J') => {
+                let row = self.cursor.row;
+                match arg(0, 0) {
+                    0 => {
+                        self.erase_cells(row, self.cursor.col, self.cols);
+                        self.erase_lines(row + 1, self.rows);
+                    }
+                    1 => {
+                        self.erase_lines(0, row);
+                        self.erase_cells(row, 0, self.cursor.col + 1);
+                    }
+                    2 => self.erase_lines(0, self.rows),
+                    3 => self.scrollback.clear(),
+                    _ => {}
+                }
+                self.cursor.pending_wrap = false;
+            }
+            ([], b'K') => {
+                let row = self.cursor.row;
+                match arg(0, 0) {
+                    0 => self.erase_cells(row, self.cursor.col, self.cols),
+                    1 => self.erase_cells(row, 0, self.cursor.col + 1),
+                    2 => self.erase_cells(row, 0, self.cols),
+                    _ => {}
+                }
+                self.cursor.pending_wrap = false;
+            }
+            ([], b'L') => self.insert_lines(arg(0, 1)),
+            ([], b'M') => self.delete_lines(arg(0, 1)),
+            ([], b'P') => self.delete_cells(arg(0, 1)),
+            ([], b'X') => {
+                let col = self.cursor.col;
+                self.erase_cells(self.cursor.row, col, col.saturating_add(arg(0, 1)));
+                self.cursor.pending_wrap = false;
+            }
+            ([], b'S') => self.scroll_up(arg(0, 1)),
+            // With more parameters, this is mouse tracking
+            ([], b'T') if args.len() <= 1 => self.scroll_down(arg(0, 1)),
+            ([], b'b') => {
+                if let Some(c) = self.last_char {
+                    let count = arg(0, 1).min(self.rows * self.cols);
+                    for _ in 0..count {
+                        self.put_char(c);
+                    }
+                }
+            }
+            ([], b'g') => match arg(0, 0) {
+                0 => self.tabs[self.cursor.col] = false,
+                3 => self.tabs.fill(false),
+                _ => {}
+            },
+            ([], b'r') => self.set_scroll_region(arg(0, 1) - 1, arg(1, self.rows) - 1),
+            ([], b's') => self.save_cursor(),
+            ([], b'u') => self.restore_cursor(),
+            _ => {}
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        if ignore || !intermediates.is_empty() {
+            return;
+        }
+
+        match byte {
+            b'7' => self.save_cursor(),
+            b'8' => self.restore_cursor(),
+            b'D' => self.linefeed(),
+            b'E' => {
+                self.linefeed();
+                self.cursor.col = 0;
+            }
+            b'H' => self.tabs[self.cursor.col] = true,
+            b'M' => self.reverse_index(),
+            b'c' => self.full_reset(),
+            _ => {}
+        }
+    }
+}
+
+impl std::io::Write for Screen {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.process(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+struct Cursor {
+    row: usize,
+    col: usize,
+    /// The last column was written and the next printable character goes on the next line
+    pending_wrap: bool,
+}
+
+#[derive(Clone, Debug)]
+struct SavedCursor {
+    cursor: Cursor,
+    sgr: SgrState,
+    url: Option<Arc<str>>,
+}
+
+/// The primary screen, while the alternate screen is active
+#[derive(Clone, Debug)]
+struct Alternate {
+    grid: Vec<Line>,
+    saved: Option<SavedCursor>,
+}
+
+fn default_tabs(cols: usize) -> Vec<bool> {
+    (0..cols)
+        .map(|col| col != 0 && col % TAB_WIDTH == 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[track_caller]
+    fn replay(rows: usize, cols: usize, input: &str) -> Screen {
+        let mut screen = Screen::new(rows, cols);
+        screen.process(input.as_bytes());
+        screen
+    }
+
+    #[track_caller]
+    fn text(screen: &Screen) -> Vec<String> {
+        screen.screen().iter().map(|line| line.text()).collect()
+    }
+
+    #[test]
+    fn carriage_return_overwrites() {
+        let screen = replay(3, 20, "[  0%]\r[ 50%]\r[100%]\ndone");
+        assert_eq!(text(&screen), ["[100%]", "done", ""]);
+        assert_eq!(screen.cursor(), (1, 4));
+    }
+
+    #[test]
+    fn without_onlcr() {
+        let mut screen = Screen::new(3, 10).onlcr(false);
+        screen.process(b"ab\ncd");
+        assert_eq!(text(&screen), ["ab", "  cd", ""]);
+    }
+
+    #[test]
+    fn cursor_up_and_erase_line() {
+        let input = "one\ntwo\nthree\n\x1b[2A\x1b[2Kfour";
+        let screen = replay(4, 10, input);
+        assert_eq!(text(&screen), ["one", "four", "three", ""]);
+    }
+
+    #[test]
+    fn erase_in_line() {
+        let screen = replay(1, 10, "abcdefgh\x1b[4G\x1b[K");
+        assert_eq!(text(&screen), ["abc"]);
+        let screen = replay(1, 10, "abcdefgh\x1b[4G\x1b[1K");
+        assert_eq!(text(&screen), ["    efgh"]);
+    }
+
+    #[test]
+    fn erase_in_display() {
+        let screen = replay(3, 5, "aaa\nbbb\nccc\x1b[2;2H\x1b[J");
+        assert_eq!(text(&screen), ["aaa", "b", ""]);
+        let screen = replay(3, 5, "aaa\nbbb\nccc\x1b[2;2H\x1b[1J");
+        assert_eq!(text(&screen), ["", "  b", "ccc"]);
+        let screen = replay(3, 5, "aaa\nbbb\nccc\x1b[2J");
+        assert_eq!(text(&screen), ["", "", ""]);
+    }
+
+    #[test]
+    fn cursor_position() {
+        let screen = replay(3, 10, "\x1b[2;3Hx\x1b[Hy\x1b[99;99Hz");
+        assert_eq!(text(&screen), ["y", "  x", "         z"]);
+    }
+
+    #[test]
+    fn autowrap() {
+        let screen = replay(3, 4, "abcdef");
+        assert_eq!(text(&screen), ["abcd", "ef", ""]);
+        assert!(screen.screen()[0].is_wrapped());
+        assert!(!screen.screen()[1].is_wrapped());
+    }
+
+    #[test]
+    fn deferred_wrap() {
+        let screen = replay(3, 4, "abcd\r\n");
+        assert_eq!(text(&screen), ["abcd", "", ""]);
+        assert_eq!(screen.cursor(), (1, 0));
+    }
+
+    #[test]
+    fn no_autowrap() {
+        let screen = replay(2, 4, "\x1b[?7labcdef");
+        assert_eq!(text(&screen), ["abcf", ""]);
+    }
+
+    #[test]
+    fn scrollback() {
+        let screen = replay(2, 10, "1\n2\n3\n4");
+        assert_eq!(text(&screen), ["3", "4"]);
+        let scrollback = screen.scrollback().map(|l| l.text()).collect::<Vec<_>>();
+        assert_eq!(scrollback, ["1", "2"]);
+        assert_eq!(screen.render_plain(), "1\n2\n3\n4\n");
+    }
+
+    #[test]
+    fn scrollback_limit() {
+        let mut screen = Screen::new(2, 10).scrollback_limit(1);
+        screen.process(b"1\n2\n3\n4");
+        let scrollback = screen.scrollback().map(|l| l.text()).collect::<Vec<_>>();
+        assert_eq!(scrollback, ["2"]);
+    }
+
+    #[test]
+    fn scroll_region() {
+        let input = "header\n1\n2\nfooter\x1b[2;3r\x1b[3;1H\n3\n4";
+        let screen = replay(4, 10, input);
+        assert_eq!(text(&screen), ["header", "3", "4", "footer"]);
+        assert_eq!(screen.scrollback().len(), 0);
+    }
+
+    #[test]
+    fn scroll_up_and_down() {
+        let screen = replay(3, 5, "a\nb\nc\x1b[S");
+        assert_eq!(text(&screen), ["b", "c", ""]);
+        let screen = replay(3, 5, "a\nb\nc\x1b[T");
+        assert_eq!(text(&screen), ["", "a", "b"]);
+        let screen = replay(3, 5, "a\nb\nc\x1b[H\x1bM");
+        assert_eq!(text(&screen), ["", "a", "b"]);
+    }
+
+    #[test]
+    fn insert_and_delete_lines() {
+        let screen = replay(3, 5, "a\nb\nc\x1b[2H\x1b[L");
+        assert_eq!(text(&screen), ["a", "", "b"]);
+        let screen = replay(3, 5, "a\nb\nc\x1b[1H\x1b[M");
+        assert_eq!(text(&screen), ["b", "c", ""]);
+    }
+
+    #[test]
+    fn insert_and_delete_cells() {
+        let screen = replay(1, 6, "abcdef\x1b[2G\x1b[2@");
+        assert_eq!(text(&screen), ["a  bcd"]);
+        let screen = replay(1, 6, "abcdef\x1b[2G\x1b[2P");
+        assert_eq!(text(&screen), ["adef"]);
+        let screen = replay(1, 6, "abcdef\x1b[2G\x1b[2X");
+        assert_eq!(text(&screen), ["a  def"]);
+    }
+
+    #[test]
+    fn repeat() {
+        let screen = replay(1, 10, "-\x1b[4b");
+        assert_eq!(text(&screen), ["-----"]);
+    }
+
+    #[test]
+    fn tabs() {
+        let screen = replay(1, 20, "a\tb\tc");
+        assert_eq!(text(&screen), ["a       b       c"]);
+        let screen = replay(1, 20, "\x1b[3g\x1b[4G\x1bH\ra\tb");
+        assert_eq!(text(&screen), ["a  b"]);
+        let screen = replay(1, 20, "\x1b[18Ga\x1b[Zb");
+        assert_eq!(text(&screen), ["                ba"]);
+    }
+
+    #[test]
+    fn wide_chars() {
+        let screen = replay(2, 5, "a日本");
+        assert_eq!(text(&screen), ["a日本", ""]);
+        assert!(screen.screen()[0].cells()[2].is_continuation());
+        let screen = replay(2, 5, "ab日本");
+        assert_eq!(text(&screen), ["ab日", "本"]);
+    }
+
+    #[test]
+    fn overwrite_wide_char() {
+        let screen = replay(1, 5, "日本\x1b[2Gx");
+        assert_eq!(text(&screen), [" x本"]);
+        let screen = replay(1, 5, "日本\x1b[3Gx");
+        assert_eq!(text(&screen), ["日x"]);
+    }
+
+    #[test]
+    fn combining_chars() {
+        let screen = replay(1, 5, "e\u{301}x");
+        assert_eq!(text(&screen), ["e\u{301}x"]);
+        assert_eq!(screen.cursor(), (0, 2));
+    }
+
+    #[test]
+    fn save_restore_cursor() {
+        let screen = replay(2, 10, "ab\x1b7\x1b[31mcd\x1b8ef");
+        assert_eq!(text(&screen), ["abef", ""]);
+        assert_eq!(screen.screen()[0].cells()[2].style(), Style::new());
+        let screen = replay(2, 10, "ab\x1b[s\ncd\x1b[uef");
+        assert_eq!(text(&screen), ["abef", "cd"]);
+    }
+
+    #[test]
+    fn alternate_screen() {
+        let screen = replay(2, 20, "before\x1b[?1049h\x1b[Htui\x1b[?1049lafter");
+        assert_eq!(text(&screen), ["beforeafter", ""]);
+    }
+
+    #[test]
+    fn styles() {
+        let red = anstyle::AnsiColor::Red.on_default();
+        let input = format!("a{red}bc{red:#}d");
+        let screen = replay(1, 10, &input);
+        let spans = screen.screen()[0].spans();
+        let spans = spans
+            .iter()
+            .map(|span| (span.text(), span.style()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            spans,
+            [("a", Style::new()), ("bc", red), ("d", Style::new())]
+        );
+        assert_eq!(screen.render_ansi(), format!("a{red}bc{red:#}d\n"));
+    }
+
+    #[test]
+    fn erase_keeps_background() {
+        let blue = anstyle::AnsiColor::Blue.on_default().bold();
+        let input = format!("{blue}\x1b[K");
+        let screen = replay(1, 3, &input);
+        let cell = &screen.screen()[0].cells()[2];
+        assert_eq!(cell.style(), Style::new().bg_color(blue.get_bg_color()));
+    }
+
+    #[test]
+    fn hyperlink() {
+        let input = "\x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\ text";
+        let screen = replay(1, 20, input);
+        let spans = screen.screen()[0].spans();
+        assert_eq!(spans[0].text(), "link");
+        assert_eq!(spans[0].url(), Some("https://example.com"));
+        assert_eq!(spans[1].url(), None);
+        assert_eq!(screen.render_ansi(), format!("{input}\n"));
+    }
+
+    #[test]
+    fn full_reset() {
+        let screen = replay(2, 10, "1\n2\n3\x1b[31m\x1bcx");
+        assert_eq!(text(&screen), ["x", ""]);
+        assert_eq!(screen.screen()[0].cells()[0].style(), Style::new());
+        assert_eq!(screen.scrollback().len(), 1);
+    }
+
+    #[test]
+    fn render_drops_trailing_blank_lines() {
+        let screen = replay(5, 10, "a\n\nb\n");
+        assert_eq!(screen.render_plain(), "a\n\nb\n");
+    }
+
+    proptest! {
+        #[test]
+        #[cfg_attr(miri, ignore)]  // See https://github.com/AltSysrq/proptest/issues/253
+        fn keeps_dimensions(rows in 1..10_usize, cols in 1..10_usize, input in "(\\PC|\x1b\\[[0-9;?]{0,6}[@-~]|\x1b[78DEHMc]|[\r\n\t\x08])*") {
+            let screen = replay(rows, cols, &input);
+            prop_assert_eq!(screen.screen().len(), rows);
+            for line in screen.screen() {
+                prop_assert_eq!(line.cells().len(), cols);
+            }
+            let (row, col) = screen.cursor();
+            prop_assert!(row < rows);
+            prop_assert!(col < cols);
+        }
+    }
+}
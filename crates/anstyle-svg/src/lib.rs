@@ -413,6 +413,38 @@ fn write_effects_in_use(buffer: &mut String, elements: &[adapter::Element]) {
         )
         .unwrap();
     }
+    if effects_in_use.contains(anstyle::Effects::OVERLINE) {
+        writeln!(
+            buffer,
+            r#"    .overline {{ text-decoration-line: overline; }}"#
+        )
+        .unwrap();
+    }
+    if effects_in_use.contains(anstyle::Effects::SUPERSCRIPT) {
+        writeln!(
+            buffer,
+            r#"    .superscript {{ baseline-shift: super; vertical-align: super; }}"#
+        )
+        .unwrap();
+    }
+    if effects_in_use.contains(anstyle::Effects::SUBSCRIPT) {
+        writeln!(
+            buffer,
+            r#"    .subscript {{ baseline-shift: sub; vertical-align: sub; }}"#
+        )
+        .unwrap();
+    }
+    // SVG text has no outline, so these only show in HTML
+    if effects_in_use.contains(anstyle::Effects::FRAMED) {
+        writeln!(buffer, r#"    .framed {{ outline: 1px solid; }}"#).unwrap();
+    }
+    if effects_in_use.contains(anstyle::Effects::ENCIRCLED) {
+        writeln!(
+            buffer,
+            r#"    .encircled {{ outline: 1px solid; border-radius: 0.5em; }}"#
+        )
+        .unwrap();
+    }
     if effects_in_use.contains(anstyle::Effects::DIMMED) {
         writeln!(buffer, r#"    .dimmed {{ opacity: 0.4; }}"#).unwrap();
     }
@@ -435,7 +467,14 @@ fn write_fg_span(buffer: &mut String, span: &str, element: &adapter::Element, fr
     let dotted_underline = effects.contains(anstyle::Effects::DOTTED_UNDERLINE);
     let dashed_underline = effects.contains(anstyle::Effects::DASHED_UNDERLINE);
     let strikethrough = effects.contains(anstyle::Effects::STRIKETHROUGH);
+    let overline = effects.contains(anstyle::Effects::OVERLINE);
+    let superscript = effects.contains(anstyle::Effects::SUPERSCRIPT);
+    let subscript = effects.contains(anstyle::Effects::SUBSCRIPT);
+    let framed = effects.contains(anstyle::Effects::FRAMED);
+    let encircled = effects.contains(anstyle::Effects::ENCIRCLED);
     // skipping INVERT as that was handled earlier
+    // skipping BLINK, RAPID_BLINK, and PROPORTIONAL_SPACING as a static, monospace rendering
+    // can't show them
     let bold = effects.contains(anstyle::Effects::BOLD);
     let italic = effects.contains(anstyle::Effects::ITALIC);
     let dimmed = effects.contains(anstyle::Effects::DIMMED);
@@ -467,6 +506,21 @@ fn write_fg_span(buffer: &mut String, span: &str, element: &adapter::Element, fr
     if strikethrough {
         classes.push("strikethrough");
     }
+    if overline {
+        classes.push("overline");
+    }
+    if superscript {
+        classes.push("superscript");
+    }
+    if subscript {
+        classes.push("subscript");
+    }
+    if framed {
+        classes.push("framed");
+    }
+    if encircled {
+        classes.push("encircled");
+    }
     if bold {
         classes.push("bold");
     }
@@ -42,6 +42,9 @@ pub fn to_yansi_style(style: anstyle::Style) -> yansi::Style {
     if effects.contains(anstyle::Effects::STRIKETHROUGH) {
         style = style.strike();
     }
+    if effects.contains(anstyle::Effects::RAPID_BLINK) {
+        style = style.rapid_blink();
+    }
     style
 }
 
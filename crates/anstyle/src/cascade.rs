@@ -0,0 +1,408 @@
+/// A color within a [`StyleLayer`]
+///
+/// Unlike the `Option<Color>` in [`Style`][crate::Style], this distinguishes between keeping the
+/// color from the layers below and resetting it to the terminal's default.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorLayer {
+    /// Keep the color from the layers below
+    #[default]
+    Inherit,
+    /// Reset to the terminal's default color
+    Default,
+    /// Use this color
+    Color(crate::Color),
+}
+
+impl ColorLayer {
+    /// Cascade `self` over the color from the layers below
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let red = anstyle::AnsiColor::Red.into();
+    /// let blue = anstyle::AnsiColor::Blue.into();
+    /// assert_eq!(anstyle::ColorLayer::Inherit.apply_to(Some(red)), Some(red));
+    /// assert_eq!(anstyle::ColorLayer::Default.apply_to(Some(red)), None);
+    /// assert_eq!(anstyle::ColorLayer::Color(blue).apply_to(Some(red)), Some(blue));
+    /// ```
+    #[inline]
+    pub const fn apply_to(self, base: Option<crate::Color>) -> Option<crate::Color> {
+        match self {
+            Self::Inherit => base,
+            Self::Default => None,
+            Self::Color(color) => Some(color),
+        }
+    }
+
+    /// Cascade `over` on top of `self`
+    #[inline]
+    pub const fn merge(self, over: Self) -> Self {
+        match over {
+            Self::Inherit => self,
+            Self::Default | Self::Color(_) => over,
+        }
+    }
+}
+
+impl From<crate::Color> for ColorLayer {
+    #[inline]
+    fn from(color: crate::Color) -> Self {
+        Self::Color(color)
+    }
+}
+
+/// A partial [`Style`][crate::Style] for cascading over other styles
+///
+/// Layers can explicitly reset colors and remove effects, making them suitable for themes
+/// built up from a base style with role and user overrides.
+///
+/// # Examples
+///
+/// ```rust
+/// use anstyle::AnsiColor;
+/// use anstyle::ColorLayer;
+/// use anstyle::Effects;
+/// use anstyle::StyleLayer;
+///
+/// let base = StyleLayer::new()
+///     .fg_color(ColorLayer::Color(AnsiColor::White.into()))
+///     .bg_color(ColorLayer::Color(AnsiColor::Black.into()));
+/// let role = StyleLayer::new()
+///     .fg_color(ColorLayer::Color(AnsiColor::Red.into()))
+///     .effects(Effects::BOLD | Effects::UNDERLINE);
+/// let user = StyleLayer::new()
+///     .bg_color(ColorLayer::Default)
+///     .remove_effects(Effects::UNDERLINE);
+///
+/// let style: anstyle::Style = [base, role, user].into_iter().collect();
+/// assert_eq!(style, AnsiColor::Red.on_default().bold());
+/// ```
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StyleLayer {
+    fg: ColorLayer,
+    bg: ColorLayer,
+    underline: ColorLayer,
+    add_effects: crate::Effects,
+    remove_effects: crate::Effects,
+}
+
+/// # Core
+impl StyleLayer {
+    /// Inherit everything
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let layer = anstyle::StyleLayer::new();
+    /// ```
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            fg: ColorLayer::Inherit,
+            bg: ColorLayer::Inherit,
+            underline: ColorLayer::Inherit,
+            add_effects: crate::Effects::new(),
+            remove_effects: crate::Effects::new(),
+        }
+    }
+
+    /// Set foreground color
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let layer = anstyle::StyleLayer::new().fg_color(anstyle::ColorLayer::Default);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn fg_color(mut self, fg: ColorLayer) -> Self {
+        self.fg = fg;
+        self
+    }
+
+    /// Set background color
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let layer = anstyle::StyleLayer::new().bg_color(anstyle::ColorLayer::Default);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn bg_color(mut self, bg: ColorLayer) -> Self {
+        self.bg = bg;
+        self
+    }
+
+    /// Set underline color
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let layer = anstyle::StyleLayer::new().underline_color(anstyle::ColorLayer::Default);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn underline_color(mut self, underline: ColorLayer) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    /// Add text effects, overriding any earlier [`StyleLayer::remove_effects`] of them
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let layer = anstyle::StyleLayer::new().effects(anstyle::Effects::BOLD);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn effects(mut self, effects: crate::Effects) -> Self {
+        self.add_effects = self.add_effects.insert(effects);
+        self.remove_effects = self.remove_effects.remove(effects);
+        self
+    }
+
+    /// Remove text effects, overriding any earlier [`StyleLayer::effects`] of them
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let layer = anstyle::StyleLayer::new().remove_effects(anstyle::Effects::BOLD);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn remove_effects(mut self, effects: crate::Effects) -> Self {
+        self.remove_effects = self.remove_effects.insert(effects);
+        self.add_effects = self.add_effects.remove(effects);
+        self
+    }
+
+    /// Cascade `over` on top of `self`, combining them into one layer
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let base = anstyle::StyleLayer::new().effects(anstyle::Effects::BOLD);
+    /// let over = anstyle::StyleLayer::new().remove_effects(anstyle::Effects::BOLD);
+    /// assert_eq!(base.merge(over), over);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn merge(self, over: Self) -> Self {
+        Self {
+            fg: self.fg.merge(over.fg),
+            bg: self.bg.merge(over.bg),
+            underline: self.underline.merge(over.underline),
+            add_effects: self
+                .add_effects
+                .remove(over.remove_effects)
+                .insert(over.add_effects),
+            remove_effects: self
+                .remove_effects
+                .remove(over.add_effects)
+                .insert(over.remove_effects),
+        }
+    }
+
+    /// Cascade `self` over a concrete `Style`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let base = anstyle::AnsiColor::Red.on_default().bold();
+    /// let layer = anstyle::StyleLayer::new()
+    ///     .fg_color(anstyle::ColorLayer::Default)
+    ///     .effects(anstyle::Effects::ITALIC);
+    /// assert_eq!(layer.apply_to(base), anstyle::Style::new().bold().italic());
+    /// ```
+    #[inline]
+    pub const fn apply_to(self, base: crate::Style) -> crate::Style {
+        base.fg_color(self.fg.apply_to(base.get_fg_color()))
+            .bg_color(self.bg.apply_to(base.get_bg_color()))
+            .underline_color(self.underline.apply_to(base.get_underline_color()))
+            .effects(
+                base.get_effects()
+                    .remove(self.remove_effects)
+                    .insert(self.add_effects),
+            )
+    }
+
+    /// Flatten into a concrete `Style`, using the terminal's defaults for anything inherited
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let layer = anstyle::StyleLayer::new().effects(anstyle::Effects::BOLD);
+    /// assert_eq!(layer.resolve(), anstyle::Style::new().bold());
+    /// ```
+    #[inline]
+    pub const fn resolve(self) -> crate::Style {
+        self.apply_to(crate::Style::new())
+    }
+}
+
+/// # Reflection
+impl StyleLayer {
+    #[inline]
+    #[allow(missing_docs)]
+    pub const fn get_fg_color(self) -> ColorLayer {
+        self.fg
+    }
+
+    #[inline]
+    #[allow(missing_docs)]
+    pub const fn get_bg_color(self) -> ColorLayer {
+        self.bg
+    }
+
+    #[inline]
+    #[allow(missing_docs)]
+    pub const fn get_underline_color(self) -> ColorLayer {
+        self.underline
+    }
+
+    /// Effects this layer adds
+    #[inline]
+    pub const fn get_effects(self) -> crate::Effects {
+        self.add_effects
+    }
+
+    /// Effects this layer removes
+    #[inline]
+    pub const fn get_removed_effects(self) -> crate::Effects {
+        self.remove_effects
+    }
+
+    /// Check if everything is inherited
+    #[inline]
+    pub const fn is_inherit(self) -> bool {
+        matches!(self.fg, ColorLayer::Inherit)
+            && matches!(self.bg, ColorLayer::Inherit)
+            && matches!(self.underline, ColorLayer::Inherit)
+            && self.add_effects.is_plain()
+            && self.remove_effects.is_plain()
+    }
+}
+
+/// Override only what `Style` sets, inheriting unset colors and all other effects
+///
+/// # Examples
+///
+/// ```rust
+/// let layer: anstyle::StyleLayer = anstyle::Style::new().bold().into();
+/// assert_eq!(layer.get_fg_color(), anstyle::ColorLayer::Inherit);
+/// ```
+impl From<crate::Style> for StyleLayer {
+    #[inline]
+    fn from(style: crate::Style) -> Self {
+        let color = |color: Option<crate::Color>| color.map(Into::into).unwrap_or_default();
+        Self::new()
+            .fg_color(color(style.get_fg_color()))
+            .bg_color(color(style.get_bg_color()))
+            .underline_color(color(style.get_underline_color()))
+            .effects(style.get_effects())
+    }
+}
+
+/// Merge a stack of layers, from the bottom up
+impl FromIterator<Self> for StyleLayer {
+    fn from_iter<I: IntoIterator<Item = Self>>(iter: I) -> Self {
+        iter.into_iter().fold(Self::new(), Self::merge)
+    }
+}
+
+/// Resolve a stack of layers, from the bottom up
+impl FromIterator<StyleLayer> for crate::Style {
+    fn from_iter<I: IntoIterator<Item = StyleLayer>>(iter: I) -> Self {
+        iter.into_iter().collect::<StyleLayer>().resolve()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::AnsiColor;
+    use crate::Effects;
+    use crate::Style;
+
+    #[test]
+    fn merge_matches_apply_in_order() {
+        let red = ColorLayer::Color(AnsiColor::Red.into());
+        let blue = ColorLayer::Color(AnsiColor::Blue.into());
+        let layers = [
+            StyleLayer::new(),
+            StyleLayer::new().fg_color(red).effects(Effects::BOLD),
+            StyleLayer::new()
+                .fg_color(ColorLayer::Default)
+                .bg_color(blue)
+                .remove_effects(Effects::BOLD | Effects::ITALIC),
+            StyleLayer::new()
+                .underline_color(red)
+                .effects(Effects::ITALIC | Effects::UNDERLINE),
+            StyleLayer::new()
+                .bg_color(ColorLayer::Default)
+                .remove_effects(Effects::UNDERLINE)
+                .effects(Effects::BOLD),
+        ];
+        let bases = [
+            Style::new(),
+            AnsiColor::Green.on(AnsiColor::Yellow).italic().underline(),
+        ];
+        for base in bases {
+            for a in layers {
+                for b in layers {
+                    for c in layers {
+                        let expected = c.apply_to(b.apply_to(a.apply_to(base)));
+                        assert_eq!(
+                            [a, b, c].into_iter().collect::<StyleLayer>().apply_to(base),
+                            expected,
+                            "{base:?} <- {a:?} <- {b:?} <- {c:?}"
+                        );
+                        assert_eq!(a.merge(b).merge(c), a.merge(b.merge(c)));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inherit_vs_default() {
+        let base = AnsiColor::Red.on(AnsiColor::Blue).underline();
+
+        let inherit = StyleLayer::new();
+        assert!(inherit.is_inherit());
+        assert_eq!(inherit.apply_to(base), base);
+
+        let default = StyleLayer::new()
+            .fg_color(ColorLayer::Default)
+            .bg_color(ColorLayer::Default);
+        assert!(!default.is_inherit());
+        assert_eq!(default.apply_to(base), Style::new().underline());
+    }
+
+    #[test]
+    fn later_effect_wins_within_layer() {
+        let layer = StyleLayer::new()
+            .effects(Effects::BOLD)
+            .remove_effects(Effects::BOLD);
+        assert_eq!(layer.get_effects(), Effects::new());
+        assert_eq!(layer.get_removed_effects(), Effects::BOLD);
+        assert_eq!(layer.apply_to(Style::new().bold()), Style::new());
+
+        let layer = layer.effects(Effects::BOLD);
+        assert_eq!(layer.get_removed_effects(), Effects::new());
+        assert_eq!(layer.apply_to(Style::new()), Style::new().bold());
+    }
+
+    #[test]
+    fn from_style() {
+        let style = AnsiColor::Red.on_default().bold();
+        let layer = StyleLayer::from(style);
+        assert_eq!(layer.resolve(), style);
+        assert_eq!(
+            layer.apply_to(Style::new().bg_color(Some(AnsiColor::Blue.into())).italic()),
+            AnsiColor::Red.on(AnsiColor::Blue).bold().italic()
+        );
+    }
+}
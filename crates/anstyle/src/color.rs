@@ -92,6 +92,42 @@ impl Color {
         };
         buffer.write_to(write)
     }
+
+    /// Write the SGR parameters for a foreground color, like `38;5;208`
+    #[inline]
+    pub(crate) fn write_fg_params(self, write: &mut dyn core::fmt::Write) -> core::fmt::Result {
+        let buffer = match self {
+            Self::Ansi(color) => color.as_fg_buffer(),
+            Self::Ansi256(color) => color.as_fg_buffer(),
+            Self::Rgb(color) => color.as_fg_buffer(),
+        };
+        write.write_str(buffer.as_params())
+    }
+
+    /// Write the SGR parameters for a background color, like `48;5;208`
+    #[inline]
+    pub(crate) fn write_bg_params(self, write: &mut dyn core::fmt::Write) -> core::fmt::Result {
+        let buffer = match self {
+            Self::Ansi(color) => color.as_bg_buffer(),
+            Self::Ansi256(color) => color.as_bg_buffer(),
+            Self::Rgb(color) => color.as_bg_buffer(),
+        };
+        write.write_str(buffer.as_params())
+    }
+
+    /// Write the SGR parameters for an underline color, like `58;5;208`
+    #[inline]
+    pub(crate) fn write_underline_params(
+        self,
+        write: &mut dyn core::fmt::Write,
+    ) -> core::fmt::Result {
+        let buffer = match self {
+            Self::Ansi(color) => color.as_underline_buffer(),
+            Self::Ansi256(color) => color.as_underline_buffer(),
+            Self::Rgb(color) => color.as_underline_buffer(),
+        };
+        write.write_str(buffer.as_params())
+    }
 }
 
 impl From<AnsiColor> for Color {
@@ -617,6 +653,13 @@ impl DisplayBuffer {
         }
     }
 
+    /// The SGR parameters, without the surrounding escape
+    #[inline]
+    fn as_params(&self) -> &str {
+        let escape = self.as_str();
+        &escape[2..escape.len() - 1]
+    }
+
     #[inline]
     #[cfg(feature = "std")]
     fn write_to(self, write: &mut dyn std::io::Write) -> std::io::Result<()> {
@@ -0,0 +1,365 @@
+use crate::RgbColor;
+
+/// # Manipulation
+impl RgbColor {
+    /// Mix `other` over `self`, where an `alpha` of `0` is all `self` and `255` is all `other`
+    ///
+    /// Blending is done on the sRGB components, like most terminals and browsers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let black = anstyle::RgbColor(0, 0, 0);
+    /// let white = anstyle::RgbColor(255, 255, 255);
+    /// assert_eq!(black.blend(white, 0), black);
+    /// assert_eq!(black.blend(white, 128), anstyle::RgbColor(128, 128, 128));
+    /// assert_eq!(black.blend(white, 255), white);
+    /// ```
+    #[inline]
+    pub const fn blend(self, other: Self, alpha: u8) -> Self {
+        const fn mix(base: u8, over: u8, alpha: u8) -> u8 {
+            let alpha = alpha as u32;
+            ((base as u32 * (255 - alpha) + over as u32 * alpha + 127) / 255) as u8
+        }
+        Self(
+            mix(self.r(), other.r(), alpha),
+            mix(self.g(), other.g(), alpha),
+            mix(self.b(), other.b(), alpha),
+        )
+    }
+
+    /// Increase the perceived lightness by `amount`, from `0.0` to `1.0`
+    ///
+    /// Lightness is adjusted in [`Oklch`], keeping the hue and chroma as close as possible.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let grey = anstyle::RgbColor(128, 128, 128);
+    /// let lighter = grey.lighten(0.1);
+    /// assert!(grey.r() < lighter.r());
+    /// assert_eq!(grey.lighten(1.0), anstyle::RgbColor(255, 255, 255));
+    /// ```
+    pub fn lighten(self, amount: f32) -> Self {
+        let mut oklch = self.to_oklch();
+        oklch.l = (oklch.l + amount).clamp(0.0, 1.0);
+        Self::from_oklch(oklch)
+    }
+
+    /// Decrease the perceived lightness by `amount`, from `0.0` to `1.0`
+    ///
+    /// Lightness is adjusted in [`Oklch`], keeping the hue and chroma as close as possible.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let grey = anstyle::RgbColor(128, 128, 128);
+    /// let darker = grey.darken(0.1);
+    /// assert!(darker.r() < grey.r());
+    /// assert_eq!(grey.darken(1.0), anstyle::RgbColor(0, 0, 0));
+    /// ```
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+
+    /// [Relative luminance](https://www.w3.org/TR/WCAG21/#dfn-relative-luminance), from `0.0`
+    /// for black to `1.0` for white
+    pub fn relative_luminance(self) -> f32 {
+        let r = srgb_to_linear(self.r());
+        let g = srgb_to_linear(self.g());
+        let b = srgb_to_linear(self.b());
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// [WCAG contrast ratio](https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio), from `1.0` to
+    /// `21.0`
+    ///
+    /// WCAG recommends at least `4.5` for text and `3.0` for large text.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let black = anstyle::RgbColor(0, 0, 0);
+    /// let white = anstyle::RgbColor(255, 255, 255);
+    /// assert!(black.contrast_ratio(white) > 20.9);
+    /// assert_eq!(black.contrast_ratio(white), white.contrast_ratio(black));
+    /// assert_eq!(white.contrast_ratio(white), 1.0);
+    /// ```
+    pub fn contrast_ratio(self, other: Self) -> f32 {
+        let l1 = self.relative_luminance();
+        let l2 = other.relative_luminance();
+        let (lighter, darker) = if l1 < l2 { (l2, l1) } else { (l1, l2) };
+        (lighter + 0.05) / (darker + 0.05)
+    }
+
+    /// Convert to [`Hsl`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let hsl = anstyle::RgbColor(255, 0, 0).to_hsl();
+    /// assert_eq!(hsl, anstyle::Hsl { h: 0.0, s: 1.0, l: 0.5 });
+    /// ```
+    pub fn to_hsl(self) -> Hsl {
+        let max = self.r().max(self.g()).max(self.b());
+        let min = self.r().min(self.g()).min(self.b());
+        let l = (max as f32 + min as f32) / 2.0 / 255.0;
+        if max == min {
+            return Hsl { h: 0.0, s: 0.0, l };
+        }
+
+        let r = self.r() as f32 / 255.0;
+        let g = self.g() as f32 / 255.0;
+        let b = self.b() as f32 / 255.0;
+        let delta = (max - min) as f32 / 255.0;
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == self.r() {
+            ((g - b) / delta).rem_euclid(6.0)
+        } else if max == self.g() {
+            (b - r) / delta + 2.0
+        } else {
+            (r - g) / delta + 4.0
+        };
+        Hsl { h: h * 60.0, s, l }
+    }
+
+    /// Convert from [`Hsl`], clamping out-of-range values
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let hsl = anstyle::Hsl { h: 120.0, s: 1.0, l: 0.25 };
+    /// assert_eq!(anstyle::RgbColor::from_hsl(hsl), anstyle::RgbColor(0, 128, 0));
+    /// ```
+    pub fn from_hsl(hsl: Hsl) -> Self {
+        let h = hsl.h.rem_euclid(360.0) / 60.0;
+        let s = hsl.s.clamp(0.0, 1.0);
+        let l = hsl.l.clamp(0.0, 1.0);
+
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = l - c / 2.0;
+        Self(unit_to_u8(r + m), unit_to_u8(g + m), unit_to_u8(b + m))
+    }
+
+    /// Convert to [`Oklch`]
+    pub fn to_oklch(self) -> Oklch {
+        let r = srgb_to_linear(self.r());
+        let g = srgb_to_linear(self.g());
+        let b = srgb_to_linear(self.b());
+
+        let l_ = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+        let m_ = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+        let s_ = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+        let l = 0.210_454_26 * l_ + 0.793_617_8 * m_ - 0.004_072_047 * s_;
+        let a = 1.977_998_5 * l_ - 2.428_592_2 * m_ + 0.450_593_7 * s_;
+        let b = 0.025_904_037 * l_ + 0.782_771_77 * m_ - 0.808_675_77 * s_;
+
+        let c = a.hypot(b);
+        let h = if c < ACHROMATIC {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        };
+        Oklch { l, c, h }
+    }
+
+    /// Convert from [`Oklch`]
+    ///
+    /// Colors outside of the sRGB gamut have their chroma reduced until they fit.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let color = anstyle::RgbColor(200, 100, 50);
+    /// assert_eq!(anstyle::RgbColor::from_oklch(color.to_oklch()), color);
+    /// ```
+    pub fn from_oklch(oklch: Oklch) -> Self {
+        let l = oklch.l.clamp(0.0, 1.0);
+        let c = oklch.c.max(0.0);
+        let h = oklch.h.to_radians();
+
+        if let Some(rgb) = oklab_to_linear(l, c * h.cos(), c * h.sin()) {
+            return linear_to_rgb(rgb);
+        }
+        // Binary search for the most chroma that is still in gamut
+        let mut low = 0.0;
+        let mut high = c;
+        for _ in 0..16 {
+            let mid = (low + high) / 2.0;
+            if oklab_to_linear(l, mid * h.cos(), mid * h.sin()).is_some() {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        let rgb = oklab_to_linear(l, low * h.cos(), low * h.sin())
+            .unwrap_or_else(|| oklab_to_linear_unclamped(l, 0.0, 0.0));
+        linear_to_rgb(rgb)
+    }
+}
+
+/// Hue, saturation, and lightness
+///
+/// See [`RgbColor::to_hsl`]
+#[allow(clippy::exhaustive_structs)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct Hsl {
+    /// Hue, in degrees from `0.0` to `360.0`
+    pub h: f32,
+    /// Saturation, from `0.0` to `1.0`
+    pub s: f32,
+    /// Lightness, from `0.0` to `1.0`
+    pub l: f32,
+}
+
+/// Perceptual lightness, chroma, and hue of [OKLCH](https://bottosson.github.io/posts/oklab/)
+///
+/// See [`RgbColor::to_oklch`]
+#[allow(clippy::exhaustive_structs)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct Oklch {
+    /// Lightness, from `0.0` to `1.0`
+    pub l: f32,
+    /// Chroma, from `0.0` to about `0.37` within sRGB
+    pub c: f32,
+    /// Hue, in degrees from `0.0` to `360.0`
+    pub h: f32,
+}
+
+/// Chroma below which a hue is meaningless
+const ACHROMATIC: f32 = 1e-4;
+
+/// Tolerance for rounding errors when checking the gamut
+const GAMUT_EPSILON: f32 = 1e-4;
+
+fn srgb_to_linear(component: u8) -> f32 {
+    let c = component as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn unit_to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn linear_to_rgb([r, g, b]: [f32; 3]) -> RgbColor {
+    RgbColor(
+        unit_to_u8(linear_to_srgb(r)),
+        unit_to_u8(linear_to_srgb(g)),
+        unit_to_u8(linear_to_srgb(b)),
+    )
+}
+
+/// Convert to linear sRGB, if in gamut
+fn oklab_to_linear(l: f32, a: f32, b: f32) -> Option<[f32; 3]> {
+    let rgb = oklab_to_linear_unclamped(l, a, b);
+    let in_gamut = rgb
+        .iter()
+        .all(|c| (-GAMUT_EPSILON..=1.0 + GAMUT_EPSILON).contains(c));
+    in_gamut.then_some(rgb)
+}
+
+fn oklab_to_linear_unclamped(l: f32, a: f32, b: f32) -> [f32; 3] {
+    let l_ = l + 0.396_337_78 * a + 0.215_803_76 * b;
+    let m_ = l - 0.105_561_346 * a - 0.063_854_17 * b;
+    let s_ = l - 0.089_484_18 * a - 1.291_485_5 * b;
+
+    let l = l_ * l_ * l_;
+    let m = m_ * m_ * m_;
+    let s = s_ * s_ * s_;
+
+    [
+        4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+        -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+        -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hsl_roundtrip() {
+        for r in (0..=255).step_by(17) {
+            for g in (0..=255).step_by(17) {
+                for b in (0..=255).step_by(17) {
+                    let color = RgbColor(r, g, b);
+                    assert_eq!(RgbColor::from_hsl(color.to_hsl()), color);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn oklch_roundtrip() {
+        for r in (0..=255).step_by(17) {
+            for g in (0..=255).step_by(17) {
+                for b in (0..=255).step_by(17) {
+                    let color = RgbColor(r, g, b);
+                    assert_eq!(RgbColor::from_oklch(color.to_oklch()), color);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn oklch_reference() {
+        let white = RgbColor(255, 255, 255).to_oklch();
+        assert!((white.l - 1.0).abs() < 0.001, "{white:?}");
+        assert!(white.c < 0.001, "{white:?}");
+
+        let red = RgbColor(255, 0, 0).to_oklch();
+        assert!((red.l - 0.628).abs() < 0.001, "{red:?}");
+        assert!((red.c - 0.258).abs() < 0.001, "{red:?}");
+        assert!((red.h - 29.23).abs() < 0.1, "{red:?}");
+    }
+
+    #[test]
+    fn from_oklch_out_of_gamut() {
+        let color = RgbColor::from_oklch(Oklch {
+            l: 0.9,
+            c: 0.4,
+            h: 140.0,
+        });
+        assert!(color.g() > color.r() && color.g() > color.b(), "{color:?}");
+    }
+
+    #[test]
+    fn lighten_keeps_hue() {
+        let blue = RgbColor(0, 0, 200);
+        let lighter = blue.lighten(0.2);
+        assert!(blue.to_oklch().l < lighter.to_oklch().l);
+        assert!((blue.to_oklch().h - lighter.to_oklch().h).abs() < 2.0);
+    }
+
+    #[test]
+    fn contrast_ratio_reference() {
+        // #767676 is the lightest grey meeting 4.5 against white
+        let ratio = RgbColor(0x76, 0x76, 0x76).contrast_ratio(RgbColor(255, 255, 255));
+        assert!((4.5..4.6).contains(&ratio), "{ratio}");
+        let ratio = RgbColor(0x77, 0x77, 0x77).contrast_ratio(RgbColor(255, 255, 255));
+        assert!(ratio < 4.5, "{ratio}");
+    }
+}
@@ -6,7 +6,7 @@
 /// let effects = anstyle::Effects::BOLD | anstyle::Effects::UNDERLINE;
 /// ```
 #[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Effects(u16);
+pub struct Effects(u32);
 
 impl Effects {
     /// No [`Effects`] applied
@@ -36,6 +36,20 @@ impl Effects {
     pub const HIDDEN: Self = Self(1 << 10);
     ///  Characters legible but marked as if for deletion. Not supported in Terminal.app
     pub const STRIKETHROUGH: Self = Self(1 << 11);
+    /// Not supported in Windows Terminal and Terminal.app
+    pub const OVERLINE: Self = Self(1 << 12);
+    /// Not widely supported. Sometimes treated as [`Effects::BLINK`]
+    pub const RAPID_BLINK: Self = Self(1 << 13);
+    /// Not widely supported
+    pub const FRAMED: Self = Self(1 << 14);
+    /// Not widely supported
+    pub const ENCIRCLED: Self = Self(1 << 15);
+    /// Implemented only in mintty
+    pub const SUPERSCRIPT: Self = Self(1 << 16);
+    /// Implemented only in mintty
+    pub const SUBSCRIPT: Self = Self(1 << 17);
+    /// Not known to be supported by any terminal emulator
+    pub const PROPORTIONAL_SPACING: Self = Self(1 << 18);
 
     /// No effects enabled
     ///
@@ -163,6 +177,12 @@ impl Effects {
         }
     }
 
+    /// Effect for a [`METADATA`] index
+    #[inline(always)]
+    pub(crate) const fn from_index(index: usize) -> Self {
+        Self(1 << index)
+    }
+
     /// Render the ANSI code
     #[inline]
     pub fn render(self) -> impl core::fmt::Display + Copy {
@@ -262,57 +282,127 @@ impl core::ops::SubAssign for Effects {
 
 pub(crate) struct Metadata {
     pub(crate) name: &'static str,
+    /// Word for the text syntax, see [`Effects::from_str`][core::str::FromStr::from_str]
+    pub(crate) text: &'static str,
     pub(crate) escape: &'static str,
+    /// Turns off this effect, possibly along with others sharing the same code
+    pub(crate) off: &'static str,
 }
 
-pub(crate) const METADATA: [Metadata; 12] = [
+pub(crate) const METADATA: [Metadata; 19] = [
     Metadata {
         name: "BOLD",
+        text: "bold",
         escape: escape!("1"),
+        off: escape!("22"),
     },
     Metadata {
         name: "DIMMED",
+        text: "dimmed",
         escape: escape!("2"),
+        off: escape!("22"),
     },
     Metadata {
         name: "ITALIC",
+        text: "italic",
         escape: escape!("3"),
+        off: escape!("23"),
     },
     Metadata {
         name: "UNDERLINE",
+        text: "underline",
         escape: escape!("4"),
+        off: escape!("24"),
     },
     Metadata {
         name: "DOUBLE_UNDERLINE",
+        text: "underline:double",
         escape: escape!("21"),
+        off: escape!("24"),
     },
     Metadata {
         name: "CURLY_UNDERLINE",
+        text: "underline:curly",
         escape: escape!("4:3"),
+        off: escape!("24"),
     },
     Metadata {
         name: "DOTTED_UNDERLINE",
+        text: "underline:dotted",
         escape: escape!("4:4"),
+        off: escape!("24"),
     },
     Metadata {
         name: "DASHED_UNDERLINE",
+        text: "underline:dashed",
         escape: escape!("4:5"),
+        off: escape!("24"),
     },
     Metadata {
         name: "BLINK",
+        text: "blink",
         escape: escape!("5"),
+        off: escape!("25"),
     },
     Metadata {
         name: "INVERT",
+        text: "invert",
         escape: escape!("7"),
+        off: escape!("27"),
     },
     Metadata {
         name: "HIDDEN",
+        text: "hidden",
         escape: escape!("8"),
+        off: escape!("28"),
     },
     Metadata {
         name: "STRIKETHROUGH",
+        text: "strikethrough",
         escape: escape!("9"),
+        off: escape!("29"),
+    },
+    Metadata {
+        name: "OVERLINE",
+        text: "overline",
+        escape: escape!("53"),
+        off: escape!("55"),
+    },
+    Metadata {
+        name: "RAPID_BLINK",
+        text: "rapid_blink",
+        escape: escape!("6"),
+        off: escape!("25"),
+    },
+    Metadata {
+        name: "FRAMED",
+        text: "framed",
+        escape: escape!("51"),
+        off: escape!("54"),
+    },
+    Metadata {
+        name: "ENCIRCLED",
+        text: "encircled",
+        escape: escape!("52"),
+        off: escape!("54"),
+    },
+    Metadata {
+        name: "SUPERSCRIPT",
+        text: "superscript",
+        escape: escape!("73"),
+        off: escape!("75"),
+    },
+    Metadata {
+        name: "SUBSCRIPT",
+        text: "subscript",
+        escape: escape!("74"),
+        off: escape!("75"),
+    },
+    Metadata {
+        name: "PROPORTIONAL_SPACING",
+        text: "proportional_spacing",
+        escape: escape!("26"),
+        off: escape!("50"),
     },
 ];
 
@@ -52,18 +52,36 @@
 #![warn(clippy::print_stderr)]
 #![warn(clippy::print_stdout)]
 
+#[cfg(feature = "std")]
+extern crate alloc;
+
 #[macro_use]
 mod macros;
 
+mod cascade;
 mod color;
+#[cfg(feature = "color-math")]
+mod color_math;
 mod effect;
 mod reset;
+#[cfg(feature = "serde")]
+mod serde_impl;
 mod style;
+#[cfg(feature = "std")]
+mod styled_str;
+mod text;
+mod transition;
 
+pub use cascade::*;
 pub use color::*;
+#[cfg(feature = "color-math")]
+pub use color_math::*;
 pub use effect::*;
 pub use reset::*;
 pub use style::*;
+#[cfg(feature = "std")]
+pub use styled_str::*;
+pub use text::*;
 
 #[doc = include_str!("../README.md")]
 #[cfg(doctest)]
@@ -0,0 +1,405 @@
+//! [`serde`] support, serializing to the text syntax
+//!
+//! Deserializing from a human-readable format accepts either the text syntax or a structured form,
+//! while other formats, which can't be inspected for which form was used, only accept the text:
+//! - [`Color`]: a string, an [`Ansi256Color`] index, or a table of `r`, `g`, and `b`
+//! - [`Effects`]: a string or a list of strings
+//! - [`Style`]: a string or a table of `fg_color`, `bg_color`, `underline_color`, and `effects`
+
+use core::fmt;
+
+use serde::de;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+use crate::Ansi256Color;
+use crate::AnsiColor;
+use crate::Color;
+use crate::Effects;
+use crate::RgbColor;
+use crate::Style;
+
+/// Serialize as text, see [`Style::describe`]
+impl Serialize for Style {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.describe())
+    }
+}
+
+impl<'de> Deserialize<'de> for Style {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(StyleVisitor)
+        } else {
+            deserializer.deserialize_str(StyleVisitor)
+        }
+    }
+}
+
+struct StyleVisitor;
+
+impl<'de> de::Visitor<'de> for StyleVisitor {
+    type Value = Style;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a style string or table")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        const FIELDS: &[&str] = &["fg_color", "bg_color", "underline_color", "effects"];
+
+        let mut fg = None;
+        let mut bg = None;
+        let mut underline = None;
+        let mut effects = None;
+        while let Some(field) = map.next_key_seed(Field(FIELDS))? {
+            match field {
+                0 => set_field(&mut fg, &mut map, FIELDS[field])?,
+                1 => set_field(&mut bg, &mut map, FIELDS[field])?,
+                2 => set_field(&mut underline, &mut map, FIELDS[field])?,
+                _ => set_field(&mut effects, &mut map, FIELDS[field])?,
+            }
+        }
+        Ok(Style::new()
+            .fg_color(fg)
+            .bg_color(bg)
+            .underline_color(underline)
+            .effects(effects.unwrap_or_default()))
+    }
+}
+
+/// Serialize as text, see [`Effects::from_str`][core::str::FromStr::from_str]
+impl Serialize for Effects {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Effects {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(EffectsVisitor)
+        } else {
+            deserializer.deserialize_str(EffectsVisitor)
+        }
+    }
+}
+
+struct EffectsVisitor;
+
+impl<'de> de::Visitor<'de> for EffectsVisitor {
+    type Value = Effects;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an effects string or list")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut effects = Effects::new();
+        while let Some(effect) = seq.next_element_seed(EffectsSeed)? {
+            effects |= effect;
+        }
+        Ok(effects)
+    }
+}
+
+/// A single string within a list of effects
+struct EffectsSeed;
+
+impl<'de> de::DeserializeSeed<'de> for EffectsSeed {
+    type Value = Effects;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(EffectsVisitor)
+    }
+}
+
+/// Serialize as text, see [`Color::from_str`][core::str::FromStr::from_str]
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(ColorVisitor)
+        } else {
+            deserializer.deserialize_str(ColorVisitor)
+        }
+    }
+}
+
+struct ColorVisitor;
+
+impl<'de> de::Visitor<'de> for ColorVisitor {
+    type Value = Color;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a color string, index, or table")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        u8::try_from(v)
+            .map(|index| Ansi256Color(index).into())
+            .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        u8::try_from(v)
+            .map(|index| Ansi256Color(index).into())
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        const FIELDS: &[&str] = &["r", "g", "b"];
+
+        let mut channels = [None; 3];
+        while let Some(field) = map.next_key_seed(Field(FIELDS))? {
+            set_field(&mut channels[field], &mut map, FIELDS[field])?;
+        }
+        let [r, g, b] = channels;
+        let r = r.ok_or_else(|| de::Error::missing_field(FIELDS[0]))?;
+        let g = g.ok_or_else(|| de::Error::missing_field(FIELDS[1]))?;
+        let b = b.ok_or_else(|| de::Error::missing_field(FIELDS[2]))?;
+        Ok(RgbColor(r, g, b).into())
+    }
+}
+
+/// Serialize as text, like `bright_red`
+impl Serialize for AnsiColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AnsiColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Color::deserialize(deserializer)? {
+            Color::Ansi(color) => Ok(color),
+            color => Err(de::Error::custom(format_args!(
+                "expected a 4-bit color name, found `{color}`"
+            ))),
+        }
+    }
+}
+
+/// Serialize as text, like `208`
+impl Serialize for Ansi256Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Ansi256Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Color::deserialize(deserializer)? {
+            Color::Ansi(color) => Ok(Self::from_ansi(color)),
+            Color::Ansi256(color) => Ok(color),
+            color @ Color::Rgb(_) => Err(de::Error::custom(format_args!(
+                "expected an 8-bit color, found `{color}`"
+            ))),
+        }
+    }
+}
+
+/// Serialize as text, like `#ff8800`
+impl Serialize for RgbColor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RgbColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Color::deserialize(deserializer)? {
+            Color::Rgb(color) => Ok(color),
+            color => Err(de::Error::custom(format_args!(
+                "expected a 24-bit color, found `{color}`"
+            ))),
+        }
+    }
+}
+
+fn set_field<'de, T: Deserialize<'de>, A: de::MapAccess<'de>>(
+    value: &mut Option<T>,
+    map: &mut A,
+    name: &'static str,
+) -> Result<(), A::Error> {
+    if value.is_some() {
+        return Err(de::Error::duplicate_field(name));
+    }
+    *value = Some(map.next_value()?);
+    Ok(())
+}
+
+/// Look up a table key, returning its index in the list of fields
+struct Field(&'static [&'static str]);
+
+impl<'de> de::DeserializeSeed<'de> for Field {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for Field {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a field name")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.0
+            .iter()
+            .position(|field| *field == v)
+            .ok_or_else(|| E::unknown_field(v, self.0))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod test {
+    use super::*;
+
+    #[track_caller]
+    fn assert_de<T: for<'de> Deserialize<'de> + PartialEq + fmt::Debug>(json: &str, expected: T) {
+        let actual = serde_json::from_str::<T>(json).unwrap();
+        assert_eq!(actual, expected, "{json}");
+    }
+
+    #[track_caller]
+    fn assert_de_err<T: for<'de> Deserialize<'de> + fmt::Debug>(json: &str, expected: &str) {
+        let actual = serde_json::from_str::<T>(json).unwrap_err().to_string();
+        assert_eq!(actual, expected, "{json}");
+    }
+
+    #[test]
+    fn style_round_trip() {
+        let style = Style::new()
+            .bold()
+            .fg_color(Some(Ansi256Color(208).into()))
+            .bg_color(Some(AnsiColor::Blue.into()))
+            .effects(Effects::BOLD | Effects::CURLY_UNDERLINE)
+            .underline_color(Some(RgbColor(0xff, 0x88, 0x00).into()));
+        let json = serde_json::to_string(&style).unwrap();
+        assert_eq!(json, r##""bold 208 on blue underline:curly:#ff8800""##);
+        assert_de(&json, style);
+    }
+
+    #[test]
+    fn style_table() {
+        assert_de(
+            r#"{"fg_color": "red", "bg_color": 16, "underline_color": {"r": 1, "g": 2, "b": 3}, "effects": ["bold", "underline"]}"#,
+            Style::new()
+                .fg_color(Some(AnsiColor::Red.into()))
+                .bg_color(Some(Ansi256Color(16).into()))
+                .underline_color(Some(RgbColor(1, 2, 3).into()))
+                .effects(Effects::BOLD | Effects::UNDERLINE),
+        );
+        assert_de(r#"{}"#, Style::new());
+        assert_de_err::<Style>(
+            r#"{"fg": "red"}"#,
+            "unknown field `fg`, expected one of `fg_color`, `bg_color`, `underline_color`, `effects` at line 1 column 5",
+        );
+        assert_de_err::<Style>(
+            r#"{"effects": "bold", "effects": "italic"}"#,
+            "duplicate field `effects` at line 1 column 29",
+        );
+    }
+
+    #[test]
+    fn style_errors() {
+        assert_de_err::<Style>(
+            r#""bold on""#,
+            "expected a color after `on` at 5..7 at line 1 column 9",
+        );
+        assert_de_err::<Style>(
+            "true",
+            "invalid type: boolean `true`, expected a style string or table at line 1 column 4",
+        );
+    }
+
+    #[test]
+    fn effects() {
+        assert_de(r#""bold italic""#, Effects::BOLD | Effects::ITALIC);
+        assert_de(
+            r#"["bold", "underline:dotted hidden"]"#,
+            Effects::BOLD | Effects::DOTTED_UNDERLINE | Effects::HIDDEN,
+        );
+        assert_eq!(
+            serde_json::to_string(&(Effects::HIDDEN | Effects::BOLD)).unwrap(),
+            r#""bold hidden""#
+        );
+        assert_de_err::<Effects>(
+            r#"["bold", 1]"#,
+            "invalid type: integer `1`, expected an effects string or list at line 1 column 10",
+        );
+    }
+
+    #[test]
+    fn not_human_readable() {
+        let style = Style::new()
+            .fg_color(Some(Ansi256Color(208).into()))
+            .effects(Effects::BOLD | Effects::CURLY_UNDERLINE)
+            .underline_color(Some(RgbColor(0xff, 0x88, 0x00).into()));
+        let bytes = bincode::serialize(&style).unwrap();
+        assert_eq!(bincode::deserialize::<Style>(&bytes).unwrap(), style);
+
+        let effects = Effects::BOLD | Effects::HIDDEN;
+        let bytes = bincode::serialize(&effects).unwrap();
+        assert_eq!(bincode::deserialize::<Effects>(&bytes).unwrap(), effects);
+
+        let color = Color::from(RgbColor(0xff, 0x88, 0x00));
+        let bytes = bincode::serialize(&color).unwrap();
+        assert_eq!(bincode::deserialize::<Color>(&bytes).unwrap(), color);
+        assert_eq!(
+            bincode::deserialize::<RgbColor>(&bytes).unwrap(),
+            RgbColor(0xff, 0x88, 0x00)
+        );
+    }
+
+    #[test]
+    fn colors() {
+        assert_de(r#""ansi:208""#, Color::from(Ansi256Color(208)));
+        assert_de("208", Color::from(Ansi256Color(208)));
+        assert_de(r##""#FF8800""##, Color::from(RgbColor(0xff, 0x88, 0x00)));
+        assert_de(
+            r#"{"r": 255, "g": 136, "b": 0}"#,
+            RgbColor(0xff, 0x88, 0x00),
+        );
+        assert_de(r#""bright_red""#, AnsiColor::BrightRed);
+        assert_de(r#""red""#, Ansi256Color(1));
+        assert_eq!(
+            serde_json::to_string(&Color::from(Ansi256Color(208))).unwrap(),
+            r#""208""#
+        );
+        assert_de_err::<Color>(
+            "256",
+            "invalid value: integer `256`, expected a color string, index, or table at line 1 column 3",
+        );
+        assert_de_err::<Color>(
+            r#"{"r": 255, "g": 136}"#,
+            "missing field `b` at line 1 column 20",
+        );
+        assert_de_err::<AnsiColor>("208", "expected a 4-bit color name, found `208`");
+        assert_de_err::<RgbColor>(r#""red""#, "expected a 24-bit color, found `red`");
+    }
+}
@@ -170,6 +170,55 @@ impl Style {
             Ok(())
         }
     }
+
+    /// Render the shortest ANSI code to change from this style to `next`
+    ///
+    /// Unlike rendering [`Style::render_reset`] followed by [`Style::render`], this only turns off
+    /// effects and switches colors that differ, falling back to a reset when that is shorter.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let error = anstyle::Style::new().bold().fg_color(Some(anstyle::AnsiColor::Red.into()));
+    /// let warning = error.fg_color(Some(anstyle::AnsiColor::Yellow.into()));
+    /// assert_eq!(error.render_transition(warning).to_string(), "\x1B[33m");
+    /// assert_eq!(error.render_transition(anstyle::Style::new()).to_string(), "\x1B[0m");
+    /// ```
+    #[inline]
+    pub fn render_transition(self, next: Self) -> impl core::fmt::Display + Copy {
+        crate::transition::StyleTransition {
+            from: self,
+            to: next,
+        }
+    }
+
+    /// Write the shortest ANSI code to change from this style to `next`
+    ///
+    /// See [`Style::render_transition`].
+    #[inline]
+    #[cfg(feature = "std")]
+    pub fn write_transition_to(
+        self,
+        next: Self,
+        write: &mut dyn std::io::Write,
+    ) -> std::io::Result<()> {
+        write!(write, "{}", self.render_transition(next))
+    }
+
+    /// Describe the style in the text syntax, see [`Style::from_str`][core::str::FromStr::from_str]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let style = anstyle::Style::new()
+    ///     .bold()
+    ///     .bg_color(Some(anstyle::AnsiColor::Blue.into()));
+    /// assert_eq!(style.describe().to_string(), "bold on blue");
+    /// ```
+    #[inline]
+    pub fn describe(self) -> impl core::fmt::Display + Copy {
+        crate::text::StyleText(self)
+    }
 }
 
 /// # Convenience
@@ -285,6 +334,20 @@ impl Style {
         self.effects = self.effects.insert(crate::Effects::STRIKETHROUGH);
         self
     }
+
+    /// Apply `overline` effect
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let style = anstyle::Style::new().overline();
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn overline(mut self) -> Self {
+        self.effects = self.effects.insert(crate::Effects::OVERLINE);
+        self
+    }
 }
 
 /// # Reflection
@@ -0,0 +1,416 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::Style;
+
+/// Owned text made of [`Style`]d spans
+///
+/// You can print a `StyledStr` to render the text with the ANSI codes for each span.
+/// [`StyledStr::as_str`] gives the same text without any escape codes.
+///
+/// # Examples
+///
+/// ```rust
+/// let mut message = anstyle::StyledStr::new();
+/// message.push_str(anstyle::Style::new().bold(), "error");
+/// message.push_str(anstyle::Style::new(), ": file not found");
+///
+/// assert_eq!(message.as_str(), "error: file not found");
+/// assert_eq!(message.to_string(), "\x1b[1merror\x1b[0m: file not found");
+/// ```
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct StyledStr {
+    text: String,
+    /// Each span's style and end offset into `text`
+    ///
+    /// Spans are contiguous, non-empty, and adjacent spans never share a style.
+    spans: Vec<(Style, usize)>,
+}
+
+/// # Core
+impl StyledStr {
+    /// Empty text
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let text = anstyle::StyledStr::new();
+    /// assert!(text.is_empty());
+    /// ```
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            text: String::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Append `text` with the given `style`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut text = anstyle::StyledStr::new();
+    /// text.push_str(anstyle::AnsiColor::Red.on_default(), "red");
+    /// text.push_str(anstyle::Style::new(), " plain");
+    /// assert_eq!(text.as_str(), "red plain");
+    /// ```
+    pub fn push_str(&mut self, style: Style, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        self.text.push_str(text);
+        let end = self.text.len();
+        match self.spans.last_mut() {
+            Some((last, last_end)) if *last == style => *last_end = end,
+            _ => self.spans.push((style, end)),
+        }
+    }
+
+    /// Append a single `char` with the given `style`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut text = anstyle::StyledStr::new();
+    /// text.push(anstyle::Style::new().bold(), '!');
+    /// assert_eq!(text.as_str(), "!");
+    /// ```
+    #[inline]
+    pub fn push(&mut self, style: Style, c: char) {
+        let mut buffer = [0; 4];
+        self.push_str(style, c.encode_utf8(&mut buffer));
+    }
+
+    /// Append all spans from `other`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut text = anstyle::StyledStr::from("Hello");
+    /// let mut name = anstyle::StyledStr::new();
+    /// name.push_str(anstyle::Style::new().bold(), " world");
+    /// text.append(&name);
+    /// assert_eq!(text.as_str(), "Hello world");
+    /// assert_eq!(text.iter().count(), 2);
+    /// ```
+    pub fn append(&mut self, other: &Self) {
+        for (style, text) in other.iter() {
+            self.push_str(style, text);
+        }
+    }
+
+    /// Copy out the spans covering the `char` indices in `range`
+    ///
+    /// # Panics
+    ///
+    /// If the range is out of bounds or the start is after the end
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut text = anstyle::StyledStr::new();
+    /// text.push_str(anstyle::Style::new().bold(), "héllo");
+    /// text.push_str(anstyle::Style::new(), " world");
+    ///
+    /// let slice = text.slice(1..7);
+    /// assert_eq!(slice.as_str(), "éllo w");
+    /// assert_eq!(slice.to_string(), "\x1b[1méllo\x1b[0m w");
+    /// ```
+    pub fn slice(&self, range: impl core::ops::RangeBounds<usize>) -> Self {
+        use core::ops::Bound;
+
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => Some(end + 1),
+            Bound::Excluded(end) => Some(*end),
+            Bound::Unbounded => None,
+        };
+        let byte_start = self
+            .char_to_byte(start)
+            .unwrap_or_else(|| panic!("char index {start} is out of bounds of `StyledStr`"));
+        let byte_end = match end {
+            Some(end) => {
+                assert!(
+                    start <= end,
+                    "slice index starts at {start} but ends at {end}"
+                );
+                self.char_to_byte(end)
+                    .unwrap_or_else(|| panic!("char index {end} is out of bounds of `StyledStr`"))
+            }
+            None => self.text.len(),
+        };
+
+        let mut slice = Self::new();
+        let mut span_start = 0;
+        for (style, span_end) in self.spans.iter().copied() {
+            let start = span_start.max(byte_start);
+            let end = span_end.min(byte_end);
+            if start < end {
+                slice.push_str(style, &self.text[start..end]);
+            }
+            span_start = span_end;
+        }
+        slice
+    }
+
+    fn char_to_byte(&self, index: usize) -> Option<usize> {
+        self.text
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain(core::iter::once(self.text.len()))
+            .nth(index)
+    }
+
+    /// Remove all text, keeping the allocations
+    #[inline]
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.spans.clear();
+    }
+
+    /// Render the ANSI code
+    ///
+    /// `StyledStr` also implements `Display` directly, so calling this method is optional.
+    #[inline]
+    pub fn render(&self) -> impl core::fmt::Display + Copy + '_ {
+        StyledStrDisplay(self)
+    }
+
+    fn fmt_to(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use core::fmt::Display as _;
+
+        for (style, text) in self.iter() {
+            style.render().fmt(f)?;
+            f.write_str(text)?;
+            style.render_reset().fmt(f)?;
+        }
+        Ok(())
+    }
+
+    /// Write the text with ANSI codes
+    pub fn write_to(&self, write: &mut dyn std::io::Write) -> std::io::Result<()> {
+        for (style, text) in self.iter() {
+            style.write_to(write)?;
+            write.write_all(text.as_bytes())?;
+            style.write_reset_to(write)?;
+        }
+        Ok(())
+    }
+}
+
+/// # Reflection
+impl StyledStr {
+    /// The text without any styling
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.text.as_str()
+    }
+
+    /// Length of the text in bytes, excluding ANSI codes
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    /// Check if there is no text
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Iterate over each span's [`Style`] and text
+    ///
+    /// Adjacent spans always have different styles.
+    #[inline]
+    pub fn iter(&self) -> StyledStrIter<'_> {
+        StyledStrIter {
+            text: self.text.as_str(),
+            spans: self.spans.iter(),
+            start: 0,
+        }
+    }
+}
+
+/// # Examples
+///
+/// ```rust
+/// let text: anstyle::StyledStr = "Hello".into();
+/// assert_eq!(text.to_string(), "Hello");
+/// ```
+impl From<&str> for StyledStr {
+    #[inline]
+    fn from(text: &str) -> Self {
+        let mut styled = Self::new();
+        styled.push_str(Style::new(), text);
+        styled
+    }
+}
+
+impl From<String> for StyledStr {
+    #[inline]
+    fn from(text: String) -> Self {
+        let spans = if text.is_empty() {
+            Vec::new()
+        } else {
+            alloc::vec![(Style::new(), text.len())]
+        };
+        Self { text, spans }
+    }
+}
+
+/// # Examples
+///
+/// ```rust
+/// let text: anstyle::StyledStr = (anstyle::Style::new().bold(), "Hello").into();
+/// assert_eq!(text.to_string(), "\x1b[1mHello\x1b[0m");
+/// ```
+impl From<(Style, &str)> for StyledStr {
+    #[inline]
+    fn from((style, text): (Style, &str)) -> Self {
+        let mut styled = Self::new();
+        styled.push_str(style, text);
+        styled
+    }
+}
+
+impl<'s> Extend<(Style, &'s str)> for StyledStr {
+    fn extend<I: IntoIterator<Item = (Style, &'s str)>>(&mut self, iter: I) {
+        for (style, text) in iter {
+            self.push_str(style, text);
+        }
+    }
+}
+
+/// # Examples
+///
+/// ```rust
+/// let bold = anstyle::Style::new().bold();
+/// let text: anstyle::StyledStr = [(bold, "a"), (bold, "b")].into_iter().collect();
+/// assert_eq!(text.to_string(), "\x1b[1mab\x1b[0m");
+/// ```
+impl<'s> FromIterator<(Style, &'s str)> for StyledStr {
+    fn from_iter<I: IntoIterator<Item = (Style, &'s str)>>(iter: I) -> Self {
+        let mut styled = Self::new();
+        styled.extend(iter);
+        styled
+    }
+}
+
+impl<'s> IntoIterator for &'s StyledStr {
+    type Item = (Style, &'s str);
+    type IntoIter = StyledStrIter<'s>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl core::fmt::Display for StyledStr {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.fmt_to(f)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct StyledStrDisplay<'s>(&'s StyledStr);
+
+impl core::fmt::Display for StyledStrDisplay<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt_to(f)
+    }
+}
+
+/// Enumerate each span in a [`StyledStr`]
+#[derive(Clone, Debug)]
+pub struct StyledStrIter<'s> {
+    text: &'s str,
+    spans: core::slice::Iter<'s, (Style, usize)>,
+    start: usize,
+}
+
+impl<'s> Iterator for StyledStrIter<'s> {
+    type Item = (Style, &'s str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (style, end) = *self.spans.next()?;
+        let text = &self.text[self.start..end];
+        self.start = end;
+        Some((style, text))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.spans.size_hint()
+    }
+}
+
+impl ExactSizeIterator for StyledStrIter<'_> {}
+
+impl core::iter::FusedIterator for StyledStrIter<'_> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merges_adjacent_spans() {
+        let bold = Style::new().bold();
+        let mut text = StyledStr::new();
+        text.push_str(bold, "a");
+        text.push_str(bold, "");
+        text.push(bold, 'b');
+        text.push_str(Style::new(), "c");
+        text.push_str(Style::new(), "d");
+
+        let spans = text.iter().collect::<Vec<_>>();
+        assert_eq!(spans, [(bold, "ab"), (Style::new(), "cd")]);
+    }
+
+    #[test]
+    fn slice_bounds() {
+        let mut text = StyledStr::new();
+        text.push_str(Style::new().bold(), "ab");
+        text.push_str(Style::new().italic(), "cd");
+
+        assert_eq!(text.slice(..), text);
+        assert_eq!(text.slice(4..), StyledStr::new());
+        assert_eq!(text.slice(..=1).as_str(), "ab");
+        assert_eq!(text.slice(2..2), StyledStr::new());
+        assert_eq!(text.slice(1..3).iter().count(), 2);
+    }
+
+    #[test]
+    #[should_panic = "out of bounds"]
+    fn slice_out_of_bounds() {
+        let text = StyledStr::from("ab");
+        let _ = text.slice(..3);
+    }
+
+    #[test]
+    fn write_matches_display() {
+        let mut text = StyledStr::new();
+        text.push_str(Style::new().bold(), "a");
+        text.push_str(Style::new(), "b");
+        text.push_str(crate::AnsiColor::Red.on_default(), "c");
+
+        let mut written = Vec::new();
+        text.write_to(&mut written).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), text.to_string());
+        assert_eq!(text.render().to_string(), text.to_string());
+    }
+
+    #[test]
+    fn print_size_of() {
+        use core::mem::size_of;
+        dbg!(size_of::<StyledStr>());
+    }
+}
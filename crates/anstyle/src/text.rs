@@ -0,0 +1,572 @@
+//! Human-readable text syntax for [`Style`], [`Color`], and [`Effects`]
+
+use core::ops::Range;
+
+use crate::effect::METADATA;
+use crate::Ansi256Color;
+use crate::AnsiColor;
+use crate::Color;
+use crate::Effects;
+use crate::RgbColor;
+use crate::Style;
+
+const UNDERLINES: Effects = Effects::UNDERLINE
+    .insert(Effects::DOUBLE_UNDERLINE)
+    .insert(Effects::CURLY_UNDERLINE)
+    .insert(Effects::DOTTED_UNDERLINE)
+    .insert(Effects::DASHED_UNDERLINE);
+
+const ANSI_NAMES: [(AnsiColor, &str); 16] = [
+    (AnsiColor::Black, "black"),
+    (AnsiColor::Red, "red"),
+    (AnsiColor::Green, "green"),
+    (AnsiColor::Yellow, "yellow"),
+    (AnsiColor::Blue, "blue"),
+    (AnsiColor::Magenta, "magenta"),
+    (AnsiColor::Cyan, "cyan"),
+    (AnsiColor::White, "white"),
+    (AnsiColor::BrightBlack, "bright_black"),
+    (AnsiColor::BrightRed, "bright_red"),
+    (AnsiColor::BrightGreen, "bright_green"),
+    (AnsiColor::BrightYellow, "bright_yellow"),
+    (AnsiColor::BrightBlue, "bright_blue"),
+    (AnsiColor::BrightMagenta, "bright_magenta"),
+    (AnsiColor::BrightCyan, "bright_cyan"),
+    (AnsiColor::BrightWhite, "bright_white"),
+];
+
+/// Parse a [`Style`] from space-separated words
+///
+/// The words are:
+/// - Effects: `bold`, `dimmed`, `italic`, `blink`, `invert`, `hidden`, `strikethrough`,
+///   `overline`, `rapid_blink`, `framed`, `encircled`, `superscript`, `subscript`,
+///   `proportional_spacing`
+/// - A foreground color, see [`Color::from_str`][core::str::FromStr::from_str]
+/// - `on` followed by a background color
+/// - `underline[:KIND][:COLOR]` for an underline effect, with `KIND` being one of `double`,
+///   `curly`, `dotted`, or `dashed`, and an optional underline color.  An underline color without
+///   an underline is written as `underline:none:COLOR`.
+///
+/// Words are matched case-insensitively.  An empty string is [`Style::new`].
+///
+/// See [`Style::describe`] for writing a `Style` in this syntax.
+///
+/// # Examples
+///
+/// ```rust
+/// let style: anstyle::Style = "bold italic #ff8800 on blue underline:curly:red".parse().unwrap();
+/// assert_eq!(
+///     style,
+///     anstyle::Style::new()
+///         .bold()
+///         .italic()
+///         .fg_color(Some(anstyle::RgbColor(0xff, 0x88, 0x00).into()))
+///         .bg_color(Some(anstyle::AnsiColor::Blue.into()))
+///         .effects(anstyle::Effects::BOLD | anstyle::Effects::ITALIC | anstyle::Effects::CURLY_UNDERLINE)
+///         .underline_color(Some(anstyle::AnsiColor::Red.into()))
+/// );
+///
+/// let err = "bold on purple".parse::<anstyle::Style>().unwrap_err();
+/// assert_eq!(err.kind(), anstyle::ParseErrorKind::InvalidColor);
+/// assert_eq!(err.span(), 8..14);
+/// ```
+impl core::str::FromStr for Style {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Self::new();
+        let mut effects = Effects::new();
+        let mut words = Words::new(s);
+        while let Some((word, span)) = words.next() {
+            if word.eq_ignore_ascii_case("on") {
+                let (color, color_span) = words
+                    .next()
+                    .ok_or_else(|| ParseError::new(ParseErrorKind::MissingColor, span))?;
+                let color = parse_color(color).ok_or_else(|| {
+                    ParseError::new(ParseErrorKind::InvalidColor, color_span.clone())
+                })?;
+                if style.get_bg_color().is_some() {
+                    return Err(ParseError::new(ParseErrorKind::DuplicateColor, color_span));
+                }
+                style = style.bg_color(Some(color));
+            } else if let Some(effect) = parse_effect(word) {
+                effects |= effect;
+            } else if let Some(color) = parse_color(word) {
+                if style.get_fg_color().is_some() {
+                    return Err(ParseError::new(ParseErrorKind::DuplicateColor, span));
+                }
+                style = style.fg_color(Some(color));
+            } else if strip_prefix_ignore_ascii_case(word, "ansi:").is_some() {
+                return Err(ParseError::new(ParseErrorKind::InvalidColor, span));
+            } else if let Some((underline, color)) = split_underline(word) {
+                let effect = if underline.eq_ignore_ascii_case("underline:none") {
+                    Effects::new()
+                } else {
+                    parse_effect(underline)
+                        .filter(|effect| UNDERLINES.contains(*effect))
+                        .ok_or_else(|| {
+                            ParseError::new(ParseErrorKind::UnknownEffect, span.clone())
+                        })?
+                };
+                let color = parse_color(color)
+                    .ok_or_else(|| ParseError::new(ParseErrorKind::InvalidColor, span.clone()))?;
+                if style.get_underline_color().is_some() {
+                    return Err(ParseError::new(ParseErrorKind::DuplicateColor, span));
+                }
+                effects |= effect;
+                style = style.underline_color(Some(color));
+            } else {
+                return Err(ParseError::new(ParseErrorKind::UnknownWord, span));
+            }
+        }
+        Ok(style.effects(effects))
+    }
+}
+
+/// See [`Style::describe`]
+#[derive(Copy, Clone, Default, Debug)]
+pub(crate) struct StyleText(pub(crate) Style);
+
+impl core::fmt::Display for StyleText {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let style = self.0;
+        let effects = style.get_effects();
+        let mut words = Separated::new(f);
+        for index in effects.index_iter() {
+            if !UNDERLINES.contains(Effects::from_index(index)) {
+                words.write(format_args!("{}", METADATA[index].text))?;
+            }
+        }
+        if let Some(fg) = style.get_fg_color() {
+            words.write(format_args!("{fg}"))?;
+        }
+        if let Some(bg) = style.get_bg_color() {
+            words.write(format_args!("on {bg}"))?;
+        }
+        let mut underline_color = style.get_underline_color();
+        for index in effects.index_iter() {
+            if UNDERLINES.contains(Effects::from_index(index)) {
+                let text = METADATA[index].text;
+                match underline_color.take() {
+                    Some(color) => words.write(format_args!("{text}:{color}"))?,
+                    None => words.write(format_args!("{text}"))?,
+                }
+            }
+        }
+        if let Some(color) = underline_color {
+            words.write(format_args!("underline:none:{color}"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse [`Effects`] from space-separated words
+///
+/// The words are `bold`, `dimmed`, `italic`, `underline`, `underline:double`, `underline:curly`,
+/// `underline:dotted`, `underline:dashed`, `blink`, `invert`, `hidden`, `strikethrough`,
+/// `overline`, `rapid_blink`, `framed`, `encircled`, `superscript`, `subscript`, and
+/// `proportional_spacing`, matched case-insensitively.
+///
+/// # Examples
+///
+/// ```rust
+/// let effects: anstyle::Effects = "bold underline:curly".parse().unwrap();
+/// assert_eq!(effects, anstyle::Effects::BOLD | anstyle::Effects::CURLY_UNDERLINE);
+/// assert_eq!(effects.to_string(), "bold underline:curly");
+/// ```
+impl core::str::FromStr for Effects {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut effects = Self::new();
+        for (word, span) in Words::new(s) {
+            effects |= parse_effect(word)
+                .ok_or_else(|| ParseError::new(ParseErrorKind::UnknownEffect, span))?;
+        }
+        Ok(effects)
+    }
+}
+
+/// Write [`Effects`] as space-separated words, see [`Effects::from_str`][core::str::FromStr::from_str]
+impl core::fmt::Display for Effects {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut words = Separated::new(f);
+        for index in self.index_iter() {
+            words.write(format_args!("{}", METADATA[index].text))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a [`Color`]
+///
+/// Colors are one of:
+/// - A name for [`AnsiColor`], like `red` or `bright_red`, matched case-insensitively
+/// - An index for [`Ansi256Color`], like `208` or `ansi:208`
+/// - A hex code for [`RgbColor`], like `#ff8800`
+///
+/// # Examples
+///
+/// ```rust
+/// let color: anstyle::Color = "#ff8800".parse().unwrap();
+/// assert_eq!(color, anstyle::RgbColor(0xff, 0x88, 0x00).into());
+/// assert_eq!(color.to_string(), "#ff8800");
+/// ```
+impl core::str::FromStr for Color {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_color(s).ok_or_else(|| ParseError::new(ParseErrorKind::InvalidColor, 0..s.len()))
+    }
+}
+
+/// Write a [`Color`], see [`Color::from_str`][core::str::FromStr::from_str]
+impl core::fmt::Display for Color {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Ansi(color) => color.fmt(f),
+            Self::Ansi256(color) => color.fmt(f),
+            Self::Rgb(color) => color.fmt(f),
+        }
+    }
+}
+
+/// Write the name of an [`AnsiColor`], like `bright_red`
+impl core::fmt::Display for AnsiColor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (_, name) = ANSI_NAMES[*self as usize];
+        f.write_str(name)
+    }
+}
+
+/// Write the index of an [`Ansi256Color`], like `208`
+impl core::fmt::Display for Ansi256Color {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Write the hex code of an [`RgbColor`], like `#ff8800`
+impl core::fmt::Display for RgbColor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+fn parse_effect(word: &str) -> Option<Effects> {
+    METADATA
+        .iter()
+        .position(|metadata| metadata.text.eq_ignore_ascii_case(word))
+        .map(Effects::from_index)
+}
+
+fn parse_color(word: &str) -> Option<Color> {
+    if let Some(hex) = word.strip_prefix('#') {
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(RgbColor(channel(0)?, channel(2)?, channel(4)?).into())
+    } else if let Some(index) = parse_index(word) {
+        Some(Ansi256Color(index).into())
+    } else if let Some(index) = strip_prefix_ignore_ascii_case(word, "ansi:") {
+        parse_index(index).map(|index| Ansi256Color(index).into())
+    } else {
+        ANSI_NAMES
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(word))
+            .map(|(color, _)| (*color).into())
+    }
+}
+
+/// Split `underline[:KIND]:COLOR`, keeping an `ansi:INDEX` color whole
+fn split_underline(word: &str) -> Option<(&str, &str)> {
+    let (underline, color) = word.rsplit_once(':')?;
+    match underline.rsplit_once(':') {
+        Some((head, ansi)) if ansi.eq_ignore_ascii_case("ansi") => {
+            Some((head, &word[head.len() + 1..]))
+        }
+        _ => Some((underline, color)),
+    }
+}
+
+fn parse_index(word: &str) -> Option<u8> {
+    if !word.is_empty() && word.bytes().all(|b| b.is_ascii_digit()) {
+        word.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn strip_prefix_ignore_ascii_case<'s>(s: &'s str, prefix: &str) -> Option<&'s str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &s[prefix.len()..])
+}
+
+/// Split on ASCII whitespace, tracking where each word is
+struct Words<'s> {
+    s: &'s str,
+    offset: usize,
+}
+
+impl<'s> Words<'s> {
+    fn new(s: &'s str) -> Self {
+        Self { s, offset: 0 }
+    }
+}
+
+impl<'s> Iterator for Words<'s> {
+    type Item = (&'s str, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.s[self.offset..];
+        let start = self.offset + rest.find(|c: char| !c.is_ascii_whitespace())?;
+        let rest = &self.s[start..];
+        let end = start
+            + rest
+                .find(|c: char| c.is_ascii_whitespace())
+                .unwrap_or(rest.len());
+        self.offset = end;
+        Some((&self.s[start..end], start..end))
+    }
+}
+
+/// Write space-separated words
+struct Separated<'f, 'a> {
+    f: &'f mut core::fmt::Formatter<'a>,
+    first: bool,
+}
+
+impl<'f, 'a> Separated<'f, 'a> {
+    fn new(f: &'f mut core::fmt::Formatter<'a>) -> Self {
+        Self { f, first: true }
+    }
+
+    fn write(&mut self, word: core::fmt::Arguments<'_>) -> core::fmt::Result {
+        if !self.first {
+            self.f.write_str(" ")?;
+        }
+        self.first = false;
+        self.f.write_fmt(word)
+    }
+}
+
+/// Failed to parse a [`Style`], [`Color`], or [`Effects`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    span: Range<usize>,
+}
+
+impl ParseError {
+    fn new(kind: ParseErrorKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+
+    /// What went wrong
+    #[inline]
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Byte range of the offending word in the input
+    #[inline]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at {}..{}", self.kind, self.span.start, self.span.end)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// See [`ParseError::kind`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// Not an effect or a color
+    UnknownWord,
+    /// Not an effect
+    UnknownEffect,
+    /// Not a color
+    InvalidColor,
+    /// `on` without a color after it
+    MissingColor,
+    /// The foreground, background, or underline color was given more than once
+    DuplicateColor,
+}
+
+impl core::fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownWord => f.write_str("unknown effect or color"),
+            Self::UnknownEffect => f.write_str("unknown effect"),
+            Self::InvalidColor => f.write_str("invalid color"),
+            Self::MissingColor => f.write_str("expected a color after `on`"),
+            Self::DuplicateColor => f.write_str("color is already set"),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod test {
+    use super::*;
+
+    #[track_caller]
+    fn assert_round_trip(style: Style) {
+        let text = style.describe().to_string();
+        let actual = text.parse::<Style>();
+        assert_eq!(actual, Ok(style), "{text:?}");
+    }
+
+    #[test]
+    fn style_round_trips() {
+        let all = (0..METADATA.len())
+            .map(Effects::from_index)
+            .fold(Effects::new(), |effects, effect| effects | effect);
+        let pairs = (0..METADATA.len()).flat_map(|i| {
+            (0..METADATA.len()).map(move |j| Effects::from_index(i) | Effects::from_index(j))
+        });
+        for effects in pairs.chain([Effects::new(), all]) {
+            assert_round_trip(Style::new().effects(effects));
+            assert_round_trip(
+                Style::new()
+                    .effects(effects)
+                    .underline_color(Some(Ansi256Color(208).into())),
+            );
+        }
+
+        let colors = ANSI_NAMES
+            .iter()
+            .map(|(color, _)| Color::from(*color))
+            .chain([Ansi256Color(0).into(), Ansi256Color(255).into()])
+            .chain([RgbColor(0, 0x80, 0xff).into()]);
+        for color in colors {
+            assert_round_trip(Style::new().fg_color(Some(color)));
+            assert_round_trip(Style::new().bg_color(Some(color)));
+            assert_round_trip(Style::new().underline_color(Some(color)));
+        }
+    }
+
+    #[test]
+    fn style_describe() {
+        let style = Style::new()
+            .bold()
+            .underline()
+            .fg_color(Some(AnsiColor::BrightRed.into()))
+            .bg_color(Some(Ansi256Color(16).into()))
+            .underline_color(Some(RgbColor(0, 0x80, 0xff).into()));
+        assert_eq!(
+            style.describe().to_string(),
+            "bold bright_red on 16 underline:#0080ff"
+        );
+        assert_eq!(Style::new().describe().to_string(), "");
+        assert_eq!(
+            Style::new()
+                .underline_color(Some(AnsiColor::Red.into()))
+                .describe()
+                .to_string(),
+            "underline:none:red"
+        );
+    }
+
+    #[test]
+    fn style_parse() {
+        assert_eq!(" ".parse::<Style>(), Ok(Style::new()));
+        assert_eq!(
+            "  BOLD\tOn  Red ".parse::<Style>(),
+            Ok(Style::new().bold().bg_color(Some(AnsiColor::Red.into())))
+        );
+        assert_eq!(
+            "underline:red underline:double".parse::<Style>(),
+            Ok(Style::new()
+                .effects(Effects::UNDERLINE | Effects::DOUBLE_UNDERLINE)
+                .underline_color(Some(AnsiColor::Red.into())))
+        );
+    }
+
+    #[test]
+    fn color_parse() {
+        for (input, expected) in [
+            ("Bright_Red", Color::from(AnsiColor::BrightRed)),
+            ("208", Ansi256Color(208).into()),
+            ("ANSI:208", Ansi256Color(208).into()),
+            ("#FF8800", RgbColor(0xff, 0x88, 0x00).into()),
+        ] {
+            assert_eq!(input.parse::<Color>(), Ok(expected), "{input:?}");
+        }
+        assert_eq!(
+            "ansi:1 on ansi:2 underline:dotted:ansi:3".parse::<Style>(),
+            Ok(Style::new()
+                .fg_color(Some(Ansi256Color(1).into()))
+                .bg_color(Some(Ansi256Color(2).into()))
+                .effects(Effects::DOTTED_UNDERLINE)
+                .underline_color(Some(Ansi256Color(3).into())))
+        );
+    }
+
+    #[test]
+    fn style_errors() {
+        for (input, kind, span) in [
+            ("bold purple", ParseErrorKind::UnknownWord, 5..11),
+            ("bold on", ParseErrorKind::MissingColor, 5..7),
+            ("on #12345", ParseErrorKind::InvalidColor, 3..9),
+            ("red blue", ParseErrorKind::DuplicateColor, 4..8),
+            ("on red on blue", ParseErrorKind::DuplicateColor, 10..14),
+            (
+                "underline:red underline:curly:blue",
+                ParseErrorKind::DuplicateColor,
+                14..34,
+            ),
+            ("underline:wavy:red", ParseErrorKind::UnknownEffect, 0..18),
+            ("bold:red", ParseErrorKind::UnknownEffect, 0..8),
+            ("underline:256", ParseErrorKind::InvalidColor, 0..13),
+            ("underline:none", ParseErrorKind::InvalidColor, 0..14),
+            ("+1", ParseErrorKind::UnknownWord, 0..2),
+            ("ansi:256", ParseErrorKind::InvalidColor, 0..8),
+            ("underline:ansi:256", ParseErrorKind::InvalidColor, 0..18),
+            (
+                "underline:wavy:ansi:1",
+                ParseErrorKind::UnknownEffect,
+                0..21,
+            ),
+        ] {
+            let actual = input.parse::<Style>().unwrap_err();
+            assert_eq!(actual, ParseError::new(kind, span), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn effects_errors() {
+        let actual = "bold red".parse::<Effects>().unwrap_err();
+        assert_eq!(actual.to_string(), "unknown effect at 5..8");
+    }
+
+    #[test]
+    fn color_errors() {
+        for input in [
+            "",
+            "#ff880",
+            "#ff880g",
+            "256",
+            "ansi:",
+            "ansi:256",
+            "bright-red",
+            " red",
+        ] {
+            let actual = input.parse::<Color>().unwrap_err();
+            assert_eq!(
+                actual,
+                ParseError::new(ParseErrorKind::InvalidColor, 0..input.len()),
+                "{input:?}"
+            );
+        }
+    }
+}
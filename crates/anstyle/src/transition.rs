@@ -0,0 +1,356 @@
+use crate::effect::METADATA;
+use crate::Effects;
+use crate::Style;
+
+/// See [`Style::render_transition`]
+#[derive(Copy, Clone, Default, Debug)]
+pub(crate) struct StyleTransition {
+    pub(crate) from: Style,
+    pub(crate) to: Style,
+}
+
+impl StyleTransition {
+    /// Write the SGR parameters for changing only what differs
+    fn write_changes(&self, params: &mut Params<'_>) -> core::fmt::Result {
+        let (from, to) = (self.from, self.to);
+
+        let added = to.get_effects() - from.get_effects();
+        let removed = from.get_effects() - to.get_effects();
+        let mut offs = Effects::new();
+        for index in removed.index_iter() {
+            let off = METADATA[index].off;
+            if !offs.index_iter().any(|i| METADATA[i].off == off) {
+                params.next()?.write_str(sgr_params(off))?;
+                offs |= Effects::from_index(index);
+            }
+        }
+        for index in to.get_effects().index_iter() {
+            let off = METADATA[index].off;
+            // Effects sharing an off code are re-added when turned off together and re-applied
+            // when a preceding one is added, so the last one still wins, like with `render`
+            if added.contains(Effects::from_index(index))
+                || offs.index_iter().any(|i| METADATA[i].off == off)
+                || added
+                    .index_iter()
+                    .any(|i| i < index && METADATA[i].off == off)
+            {
+                params
+                    .next()?
+                    .write_str(sgr_params(METADATA[index].escape))?;
+            }
+        }
+
+        if from.get_fg_color() != to.get_fg_color() {
+            match to.get_fg_color() {
+                Some(fg) => fg.write_fg_params(params.next()?)?,
+                None => params.next()?.write_str("39")?,
+            }
+        }
+        if from.get_bg_color() != to.get_bg_color() {
+            match to.get_bg_color() {
+                Some(bg) => bg.write_bg_params(params.next()?)?,
+                None => params.next()?.write_str("49")?,
+            }
+        }
+        if from.get_underline_color() != to.get_underline_color() {
+            match to.get_underline_color() {
+                Some(underline) => underline.write_underline_params(params.next()?)?,
+                None => params.next()?.write_str("59")?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the SGR parameters for resetting and then applying the new style
+    fn write_reset(&self, params: &mut Params<'_>) -> core::fmt::Result {
+        params.next()?.write_str("0")?;
+        Self {
+            from: Style::new(),
+            to: self.to,
+        }
+        .write_changes(params)
+    }
+}
+
+impl core::fmt::Display for StyleTransition {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.from == self.to {
+            return Ok(());
+        }
+
+        let mut changes_len = Counter(0);
+        self.write_changes(&mut Params::new(&mut changes_len))?;
+        let mut reset_len = Counter(0);
+        self.write_reset(&mut Params::new(&mut reset_len))?;
+
+        f.write_str("\x1B[")?;
+        if changes_len.0 <= reset_len.0 {
+            self.write_changes(&mut Params::new(f))?;
+        } else {
+            self.write_reset(&mut Params::new(f))?;
+        }
+        f.write_str("m")
+    }
+}
+
+/// Strip the `\x1B[` and `m` from a single SGR escape
+fn sgr_params(escape: &'static str) -> &'static str {
+    &escape[2..escape.len() - 1]
+}
+
+/// Write `;`-separated SGR parameters
+struct Params<'w> {
+    write: &'w mut dyn core::fmt::Write,
+    first: bool,
+}
+
+impl<'w> Params<'w> {
+    fn new(write: &'w mut dyn core::fmt::Write) -> Self {
+        Self { write, first: true }
+    }
+
+    /// Start the next parameter
+    fn next(&mut self) -> Result<&mut dyn core::fmt::Write, core::fmt::Error> {
+        if !self.first {
+            self.write.write_str(";")?;
+        }
+        self.first = false;
+        Ok(&mut *self.write)
+    }
+}
+
+/// Measure the length of what would be written
+struct Counter(usize);
+
+impl core::fmt::Write for Counter {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod test {
+    use super::*;
+    use crate::Ansi256Color;
+    use crate::AnsiColor;
+    use crate::RgbColor;
+
+    #[track_caller]
+    fn assert_transition(from: Style, to: Style, expected: &str) {
+        let actual = from.render_transition(to).to_string();
+        assert_eq!(actual, expected, "{from:?} -> {to:?}");
+    }
+
+    #[test]
+    fn unchanged() {
+        let style = Style::new().bold().fg_color(Some(AnsiColor::Red.into()));
+        assert_transition(style, style, "");
+        assert_transition(Style::new(), Style::new(), "");
+    }
+
+    #[test]
+    fn from_plain() {
+        let style = Style::new()
+            .bold()
+            .fg_color(Some(AnsiColor::Red.into()))
+            .bg_color(Some(Ansi256Color(208).into()));
+        assert_transition(Style::new(), style, "\x1B[1;31;48;5;208m");
+    }
+
+    #[test]
+    fn to_plain() {
+        let style = Style::new().bold().fg_color(Some(AnsiColor::Red.into()));
+        assert_transition(style, Style::new(), "\x1B[0m");
+    }
+
+    #[test]
+    fn change_color() {
+        let from = Style::new()
+            .bold()
+            .fg_color(Some(AnsiColor::Red.into()))
+            .bg_color(Some(RgbColor(1, 2, 3).into()));
+        assert_transition(
+            from,
+            from.fg_color(Some(AnsiColor::Green.into())),
+            "\x1B[32m",
+        );
+        assert_transition(from, from.fg_color(None), "\x1B[39m");
+        assert_transition(
+            from,
+            from.underline_color(Some(AnsiColor::Blue.into())),
+            "\x1B[58;5;4m",
+        );
+    }
+
+    #[test]
+    fn turn_off_effect() {
+        let from = Style::new()
+            .italic()
+            .strikethrough()
+            .fg_color(Some(RgbColor(1, 2, 3).into()));
+        assert_transition(from, from.effects(Effects::STRIKETHROUGH), "\x1B[23m");
+        assert_transition(from, from.effects(Effects::ITALIC), "\x1B[29m");
+    }
+
+    #[test]
+    fn shared_off_code() {
+        let from = Style::new()
+            .bold()
+            .dimmed()
+            .fg_color(Some(RgbColor(1, 2, 3).into()));
+        assert_transition(from, from.effects(Effects::DIMMED), "\x1B[22;2m");
+
+        let from = Style::new()
+            .underline()
+            .fg_color(Some(RgbColor(1, 2, 3).into()));
+        assert_transition(from, from.effects(Effects::CURLY_UNDERLINE), "\x1B[24;4:3m");
+    }
+
+    #[test]
+    fn prefer_reset_when_shorter() {
+        let from = Style::new()
+            .bold()
+            .italic()
+            .underline()
+            .fg_color(Some(AnsiColor::Red.into()))
+            .bg_color(Some(AnsiColor::Blue.into()));
+        assert_transition(
+            from,
+            Style::new().fg_color(Some(AnsiColor::Green.into())),
+            "\x1B[0;32m",
+        );
+    }
+
+    #[test]
+    fn applies_like_reset_and_render() {
+        let styles = [
+            Style::new(),
+            Style::new().bold(),
+            Style::new().dimmed().italic(),
+            Style::new().bold().dimmed(),
+            Style::new().underline(),
+            Style::new().effects(Effects::UNDERLINE | Effects::DASHED_UNDERLINE),
+            Style::new().effects(Effects::DASHED_UNDERLINE),
+            Style::new()
+                .effects(Effects::DOUBLE_UNDERLINE)
+                .underline_color(Some(AnsiColor::Red.into())),
+            Style::new().blink().invert().hidden().strikethrough(),
+            Style::new().blink().overline(),
+            Style::new().effects(Effects::RAPID_BLINK | Effects::FRAMED),
+            Style::new().effects(Effects::ENCIRCLED | Effects::SUPERSCRIPT),
+            Style::new().effects(Effects::SUBSCRIPT | Effects::PROPORTIONAL_SPACING),
+            Style::new().fg_color(Some(AnsiColor::Red.into())),
+            Style::new()
+                .fg_color(Some(RgbColor(255, 255, 255).into()))
+                .bg_color(Some(Ansi256Color(16).into())),
+            Style::new().bold().bg_color(Some(AnsiColor::Red.into())),
+        ];
+        for from in styles {
+            for to in styles {
+                let actual = from.render_transition(to).to_string();
+                let full = format!("{}{}", from.render_reset(), to.render());
+                assert!(
+                    actual.len() <= full.len(),
+                    "{from:?} -> {to:?}: {actual:?} is longer than {full:?}"
+                );
+                assert_eq!(
+                    apply(apply(State::default(), &from.render().to_string()), &actual),
+                    apply(State::default(), &to.render().to_string()),
+                    "{from:?} -> {to:?}: {actual:?}"
+                );
+            }
+        }
+    }
+
+    /// Terminal state for the SGR codes used here
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    struct State {
+        effects: alloc::collections::BTreeSet<&'static str>,
+        underline: Option<&'static str>,
+        fg: Option<String>,
+        bg: Option<String>,
+        underline_color: Option<String>,
+    }
+
+    fn apply(mut state: State, escapes: &str) -> State {
+        for escape in escapes.split('\x1B').filter(|e| !e.is_empty()) {
+            let params = escape.strip_prefix('[').unwrap().strip_suffix('m').unwrap();
+            let mut params = params.split(';');
+            while let Some(param) = params.next() {
+                let mut color = |kind: &str| -> String {
+                    let count = if params.next() == Some("5") { 1 } else { 3 };
+                    let value = (0..count)
+                        .map(|_| params.next().unwrap())
+                        .collect::<Vec<_>>();
+                    format!("{kind}:{}", value.join(","))
+                };
+                match param {
+                    "0" => state = State::default(),
+                    "1" => _ = state.effects.insert("bold"),
+                    "2" => _ = state.effects.insert("dimmed"),
+                    "3" => _ = state.effects.insert("italic"),
+                    "5" => _ = state.effects.insert("blink"),
+                    "7" => _ = state.effects.insert("invert"),
+                    "8" => _ = state.effects.insert("hidden"),
+                    "9" => _ = state.effects.insert("strikethrough"),
+                    "53" => _ = state.effects.insert("overline"),
+                    "6" => _ = state.effects.insert("rapid_blink"),
+                    "51" => _ = state.effects.insert("framed"),
+                    "52" => _ = state.effects.insert("encircled"),
+                    "73" => _ = state.effects.insert("superscript"),
+                    "74" => _ = state.effects.insert("subscript"),
+                    "26" => _ = state.effects.insert("proportional_spacing"),
+                    "4" | "21" | "4:3" | "4:4" | "4:5" => {
+                        state.underline = Some(
+                            METADATA
+                                .iter()
+                                .find(|m| sgr_params(m.escape) == param)
+                                .unwrap()
+                                .name,
+                        );
+                    }
+                    "22" => {
+                        state.effects.remove("bold");
+                        state.effects.remove("dimmed");
+                    }
+                    "23" => _ = state.effects.remove("italic"),
+                    "24" => state.underline = None,
+                    "25" => {
+                        state.effects.remove("blink");
+                        state.effects.remove("rapid_blink");
+                    }
+                    "27" => _ = state.effects.remove("invert"),
+                    "28" => _ = state.effects.remove("hidden"),
+                    "29" => _ = state.effects.remove("strikethrough"),
+                    "55" => _ = state.effects.remove("overline"),
+                    "54" => {
+                        state.effects.remove("framed");
+                        state.effects.remove("encircled");
+                    }
+                    "75" => {
+                        state.effects.remove("superscript");
+                        state.effects.remove("subscript");
+                    }
+                    "50" => _ = state.effects.remove("proportional_spacing"),
+                    "38" => state.fg = Some(color("extended")),
+                    "48" => state.bg = Some(color("extended")),
+                    "58" => state.underline_color = Some(color("extended")),
+                    "39" => state.fg = None,
+                    "49" => state.bg = None,
+                    "59" => state.underline_color = None,
+                    fg if fg.len() == 2 && (fg.starts_with('3') || fg.starts_with('9')) => {
+                        state.fg = Some(fg.to_owned());
+                    }
+                    bg if bg.starts_with('4') || bg.starts_with("10") => {
+                        state.bg = Some(bg.to_owned());
+                    }
+                    _ => panic!("unexpected parameter {param:?} in {escapes:?}"),
+                }
+            }
+        }
+        state
+    }
+}